@@ -18,16 +18,11 @@ impl PrettyErrorHandler {
     pub fn new(name: String, source: String) -> Self {
         Self { name, source }
     }
-}
-impl ErrorHandler for PrettyErrorHandler {
-    fn on_error(&self, err: CompilationError) {
+    fn emit(&self, diagnostic: Diagnostic<usize>, err: CompilationError) {
         let mut files = SimpleFiles::new();
         let default_vue = files.add(&self.name, &self.source);
-        let diagnostic = Diagnostic::error().with_labels(vec![Label::primary(
-            default_vue,
-            err.location.clone(),
-        )
-        .with_message(format!("{}", err))]);
+        let label = Label::primary(default_vue, err.location.clone());
+        let diagnostic = diagnostic.with_labels(vec![label.with_message(format!("{}", err))]);
 
         let writer = StandardStream::stderr(ColorChoice::Always);
         let config = codespan_reporting::term::Config::default();
@@ -36,3 +31,11 @@ impl ErrorHandler for PrettyErrorHandler {
             .expect("unable to generate codespan diagnostic");
     }
 }
+impl ErrorHandler for PrettyErrorHandler {
+    fn on_error(&self, err: CompilationError) {
+        self.emit(Diagnostic::error(), err);
+    }
+    fn on_warn(&self, warning: CompilationError) {
+        self.emit(Diagnostic::warning(), warning);
+    }
+}
@@ -1,9 +1,11 @@
 mod code_writer;
+mod ssr_writer;
 
 use crate::converter::BaseRoot;
 use crate::SFCInfo;
 use crate::ir::{self as C, ConvertInfo, IRNode, IRRoot};
 use code_writer::CodeWriter;
+use ssr_writer::SsrCodeWriter;
 
 use smallvec::{smallvec, SmallVec};
 use std::marker::PhantomData;
@@ -51,6 +53,8 @@ pub struct CodeGenerateOption {
     pub mode: ScriptMode,
     pub source_map: bool,
     pub helper_strs: &'static [&'static str],
+    /// Generate ssrRender function that pushes html strings
+    pub ssr: bool,
 }
 impl CodeGenerateOption {
     fn use_with_scope(&self) -> bool {
@@ -72,6 +76,7 @@ impl Default for CodeGenerateOption {
             },
             source_map: false,
             helper_strs: &[],
+            ssr: false,
         }
     }
 }
@@ -90,6 +95,8 @@ pub trait CoreCodeGenerator<T: ConvertInfo> {
             IR::AlterableSlot(a) => self.generate_alterable_slot(a),
             IR::CacheNode(cache) => self.generate_cache(cache),
             IR::CommentCall(c) => self.generate_comment(c),
+            IR::StaticCall(s) => self.generate_static(s),
+            IR::Hoisted(h) => self.generate_hoisted(h),
         }
     }
     fn generate_prologue(&mut self, t: &mut IRRoot<T>) -> Self::Written;
@@ -104,6 +111,8 @@ pub trait CoreCodeGenerator<T: ConvertInfo> {
     fn generate_cache(&mut self, c: C::CacheIR<T>) -> Self::Written;
     fn generate_js_expr(&mut self, e: T::JsExpression) -> Self::Written;
     fn generate_comment(&mut self, c: T::CommentType) -> Self::Written;
+    fn generate_static(&mut self, s: C::StaticIR) -> Self::Written;
+    fn generate_hoisted(&mut self, h: T::HoistedType) -> Self::Written;
 }
 
 pub struct CodeGen<T: ioWrite> {
@@ -130,6 +139,10 @@ impl<T: ioWrite> CodeGenerator for CodeGen<T> {
     type Output = io::Result<()>;
 
     fn generate<'a>(&self, root: BaseRoot<'a>, info: Self::Info<'a>) -> Self::Output {
+        if self.option.ssr {
+            let mut imp = SsrCodeWriter::new(info.writer, self.option.clone(), info.sfc_info);
+            return imp.generate_root(root);
+        }
         let mut imp = CodeWriter::new(info.writer, self.option.clone(), info.sfc_info);
         imp.generate_root(root)
             .map_err(|_| imp.writer.get_io_error())
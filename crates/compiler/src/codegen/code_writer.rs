@@ -1,7 +1,7 @@
 use super::{CodeGenerateOption, ScriptMode, CoreCodeGenerator};
 use crate::flags::{HelperCollector, PatchFlag, RuntimeHelper as RH, SlotFlag};
 use crate::converter::v_on::get_handler_type;
-use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot, TopScope};
+use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot, EntityCounter, TopScope};
 use crate::transformer::{
     BaseFor, BaseIf, BaseRenderSlot, BaseSlotFn, BaseText, BaseVNode, BaseVSlot, BaseCache,
 };
@@ -56,7 +56,7 @@ pub struct CodeWriter<'a, T: ioWrite> {
     sfc_info: &'a SFCInfo<'a>,
     indent_level: usize,
     closing_brackets: usize,
-    cache_count: usize,
+    counter: EntityCounter,
     in_alterable: bool,
     helpers: HelperCollector,
 }
@@ -68,11 +68,32 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             sfc_info,
             indent_level: 0,
             closing_brackets: 0,
-            cache_count: 0,
+            counter: EntityCounter::default(),
             in_alterable: false,
             helpers: Default::default(),
         }
     }
+    /// Allocates a slot in `_cache` in document order.
+    fn next_cache_index(&mut self) -> usize {
+        self.counter.next_cache()
+    }
+}
+
+impl<'a> CodeWriter<'a, Vec<u8>> {
+    /// Generates a standalone expression, used by SSR codegen
+    /// to interpolate expressions inside template literal.
+    pub(super) fn expr_to_string(
+        expr: Js<'a>,
+        option: Rc<CodeGenerateOption>,
+        sfc_info: &'a SFCInfo<'a>,
+        helpers: HelperCollector,
+    ) -> String {
+        let mut gen = Self::new(vec![], option, sfc_info);
+        gen.helpers = helpers;
+        gen.generate_js_expr(expr)
+            .expect("writing to vec should never fail");
+        String::from_utf8(gen.writer.inner).expect("expression should be utf8")
+    }
 }
 
 impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T> {
@@ -140,8 +161,8 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         if self.in_alterable {
             return self.generate_render_list(f);
         }
-        // write open block
-        self.gen_open_block(f.is_stable, move |gen| {
+        // unstable fragment disables tracking so items are collected as blocks
+        self.gen_open_block(!f.is_stable, move |gen| {
             gen.write_helper(RH::CREATE_ELEMENT_BLOCK)?;
             gen.write_str("(")?;
             gen_v_for_args(gen, f)?;
@@ -182,21 +203,23 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
     }
     fn generate_cache(&mut self, c: BaseCache<'a>) -> Self::Written {
         use C::CacheKind as CK;
+        // reserve index before children so nested caches get later ones
+        let index = self.next_cache_index();
         match c.kind {
             CK::Once => {
-                write!(self.writer, "_cache[{}] || (", self.cache_count)?;
+                write!(self.writer, "_cache[{}] || (", index)?;
                 self.indent()?;
                 self.write_helper(RH::SET_BLOCK_TRACKING)?;
                 self.write_str("(-1),")?;
                 self.newline()?;
-                write!(self.writer, "_cache[{}] = ", self.cache_count)?;
+                write!(self.writer, "_cache[{}] = ", index)?;
                 self.generate_ir(*c.child)?;
                 self.write_str(",")?;
                 self.newline()?;
                 self.write_helper(RH::SET_BLOCK_TRACKING)?;
                 self.write_str("(1),")?;
                 self.newline()?;
-                write!(self.writer, "_cache[{}]", self.cache_count)?;
+                write!(self.writer, "_cache[{}]", index)?;
                 self.deindent()?;
                 self.write_str(")")?;
             }
@@ -206,7 +229,7 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
                 self.generate_js_expr(expr)?;
                 self.write_str(", () => ")?;
                 self.generate_ir(*c.child)?;
-                write!(self.writer, ", _cache, {})", self.cache_count)?;
+                write!(self.writer, ", _cache, {})", index)?;
             }
             CK::MemoInVFor { expr, v_for_key } => {
                 self.write_str("const _memo=(")?;
@@ -230,7 +253,6 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
                 self.write_str("return _item")?;
             }
         }
-        self.cache_count += 1;
         Ok(())
     }
     fn generate_js_expr(&mut self, expr: Js<'a>) -> Output {
@@ -238,6 +260,10 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
             Js::Src(s) | Js::Param(s) => self.write_str(s),
             Js::Num(n) => write!(self.writer, "{}", n),
             Js::StrLit(mut l) => l.be_js_str().write_to(&mut self.writer),
+            Js::Joined { text, quoted: true } => {
+                VStr::raw(&text).be_js_str().write_to(&mut self.writer)
+            }
+            Js::Joined { text, .. } => self.write_str(&text),
             Js::Simple(e, _) => e.write_to(&mut self.writer),
             Js::Symbol(s) => self.write_helper(s),
             Js::Props(p) => self.gen_obj_props(p, |gen, v| gen.generate_js_expr(v)),
@@ -294,23 +320,48 @@ impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for CodeWriter<'a, T
         let call = Js::Call(RH::CREATE_COMMENT, vec![comment]);
         self.generate_js_expr(call)
     }
+    fn generate_static(&mut self, s: C::StaticIR) -> Output {
+        self.write_helper(RH::CREATE_STATIC)?;
+        self.write_str("(")?;
+        VStr::raw(&s.content)
+            .be_js_str()
+            .write_to(&mut self.writer)?;
+        write!(self.writer, ", {})", s.count)
+    }
+    fn generate_hoisted(&mut self, index: usize) -> Output {
+        write!(self.writer, "_hoisted_{}", index + 1)
+    }
 }
 
 impl<'a, T: ioWrite> CodeWriter<'a, T> {
     pub fn generate_root(&mut self, mut root: BaseRoot<'a>) -> Output {
         // get top scope entities
         self.helpers = root.top_scope.helpers.clone();
+        // continue indices allocated before codegen
+        self.counter = root.top_scope.counter;
 
         self.generate_prologue(&mut root)?;
         if root.body.is_empty() {
             self.write_str("null")?;
         } else {
-            let ir = if root.body.len() == 1 {
+            let ir = if !root.is_fragment && root.body.len() == 1 {
                 root.body.pop().unwrap()
             } else {
+                let mut patch_flag = PatchFlag::STABLE_FRAGMENT;
+                // one real root wrapped with comments in dev build
+                let non_comment = root
+                    .body
+                    .iter()
+                    .filter(|n| !matches!(n, IRNode::CommentCall(_)))
+                    .count();
+                if self.option.is_dev && non_comment == 1 {
+                    patch_flag |= PatchFlag::DEV_ROOT_FRAGMENT;
+                }
                 IRNode::VNodeCall(VNodeIR {
                     tag: Js::Symbol(RH::FRAGMENT),
                     children: root.body,
+                    patch_flag,
+                    is_block: true,
                     ..VNodeIR::default()
                 })
             };
@@ -368,10 +419,11 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         self.gen_imports(top)?;
         self.gen_hoist(top)?;
         self.newline()?;
+        // inline render function is returned from setup() instead
         if self.sfc_info.inline {
-            self.write_str("export ")
-        } else {
             Ok(())
+        } else {
+            self.write_str("export ")
         }
     }
     fn gen_helper_import(&mut self, helpers: HelperCollector, from: &str) -> Output {
@@ -412,9 +464,18 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         std::mem::swap(&mut imports, &mut top.imports);
         for impt in imports {
             self.write_str("import ")?;
-            self.generate_js_expr(impt.exp)?;
-            self.write_str(" from ")?;
-            self.write_str(impt.path)?;
+            if let Some(name) = impt.name {
+                self.write_str("{ ")?;
+                self.write_str(name)?;
+                self.write_str(" as ")?;
+                self.generate_js_expr(impt.exp)?;
+                self.write_str(" }")?;
+            } else {
+                self.generate_js_expr(impt.exp)?;
+            }
+            self.write_str(" from \"")?;
+            self.write_str(&impt.path)?;
+            self.write_str("\"")?;
             self.newline()?;
         }
         Ok(())
@@ -429,7 +490,7 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             self.write_str("const _withScopeId = n => (")?;
             self.write_helper(RH::PUSH_SCOPE_ID)?;
             let scope_id = self.sfc_info.scope_id.as_ref().unwrap();
-            write!(self.writer, "({}),n=n(),", scope_id)?;
+            write!(self.writer, "(\"{}\"),n=n(),", scope_id)?;
             self.write_helper(RH::POP_SCOPE_ID)?;
             self.write_str("(),n)")?;
             self.newline()?;
@@ -444,7 +505,7 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
             } else {
                 ""
             };
-            write!(self.writer, "const _hoisted_{} = {}", i, wrapper)?;
+            write!(self.writer, "const _hoisted_{} = {}", i + 1, wrapper)?;
             self.generate_ir(hoist)?;
             if scope_id_wrapper {
                 self.write_str(")")?;
@@ -461,11 +522,16 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         } else {
             "_ctx, _cache"
         };
-        // NB: vue uses arrow func for inline mode.
-        // but it makes no diff in Vue runtime implementation?
-        self.write_str("function render(")?;
-        self.write_str(args)?;
-        self.write_str(") {")?;
+        if option.inline {
+            // inline mode returns an arrow closure from setup()
+            self.write_str("(")?;
+            self.write_str(args)?;
+            self.write_str(") => {")?;
+        } else {
+            self.write_str("function render(")?;
+            self.write_str(args)?;
+            self.write_str(") {")?;
+        }
         self.closing_brackets += 1;
         self.indent()
     }
@@ -501,6 +567,10 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
     }
 
     fn gen_concate_str(&mut self, t: SmallVec<[Js<'a>; 1]>) -> Output {
+        // pure static run is written as one plain string
+        if t.len() > 1 && C::is_str_lit_compound(&t) {
+            return self.generate_js_expr(C::fold_constant(Js::Compound(t.into_vec())));
+        }
         let mut texts = t.into_iter();
         match texts.next() {
             Some(t) => self.generate_js_expr(t)?,
@@ -549,8 +619,10 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
         let mut params = vec![Some(p.value), p.key, p.index];
         if has_memo {
             params.push(Some(Js::Src("_cached")));
+            // the memo cache node takes the next index
+            let index = self.counter.caches;
             self.gen_func_expr(params, *f.child, /*need_return*/ false)?;
-            write!(self.writer, ", _cache, {}", self.cache_count - 1)?;
+            write!(self.writer, ", _cache, {}", index)?;
         } else {
             self.gen_func_expr(params, *f.child, /*need_return*/ true)?;
         }
@@ -715,10 +787,16 @@ impl<'a, T: ioWrite> CodeWriter<'a, T> {
     }
     #[inline(always)]
     fn write_patch(&mut self, flag: PatchFlag) -> Output {
-        if self.option.is_dev {
-            write!(self.writer, "{} /*{:?}*/", flag.bits(), flag)
+        if !self.option.is_dev {
+            return write!(self.writer, "{}", flag.bits());
+        }
+        // special flags are negative and must not be printed as bit sets
+        if flag == PatchFlag::HOISTED {
+            write!(self.writer, "{} /*HOISTED*/", flag.bits())
+        } else if flag == PatchFlag::BAIL {
+            write!(self.writer, "{} /*BAIL*/", flag.bits())
         } else {
-            write!(self.writer, "{}", flag.bits())
+            write!(self.writer, "{} /*{:?}*/", flag.bits(), flag)
         }
     }
 }
@@ -734,7 +812,8 @@ where
     F: FnOnce(&mut CodeWriter<'a, T>) -> Output,
 {
     if cache {
-        write!(gen.writer, "_cache[{}] || (", gen.cache_count)?;
+        let index = gen.next_cache_index();
+        write!(gen.writer, "_cache[{}] || (", index)?;
     }
     match ty {
         HandlerType::FuncExpr => func(gen)?,
@@ -755,7 +834,6 @@ where
     }
     if cache {
         gen.write_str(")")?;
-        gen.cache_count += 1;
     }
     Ok(())
 }
@@ -943,11 +1021,12 @@ fn gen_assets<'a, T: ioWrite>(
         gen.write_str(" = ")?;
         gen.write_helper(resolver)?;
         gen.write_str("(")?;
-        let raw = if resolver == RH::RESOLVE_COMPONENT {
+        let mut raw = if resolver == RH::RESOLVE_COMPONENT {
             *asset.clone().unbe_component()
         } else {
             *asset.clone().unbe_directive()
         };
+        raw.be_js_str();
         raw.write_to(&mut gen.writer)?;
         gen.write_str(hint)?;
         gen.write_str(")")?;
@@ -1013,6 +1092,51 @@ mod test {
         assert!(s.contains("\"hello\" + _toDisplayString(world)"), "{}", s);
     }
     #[test]
+    fn test_static_text_run() {
+        let mut ir = base_convert("hello");
+        let hello = cast!(&mut ir.body[0], IRNode::TextCall);
+        hello.texts.push(Js::StrLit(VStr::raw(" world")));
+        let s = gen(ir, &SFCInfo::default());
+        assert!(s.contains(r#""hello world""#), "{}", s);
+        assert!(!s.contains(" + "), "{}", s);
+    }
+    fn optimized_gen(s: &str) -> String {
+        use crate::chain;
+        use crate::transformer::{
+            mark_patch_flag::PatchFlagMarker, optimize_text::TextOptimizer, BaseTransformer,
+            Transformer,
+        };
+        let mut ir = base_convert(s);
+        BaseTransformer::transform(&mut ir, chain![TextOptimizer, PatchFlagMarker]);
+        gen(ir, &SFCInfo::default())
+    }
+    #[test]
+    fn test_sole_text_child() {
+        let s = optimized_gen("<p>{{a}}</p>");
+        assert!(!s.contains("_createTextVNode"), "{}", s);
+        assert!(s.contains("_toDisplayString(a), 1"), "{}", s);
+        let s = optimized_gen("<p>static</p>");
+        assert!(!s.contains("_createTextVNode"), "{}", s);
+        assert!(s.contains(r#""p", null, "static")"#), "{}", s);
+    }
+    #[test]
+    fn test_mixed_text_children() {
+        let s = optimized_gen("<p>{{a}}<b/>c</p>");
+        assert!(
+            s.contains("_createTextVNode(_toDisplayString(a), 1"),
+            "{}",
+            s
+        );
+        assert!(s.contains(r#"_createTextVNode("c")"#), "{}", s);
+    }
+    #[test]
+    fn test_root_text() {
+        let s = optimized_gen("a<b/>");
+        assert!(s.contains(r#"_createTextVNode("a")"#), "{}", s);
+        let s = optimized_gen("{{a}}");
+        assert!(!s.contains("_createTextVNode"), "{}", s);
+    }
+    #[test]
     fn test_text_fast_path() {
         let mut ir = base_convert("hello");
         let hello = cast!(&mut ir.body[0], IRNode::TextCall);
@@ -1058,6 +1182,28 @@ mod test {
         assert!(s.contains(r#"prop: """#), "{}", s);
     }
     #[test]
+    fn test_component_source_spelling() {
+        // mimic collect_entities which registers the resolved tag
+        let gen_comp = |tpl| {
+            let mut ir = base_convert(tpl);
+            let vn = cast!(&ir.body[0], IRNode::VNodeCall);
+            let tag = *cast!(&vn.tag, Js::Simple);
+            ir.top_scope.components.insert(tag);
+            gen(ir, &SFCInfo::default())
+        };
+        let s = gen_comp("<Foo-bar/>");
+        assert!(s.contains(r#"_resolveComponent("Foo-bar")"#), "{}", s);
+        let s = gen_comp("<FooBar/>");
+        assert!(s.contains(r#"_resolveComponent("FooBar")"#), "{}", s);
+    }
+    #[test]
+    fn test_v_bind_dynamic_key() {
+        let s = base_gen("<p id='x' :[k]='v'/>");
+        assert!(s.contains(r#"id: "x","#), "{}", s);
+        assert!(s.contains("[(k) || '']: v,"), "{}", s);
+        assert!(s.contains("FULL_PROPS"), "{}", s);
+    }
+    #[test]
     fn test_v_bind_dir() {
         let s = base_gen("<p v-bind:prop='id'/>");
         assert!(s.contains("prop: id"), "{}", s);
@@ -1094,17 +1240,92 @@ mod test {
         assert!(s.contains("createCommentVNode"), "{}", s);
     }
 
+    #[test]
+    fn test_multi_root() {
+        let s = base_gen("<p/><p/>");
+        let fragment = "(_openBlock(), _createElementBlock(_Fragment, null, [";
+        assert!(s.contains(fragment), "{}", s);
+        assert!(s.contains("64 /*"), "{}", s);
+    }
+    #[test]
+    fn test_single_root() {
+        let s = base_gen("<p/>");
+        assert!(!s.contains("_Fragment"), "{}", s);
+        assert!(s.contains("(\"p\")"), "{}", s);
+    }
+    #[test]
+    fn test_root_text_and_element() {
+        let s = base_gen("a<p/>");
+        let fragment = "(_openBlock(), _createElementBlock(_Fragment, null, [";
+        assert!(s.contains(fragment), "{}", s);
+        assert!(s.contains(r#"_createTextVNode("a")"#), "{}", s);
+        assert!(s.contains("64 /*STABLE_FRAGMENT*/"), "{}", s);
+    }
+    #[test]
+    fn test_dev_root_fragment() {
+        let s = base_gen("<!--a--><p/>");
+        assert!(s.contains("2112 /*"), "{}", s);
+        let s = base_gen("<!--a--><!--b-->");
+        assert!(s.contains("_createCommentVNode(\"a\")"), "{}", s);
+        assert!(s.contains("64 /*STABLE_FRAGMENT*/"), "{}", s);
+    }
     #[test]
     fn test_v_for() {
         let s = base_gen("<p v-for='a in b'/>");
         assert!(s.contains("\"p\""), "{}", s);
         assert!(s.contains("(a) =>"), "{}", s);
         assert!(s.contains("_createElementBlock"), "{}", s);
+        assert!(s.contains("_openBlock(true)"), "{}", s);
         let s = base_gen("<p v-for='(a, b, c) in d'/>");
         assert!(s.contains("\"p\""), "{}", s);
         assert!(s.contains("(a, b, c) =>"), "{}", s);
     }
     #[test]
+    fn test_v_for_fragment_flag() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<p v-for='a in b' :key='a'/>");
+        cast!(&mut ir.body[0], IRNode::For).fragment_flag = PatchFlag::KEYED_FRAGMENT;
+        let s = gen(ir, &info);
+        assert!(s.contains("128 /*KEYED_FRAGMENT*/"), "{}", s);
+        let mut ir = base_convert("<p v-for='a in b'/>");
+        cast!(&mut ir.body[0], IRNode::For).fragment_flag = PatchFlag::UNKEYED_FRAGMENT;
+        let s = gen(ir, &info);
+        assert!(s.contains("256 /*UNKEYED_FRAGMENT*/"), "{}", s);
+    }
+    #[test]
+    fn test_cache_and_hoist_index() {
+        let info = SFCInfo::default();
+        // nested v-once is stripped so only two cache slots are used
+        let mut ir = base_convert("<i/><b/><div v-once><p v-once/></div><p v-once/>");
+        for i in 0..2 {
+            let hoisted = std::mem::replace(&mut ir.body[i], IRNode::Hoisted(i));
+            assert_eq!(ir.top_scope.hoist(hoisted), i);
+        }
+        let s = gen(ir, &info);
+        let i = s.find("const _hoisted_1 = ").unwrap();
+        let b = s.find("const _hoisted_2 = ").unwrap();
+        assert!(i < b && b < s.find("\"b\"").unwrap(), "{}", s);
+        assert!(!s.contains("_hoisted_0"), "{}", s);
+        for i in 0..2 {
+            let cache = format!("_cache[{}]", i);
+            assert_eq!(s.matches(&cache).count(), 3, "{}", s);
+        }
+        assert!(!s.contains("_cache[2]"), "{}", s);
+        let div = s.find("_cache[0] = ").unwrap();
+        let p = s.find("_cache[1] = ").unwrap();
+        assert!(div < p && div < s.find("\"div\"").unwrap(), "{}", s);
+    }
+    #[test]
+    fn test_cache_index_continues_counter() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<p v-once/>");
+        // slot reserved by an earlier pass
+        assert_eq!(ir.top_scope.counter.next_cache(), 0);
+        let s = gen(ir, &info);
+        assert!(!s.contains("_cache[0]"), "{}", s);
+        assert!(s.contains("_cache[1] = "), "{}", s);
+    }
+    #[test]
     fn test_slot_outlet() {
         let s = base_gen("<slot name=test />");
         assert!(s.contains("_renderSlot"), "{}", s);
@@ -1116,6 +1337,16 @@ mod test {
         assert!(s.contains(r#""fallback""#), "{}", s);
     }
     #[test]
+    fn test_dynamic_slot_outlet() {
+        let s = base_gen("<slot :name='n' />");
+        assert!(s.contains("_renderSlot($slots, n)"), "{}", s);
+        let s = base_gen("<slot :name='n' :user='u' />");
+        assert!(s.contains("_renderSlot($slots, n, {"), "{}", s);
+        assert!(s.contains("user: u"), "{}", s);
+        let s = base_gen("<slot :name='n'>fallback</slot>");
+        assert!(s.contains("_renderSlot($slots, n, {}, () => ["), "{}", s);
+    }
+    #[test]
     fn test_size() {
         let ir_size = std::mem::size_of::<BaseIR<'_>>();
         let vnode_size = std::mem::size_of::<BaseVNode<'_>>();
@@ -1134,6 +1365,28 @@ mod test {
         let s = base_gen("<component is='test'>test</component>");
         assert!(s.contains("_withCtx"), "{}", s);
     }
+    #[test]
+    fn test_on_component_slot() {
+        let s = base_gen("<comp v-slot='{ item }'>{{item}}</comp>");
+        assert!(s.contains("default: _withCtx(({ item }) => ["), "{}", s);
+        assert!(s.contains("_: 1 /*Stable*/"), "{}", s);
+    }
+    #[test]
+    fn test_namespaced_attr() {
+        let s = base_gen("<svg><use xlink:href='#a' :xlink:title='t'/></svg>");
+        assert!(s.contains(r##""xlink:href": "#a""##), "{}", s);
+        assert!(s.contains(r#""xlink:title": t"#), "{}", s);
+    }
+    #[test]
+    fn test_template_slots() {
+        let s = base_gen(
+            "<comp><template #header='props'>a</template><template #footer>b</template></comp>",
+        );
+        assert!(s.contains("header: _withCtx((props) => ["), "{}", s);
+        assert!(s.contains("footer: _withCtx(() => ["), "{}", s);
+        assert!(s.contains("_: 1 /*Stable*/"), "{}", s);
+        assert!(!s.contains("default:"), "{}", s);
+    }
 
     #[test]
     fn test_render_func_args() {
@@ -1158,6 +1411,12 @@ mod test {
         assert!(s.contains("setBlockTracking"), "{}", s);
     }
     #[test]
+    fn test_nested_v_once() {
+        let s = base_gen("<div v-once><p v-once/><p><i v-once/></p></div>");
+        assert!(s.contains("_cache[0]"), "{}", s);
+        assert!(!s.contains("_cache[1]"), "{}", s);
+    }
+    #[test]
     fn test_v_memo() {
         let s = base_gen("<p v-memo='[a]'/>");
         let expected =
@@ -1174,6 +1433,41 @@ mod test {
         assert!(!s.contains("_withMemo"), "{}", s);
     }
 
+    #[test]
+    fn test_scope_id_hoist() {
+        let info = SFCInfo {
+            scope_id: Some("data-v-xxx".into()),
+            ..Default::default()
+        };
+        let mut ir = base_convert("<p/>");
+        let hoisted = ir.body.pop().unwrap();
+        ir.top_scope.hoist(hoisted);
+        ir.top_scope.helpers.ignore_missing();
+        let option = CodeGenerateOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let mut writer = CodeWriter::new(vec![], Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let s = String::from_utf8(writer.writer.inner).unwrap();
+        assert!(s.contains(r#"_pushScopeId("data-v-xxx"),n=n()"#), "{}", s);
+        assert!(s.contains("_hoisted_1 = _withScopeId(() => "), "{}", s);
+    }
+
+    #[test]
+    fn test_hoisted_ref() {
+        let mut ir = base_convert("<p/><span/>");
+        let hoisted = std::mem::replace(&mut ir.body[1], IRNode::Hoisted(0));
+        ir.top_scope.hoist(hoisted);
+        let info = SFCInfo::default();
+        let s = gen(ir, &info);
+        let expected = r#"const _hoisted_1 = _createElementVNode("span")"#;
+        assert!(s.contains(expected), "{}", s);
+        assert_eq!(s.matches("_hoisted_1").count(), 2, "{}", s);
+    }
+
     fn gen_on(s: &str) -> String {
         let ir = handler_convert(s);
         let info = SFCInfo::default();
@@ -1201,6 +1495,33 @@ mod test {
         assert!(s.contains("onClick: () => a()"), "{}", s);
     }
 
+    #[test]
+    fn test_vnode_hook() {
+        let s = gen_on("<p @vue:mounted='a'/>");
+        assert!(s.contains("onVnodeMounted: a"), "{}", s);
+        let s = gen_on("<comp @vue:before-update='a()'/>");
+        assert!(s.contains("onVnodeBeforeUpdate: $event => (a())"), "{}", s);
+        let s = gen_on("<p @vnode-mounted='a()'/>");
+        assert!(s.contains("onVnodeMounted: $event => (a())"), "{}", s);
+        let s = gen_on("<comp @vnode-mounted='a'/>");
+        assert!(s.contains("onVnodeMounted: a"), "{}", s);
+    }
+
+    #[test]
+    fn test_component_asset() {
+        let mut ir = base_convert("<Foo/><keep-alive/>");
+        ir.top_scope
+            .components
+            .insert(*VStr::raw("Foo").be_component());
+        let info = SFCInfo::default();
+        let s = gen(ir, &info);
+        let resolve = r#"const _component_Foo = _resolveComponent("Foo")"#;
+        assert!(s.contains(resolve), "{}", s);
+        assert!(s.contains("_createVNode(_component_Foo)"), "{}", s);
+        assert!(s.contains("_createVNode(_KeepAlive)"), "{}", s);
+        assert!(!s.contains("_component_keep"), "{}", s);
+    }
+
     #[test]
     fn test_helpers() {
         let info = SFCInfo::default();
@@ -1234,4 +1555,56 @@ mod test {
         assert!(s.contains("createTextVNode as _createTextVNode"), "{}", s);
         assert!(s.contains("from \"vue\""), "{}", s);
     }
+
+    #[test]
+    fn test_folded_class_style() {
+        let s = base_gen("<p class='a' :class=\"'b'\" style='color: red' :style=\"'top: 0'\"/>");
+        assert!(s.contains(r#"class: "a b","#), "{}", s);
+        assert!(s.contains(r#"style: "color: red;top: 0","#), "{}", s);
+        assert!(!s.contains("normalize"), "{}", s);
+    }
+
+    #[test]
+    fn test_self_reference() {
+        use crate::compiler::{get_base_passes, BaseCompiler, CompileOption, TemplateCompiler};
+        let compile = |info: &SFCInfo| {
+            let compiler = BaseCompiler::new(Vec::new, get_base_passes, CompileOption::default());
+            let s = compiler.compile("<FooBar/>", info).unwrap();
+            String::from_utf8(s).unwrap()
+        };
+        let info = SFCInfo::default().with_filename("FooBar.vue");
+        let s = compile(&info);
+        let expected = r#"const _component_FooBar = _resolveComponent("FooBar", true)"#;
+        assert!(s.contains(expected), "{}", s);
+        let s = compile(&SFCInfo::default());
+        let expected = r#"const _component_FooBar = _resolveComponent("FooBar")"#;
+        assert!(s.contains(expected), "{}", s);
+    }
+
+    #[test]
+    fn test_inline_mode() {
+        use crate::compiler::{get_base_passes, BaseCompiler, CompileOption, TemplateCompiler};
+        use rustc_hash::FxHashMap;
+        let mut map = FxHashMap::default();
+        map.insert("foo", BindingTypes::SetupRef);
+        let info = SFCInfo {
+            inline: true,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        let option = CompileOption {
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let s = compiler.compile("<p>{{ foo }}</p>", &info).unwrap();
+        let s = String::from_utf8(s).unwrap();
+        assert!(s.contains("(_ctx, _cache) => {"), "{}", s);
+        assert!(s.contains("_toDisplayString(foo.value)"), "{}", s);
+        assert!(!s.contains("_ctx.foo"), "{}", s);
+        assert!(!s.contains("function render"), "{}", s);
+        assert!(!s.contains("export"), "{}", s);
+    }
 }
@@ -0,0 +1,847 @@
+//! SSR code writer renders template into string pushes instead of vnode calls.
+//! e.g. `<div>{{ msg }}</div>` is compiled to
+//! ```js
+//! _push(`<div${_ssrRenderAttrs(_attrs)}>${_ssrInterpolate(msg)}</div>`)
+//! ```
+//! It reuses the same IR as the vdom codegen. Static parts are concatenated
+//! into one template literal and dynamic parts are interpolated inside it.
+use super::code_writer::CodeWriter;
+use super::{CodeGenerateOption, CoreCodeGenerator, ScriptMode};
+use crate::converter::{BaseConvertInfo, BaseIR, BaseRoot, TopScope};
+use crate::flags::{HelperCollector, RuntimeHelper as RH};
+use crate::ir::{IRNode, JsExpr as Js, RenderSlotIR, StaticIR, VNodeIR};
+use crate::transformer::{
+    BaseCache, BaseFor, BaseIf, BaseRenderSlot, BaseSlotFn, BaseText, BaseVNode, BaseVSlot,
+};
+use crate::util::{escape_html, is_boolean_attr, is_reserved_prop, is_simple_identifier, VStr};
+use crate::SFCInfo;
+
+use std::{
+    fmt::{self, Write},
+    io::{self, Write as ioWrite},
+    rc::Rc,
+};
+
+type Output = fmt::Result;
+
+const SSR_INTERPOLATE: &str = "ssrInterpolate";
+const SSR_RENDER_ATTRS: &str = "ssrRenderAttrs";
+const SSR_RENDER_LIST: &str = "ssrRenderList";
+const SSR_RENDER_COMPONENT: &str = "ssrRenderComponent";
+const SSR_RENDER_SLOT: &str = "ssrRenderSlot";
+const SSR_RENDER_SUSPENSE: &str = "ssrRenderSuspense";
+const SSR_RENDER_TELEPORT: &str = "ssrRenderTeleport";
+const SERVER_RENDERER: &str = "vue/server-renderer";
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+pub struct SsrCodeWriter<'a, T: ioWrite> {
+    writer: T,
+    option: Rc<CodeGenerateOption>,
+    sfc_info: &'a SFCInfo<'a>,
+    /// statements inside ssrRender function
+    body: String,
+    /// pending template literal content that is not pushed yet
+    pending: String,
+    indent_level: usize,
+    /// the sole root element receives fallthrough attrs
+    inject_attrs: bool,
+    /// vue runtime helpers used in expressions
+    helpers: HelperCollector,
+    /// helpers from server renderer, in the order of usage
+    ssr_helpers: Vec<&'static str>,
+    /// SSR does not hoist. Nodes hoisted by converters are rendered in place
+    hoists: Vec<Option<BaseIR<'a>>>,
+}
+
+impl<'a, T: ioWrite> SsrCodeWriter<'a, T> {
+    pub fn new(writer: T, option: Rc<CodeGenerateOption>, sfc_info: &'a SFCInfo<'a>) -> Self {
+        Self {
+            writer,
+            option,
+            sfc_info,
+            body: String::new(),
+            pending: String::new(),
+            indent_level: 0,
+            inject_attrs: false,
+            helpers: HelperCollector::new(),
+            ssr_helpers: vec![],
+            hoists: vec![],
+        }
+    }
+    pub fn generate_root(&mut self, mut root: BaseRoot<'a>) -> io::Result<()> {
+        let to_io_err = |_| io::Error::new(io::ErrorKind::Other, "unexpected fmt error");
+        self.generate_prologue(&mut root).map_err(to_io_err)?;
+        self.inject_attrs = !root.is_fragment && root.body.len() == 1;
+        let ir = if self.inject_attrs {
+            root.body.pop().unwrap()
+        } else {
+            IRNode::VNodeCall(VNodeIR {
+                tag: Js::Symbol(RH::FRAGMENT),
+                children: root.body,
+                ..VNodeIR::default()
+            })
+        };
+        self.generate_ir(ir).map_err(to_io_err)?;
+        self.generate_epilogue().map_err(to_io_err)?;
+        self.write_output()
+    }
+}
+
+impl<'a, T: ioWrite> CoreCodeGenerator<BaseConvertInfo<'a>> for SsrCodeWriter<'a, T> {
+    type Written = Output;
+    fn generate_prologue(&mut self, root: &mut BaseRoot<'a>) -> Output {
+        let hoists = std::mem::take(&mut root.top_scope.hoists);
+        self.hoists = hoists.into_iter().map(Some).collect();
+        // preamble depends on helpers so it is written after body
+        self.indent_level = if self.option.use_with_scope() { 2 } else { 1 };
+        // runtime directives are not applied on server, only components
+        self.gen_component_assets(&root.top_scope)
+    }
+    fn generate_epilogue(&mut self) -> Output {
+        self.flush_push()
+    }
+    fn generate_text(&mut self, t: BaseText<'a>) -> Output {
+        for text in t.texts {
+            match text {
+                Js::StrLit(mut s) => {
+                    // SSR outputs html so entities should be kept as is
+                    self.push_static(&s.undecode().into_string());
+                }
+                Js::Call(RH::TO_DISPLAY_STRING, mut args) => {
+                    let expr = args.pop().expect("toDisplayString must have argument");
+                    self.push_dynamic(SSR_INTERPOLATE, expr);
+                }
+                expr => self.push_dynamic(SSR_INTERPOLATE, expr),
+            }
+        }
+        Ok(())
+    }
+    fn generate_if(&mut self, i: BaseIf<'a>) -> Output {
+        // every branch can be the root so each one receives _attrs
+        let inject_attrs = std::mem::take(&mut self.inject_attrs);
+        self.flush_push()?;
+        self.newline()?;
+        let mut has_else = false;
+        for (n, branch) in i.branches.into_iter().enumerate() {
+            if let Some(condition) = branch.condition {
+                let condition = self.expr_to_string(condition);
+                if n > 0 {
+                    self.body.write_str(" else ")?;
+                }
+                write!(self.body, "if ({}) {{", condition)?;
+            } else {
+                has_else = true;
+                self.body.write_str(" else {")?;
+            }
+            self.inject_attrs = inject_attrs;
+            self.gen_block(*branch.child)?;
+        }
+        if !has_else {
+            // render an empty comment as the v-if anchor in hydration
+            self.body.write_str(" else {")?;
+            self.indent_level += 1;
+            self.push_static("<!---->");
+            self.flush_push()?;
+            self.indent_level -= 1;
+            self.newline()?;
+            self.body.write_str("}")?;
+        }
+        Ok(())
+    }
+    fn generate_for(&mut self, f: BaseFor<'a>) -> Output {
+        // v-for renders a fragment which cannot receive fallthrough attrs
+        self.inject_attrs = false;
+        self.push_static("<!--[-->");
+        self.flush_push()?;
+        self.use_ssr_helper(SSR_RENDER_LIST);
+        let source = self.expr_to_string(f.source);
+        let p = f.parse_result;
+        let params = gen_for_params(vec![Some(p.value), p.key, p.index], |e| {
+            self.expr_to_string(e)
+        });
+        self.newline()?;
+        let render_list = format!("_{}({}, ({}) => {{", SSR_RENDER_LIST, source, params);
+        self.body.write_str(&render_list)?;
+        self.gen_block(*f.child)?;
+        self.body.write_str(")")?;
+        self.push_static("<!--]-->");
+        Ok(())
+    }
+    fn generate_vnode(&mut self, v: BaseVNode<'a>) -> Output {
+        let inject_attrs = std::mem::take(&mut self.inject_attrs);
+        let tag = match v.tag {
+            Js::Symbol(RH::FRAGMENT) => {
+                self.push_static("<!--[-->");
+                self.generate_children(v.children)?;
+                self.push_static("<!--]-->");
+                return Ok(());
+            }
+            // KeepAlive has no effect on server, render its child in place
+            Js::Symbol(RH::KEEP_ALIVE) => {
+                self.inject_attrs = inject_attrs;
+                return self.generate_children(v.children);
+            }
+            Js::Symbol(RH::TELEPORT) => return self.gen_teleport(v.props, v.children),
+            Js::Symbol(RH::SUSPENSE) => return self.gen_suspense(v.children),
+            Js::StrLit(tag) if !v.is_component => tag,
+            tag => return self.gen_component(tag, v.props, v.children, inject_attrs),
+        };
+        self.push_static("<");
+        self.push_static(tag.raw);
+        self.gen_element_props(v.props, inject_attrs);
+        self.push_static(">");
+        if VOID_TAGS.contains(&tag.raw) {
+            return Ok(());
+        }
+        self.generate_children(v.children)?;
+        self.push_static("</");
+        self.push_static(tag.raw);
+        self.push_static(">");
+        Ok(())
+    }
+    fn generate_slot_outlet(&mut self, r: BaseRenderSlot<'a>) -> Output {
+        // slot content may have multiple roots so it never receives attrs
+        self.inject_attrs = false;
+        let RenderSlotIR {
+            slot_obj,
+            slot_name,
+            slot_props,
+            fallbacks,
+            ..
+        } = r;
+        self.flush_push()?;
+        self.use_ssr_helper(SSR_RENDER_SLOT);
+        let slot_obj = self.expr_to_string(slot_obj);
+        let slot_name = self.expr_to_string(slot_name);
+        let slot_props = match slot_props {
+            Some(props) => self.expr_to_string(props),
+            None => "{}".into(),
+        };
+        self.newline()?;
+        let render_slot = format!(
+            "_{}({}, {}, {}, ",
+            SSR_RENDER_SLOT, slot_obj, slot_name, slot_props
+        );
+        self.body.write_str(&render_slot)?;
+        if fallbacks.is_empty() {
+            self.body.write_str("null")?;
+        } else {
+            self.body.write_str("() => {")?;
+            self.gen_block_children(fallbacks)?;
+        }
+        self.body.write_str(", _push, _parent)")
+    }
+    fn generate_v_slot(&mut self, s: BaseVSlot<'a>) -> Output {
+        self.gen_slots(s, /*with_ctx*/ true)
+    }
+    fn generate_alterable_slot(&mut self, s: BaseSlotFn<'a>) -> Output {
+        self.gen_alterable_slot_fn(s, true)
+    }
+    fn generate_cache(&mut self, c: BaseCache<'a>) -> Output {
+        // SSR renders only once, cache is meaningless
+        self.generate_ir(*c.child)
+    }
+    fn generate_js_expr(&mut self, e: Js<'a>) -> Output {
+        let expr = self.expr_to_string(e);
+        self.body.write_str(&expr)
+    }
+    fn generate_comment(&mut self, c: &'a str) -> Output {
+        self.push_static("<!--");
+        self.push_static(c);
+        self.push_static("-->");
+        Ok(())
+    }
+    fn generate_static(&mut self, s: StaticIR) -> Output {
+        self.push_static(&s.content);
+        Ok(())
+    }
+    fn generate_hoisted(&mut self, index: usize) -> Output {
+        // each hoisted node is referenced only once
+        match self.hoists.get_mut(index).and_then(Option::take) {
+            Some(node) => self.generate_ir(node),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, T: ioWrite> SsrCodeWriter<'a, T> {
+    fn generate_children(&mut self, children: Vec<BaseIR<'a>>) -> Output {
+        for child in children {
+            self.generate_ir(child)?;
+        }
+        Ok(())
+    }
+    /// generate a block body for control flow and close the brace
+    fn gen_block(&mut self, ir: BaseIR<'a>) -> Output {
+        self.gen_block_children(vec![ir])
+    }
+    fn gen_block_children(&mut self, children: Vec<BaseIR<'a>>) -> Output {
+        self.indent_level += 1;
+        self.generate_children(children)?;
+        self.flush_push()?;
+        self.indent_level -= 1;
+        self.newline()?;
+        self.body.write_str("}")
+    }
+    fn gen_component_assets(&mut self, top: &TopScope<'a>) -> Output {
+        if top.components.is_empty() {
+            return Ok(());
+        }
+        // sort for deterministic output since components are in a hash set
+        let mut components: Vec<_> = top.components.iter().cloned().collect();
+        components.sort_by_key(|c| c.raw);
+        for asset in components {
+            let hint = if VStr::is_self_suffixed(&asset) {
+                ", true"
+            } else {
+                ""
+            };
+            let mut raw = *asset.clone().unbe_component();
+            raw.be_js_str();
+            let resolve = self.vue_helper(RH::RESOLVE_COMPONENT);
+            self.newline()?;
+            write!(
+                self.body,
+                "const {} = {}({}{})",
+                asset.into_string(),
+                resolve,
+                raw.into_string(),
+                hint
+            )?;
+        }
+        Ok(())
+    }
+    /// e.g. `_push(_ssrRenderComponent(_component_foo, null, null, _parent))`
+    fn gen_component(
+        &mut self,
+        tag: Js<'a>,
+        props: Option<Js<'a>>,
+        children: Vec<BaseIR<'a>>,
+        inject_attrs: bool,
+    ) -> Output {
+        self.flush_push()?;
+        self.use_ssr_helper(SSR_RENDER_COMPONENT);
+        let tag = self.expr_to_string(tag);
+        let props = match (props, inject_attrs) {
+            (Some(props), true) => {
+                let merged = Js::Call(RH::MERGE_PROPS, vec![props, Js::Src("_attrs")]);
+                self.expr_to_string(merged)
+            }
+            (Some(props), false) => self.expr_to_string(props),
+            (None, true) => "_attrs".into(),
+            (None, false) => "null".into(),
+        };
+        self.newline()?;
+        write!(
+            self.body,
+            "_push(_{}({}, {}, ",
+            SSR_RENDER_COMPONENT, tag, props
+        )?;
+        self.gen_component_slots(children, true)?;
+        self.body.write_str(", _parent))")
+    }
+    fn gen_component_slots(&mut self, mut children: Vec<BaseIR<'a>>, with_ctx: bool) -> Output {
+        debug_assert!(children.len() <= 1);
+        match children.pop() {
+            Some(IRNode::VSlotUse(s)) => self.gen_slots(s, with_ctx),
+            _ => self.body.write_str("null"),
+        }
+    }
+    /// e.g. `_ssrRenderTeleport(_push, (_push) => { ... }, "#target", false, _parent)`
+    fn gen_teleport(&mut self, props: Option<Js<'a>>, children: Vec<BaseIR<'a>>) -> Output {
+        self.inject_attrs = false;
+        let (mut to, mut disabled) = (None, None);
+        if let Some(Js::Props(props)) = props {
+            for (key, val) in props {
+                match key {
+                    Js::StrLit(k) if k.raw == "to" => to = Some(val),
+                    Js::StrLit(k) if k.raw == "disabled" => disabled = Some(val),
+                    _ => (),
+                }
+            }
+        }
+        let to = match to {
+            Some(to) => self.expr_to_string(to),
+            None => "undefined".into(),
+        };
+        let disabled = match disabled {
+            // boolean attribute without value, e.g. <teleport disabled>
+            Some(Js::StrLit(v)) => (v.raw.is_empty() || v.raw == "true").to_string(),
+            Some(d) => self.expr_to_string(d),
+            None => "false".into(),
+        };
+        self.flush_push()?;
+        self.use_ssr_helper(SSR_RENDER_TELEPORT);
+        self.newline()?;
+        write!(self.body, "_{}(_push, (_push) => {{", SSR_RENDER_TELEPORT)?;
+        self.gen_block_children(children)?;
+        write!(self.body, ", {}, {}, _parent)", to, disabled)
+    }
+    /// e.g. `_ssrRenderSuspense(_push, { default: () => { ... } })`
+    fn gen_suspense(&mut self, children: Vec<BaseIR<'a>>) -> Output {
+        self.inject_attrs = false;
+        self.flush_push()?;
+        self.use_ssr_helper(SSR_RENDER_SUSPENSE);
+        self.newline()?;
+        write!(self.body, "_{}(_push, ", SSR_RENDER_SUSPENSE)?;
+        // suspense calls slots without arguments so they must use outer _push
+        self.gen_component_slots(children, false)?;
+        self.body.write_str(")")
+    }
+    /// Slot functions only push strings. Components rendered by
+    /// vdom render functions on server cannot consume these slots.
+    fn gen_slots(&mut self, s: BaseVSlot<'a>, with_ctx: bool) -> Output {
+        let has_alterable = !s.alterable_slots.is_empty();
+        if has_alterable {
+            let create_slots = self.vue_helper(RH::CREATE_SLOTS);
+            self.body.write_str(&create_slots)?;
+            self.body.write_str("(")?;
+        }
+        self.body.write_str("{")?;
+        self.indent_level += 1;
+        for slot in s.stable_slots {
+            self.newline()?;
+            let name = slot_key(slot.name, |e| self.expr_to_string(e));
+            write!(self.body, "{}: ", name)?;
+            self.gen_slot_fn(slot.param, slot.body, with_ctx)?;
+            self.body.write_str(",")?;
+        }
+        self.newline()?;
+        let flag = s.slot_flag;
+        write!(self.body, "_: {} /*{:?}*/", flag as u8, flag)?;
+        self.indent_level -= 1;
+        self.newline()?;
+        self.body.write_str("}")?;
+        if !has_alterable {
+            return Ok(());
+        }
+        self.body.write_str(", [")?;
+        self.indent_level += 1;
+        for slot in s.alterable_slots {
+            self.newline()?;
+            self.gen_alterable(slot, with_ctx)?;
+            self.body.write_str(",")?;
+        }
+        self.indent_level -= 1;
+        self.newline()?;
+        self.body.write_str("])")
+    }
+    /// slot templates with v-if/v-for in expression form
+    fn gen_alterable(&mut self, ir: BaseIR<'a>, with_ctx: bool) -> Output {
+        match ir {
+            IRNode::AlterableSlot(s) => self.gen_alterable_slot_fn(s, with_ctx),
+            IRNode::If(i) => {
+                let mut has_else = false;
+                for branch in i.branches {
+                    if let Some(condition) = branch.condition {
+                        let condition = self.expr_to_string(condition);
+                        write!(self.body, "({}) ? ", condition)?;
+                        self.gen_alterable(*branch.child, with_ctx)?;
+                        self.body.write_str(" : ")?;
+                    } else {
+                        has_else = true;
+                        self.gen_alterable(*branch.child, with_ctx)?;
+                    }
+                }
+                if !has_else {
+                    self.body.write_str("undefined")?;
+                }
+                Ok(())
+            }
+            IRNode::For(f) => {
+                let render_list = self.vue_helper(RH::RENDER_LIST);
+                let source = self.expr_to_string(f.source);
+                let p = f.parse_result;
+                let params = gen_for_params(vec![Some(p.value), p.key, p.index], |e| {
+                    self.expr_to_string(e)
+                });
+                write!(self.body, "{}({}, ({}) => {{", render_list, source, params)?;
+                self.indent_level += 1;
+                self.newline()?;
+                self.body.write_str("return ")?;
+                self.gen_alterable(*f.child, with_ctx)?;
+                self.indent_level -= 1;
+                self.newline()?;
+                self.body.write_str("})")
+            }
+            _ => self.body.write_str("undefined"),
+        }
+    }
+    fn gen_alterable_slot_fn(&mut self, s: BaseSlotFn<'a>, with_ctx: bool) -> Output {
+        let name = self.expr_to_string(s.name);
+        self.body.write_str("{")?;
+        self.indent_level += 1;
+        self.newline()?;
+        write!(self.body, "name: {},", name)?;
+        self.newline()?;
+        self.body.write_str("fn: ")?;
+        self.gen_slot_fn(s.param, s.body, with_ctx)?;
+        self.indent_level -= 1;
+        self.newline()?;
+        self.body.write_str("}")
+    }
+    /// e.g. `_withCtx((_, _push, _parent, _scopeId) => { ... })`
+    fn gen_slot_fn(
+        &mut self,
+        param: Option<Js<'a>>,
+        body: Vec<BaseIR<'a>>,
+        with_ctx: bool,
+    ) -> Output {
+        if !with_ctx {
+            self.body.write_str("() => {")?;
+            return self.gen_block_children(body);
+        }
+        let with_ctx = self.vue_helper(RH::WITH_CTX);
+        let param = match param {
+            Some(p) => self.expr_to_string(p),
+            None => "_".into(),
+        };
+        write!(
+            self.body,
+            "{}(({}, _push, _parent, _scopeId) => {{",
+            with_ctx, param
+        )?;
+        self.gen_block_children(body)?;
+        self.body.write_str(")")
+    }
+    fn vue_helper(&mut self, helper: RH) -> String {
+        self.helpers.collect(helper);
+        format!("_{}", helper.helper_str(self.option.helper_strs))
+    }
+    fn gen_element_props(&mut self, props: Option<Js<'a>>, inject_attrs: bool) {
+        // reserved props and event handlers have no effect on server
+        let props = match props {
+            Some(Js::Props(props)) => {
+                let props: Vec<_> = props
+                    .into_iter()
+                    .filter(|(k, _)| match k {
+                        Js::StrLit(k) => !is_reserved_prop(k) && !VStr::is_handler(k),
+                        _ => true,
+                    })
+                    .collect();
+                if props.is_empty() {
+                    None
+                } else {
+                    Some(Js::Props(props))
+                }
+            }
+            props => props,
+        };
+        let props = match props {
+            Some(Js::Props(props)) if !inject_attrs => props,
+            Some(props) => {
+                let props = if inject_attrs {
+                    Js::Call(RH::MERGE_PROPS, vec![props, Js::Src("_attrs")])
+                } else {
+                    props
+                };
+                return self.push_dynamic(SSR_RENDER_ATTRS, props);
+            }
+            None => {
+                if inject_attrs {
+                    self.push_dynamic(SSR_RENDER_ATTRS, Js::Src("_attrs"));
+                }
+                return;
+            }
+        };
+        let mut dynamic = vec![];
+        for (key, val) in props {
+            match (&key, &val) {
+                (Js::StrLit(k), Js::Src("true")) if is_boolean_attr(k) => {
+                    self.push_static(" ");
+                    self.push_static(k.raw);
+                }
+                (Js::StrLit(k), Js::StrLit(v)) => {
+                    self.push_static_attr(k.raw, &v.into_string());
+                }
+                (Js::StrLit(k), Js::Joined { text, quoted: true }) => {
+                    self.push_static_attr(k.raw, text);
+                }
+                _ => dynamic.push((key, val)),
+            }
+        }
+        if !dynamic.is_empty() {
+            self.push_dynamic(SSR_RENDER_ATTRS, Js::Props(dynamic));
+        }
+    }
+
+    /// attribute value is decoded text so it is escaped as html
+    fn push_static_attr(&mut self, key: &str, value: &str) {
+        let mut escaped = String::new();
+        escape_html(value, &mut escaped);
+        self.push_static(" ");
+        self.push_static(key);
+        self.push_static("=\"");
+        self.push_static(&escaped);
+        self.push_static("\"");
+    }
+    /// escape static content in template literal
+    fn push_static(&mut self, s: &str) {
+        for c in s.chars() {
+            if matches!(c, '`' | '$' | '\\') {
+                self.pending.push('\\');
+            }
+            self.pending.push(c);
+        }
+    }
+    fn use_ssr_helper(&mut self, ssr_helper: &'static str) {
+        if !self.ssr_helpers.contains(&ssr_helper) {
+            self.ssr_helpers.push(ssr_helper);
+        }
+    }
+    fn push_dynamic(&mut self, ssr_helper: &'static str, expr: Js<'a>) {
+        self.use_ssr_helper(ssr_helper);
+        let expr = self.expr_to_string(expr);
+        self.pending.push_str("${_");
+        self.pending.push_str(ssr_helper);
+        self.pending.push('(');
+        self.pending.push_str(&expr);
+        self.pending.push_str(")}");
+    }
+    fn flush_push(&mut self) -> Output {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.newline()?;
+        self.body.write_str("_push(`")?;
+        self.body.write_str(&self.pending)?;
+        self.body.write_str("`)")?;
+        self.pending.clear();
+        Ok(())
+    }
+    fn newline(&mut self) -> Output {
+        self.body.write_str("\n")?;
+        for _ in 0..self.indent_level {
+            self.body.write_str("  ")?;
+        }
+        Ok(())
+    }
+    fn expr_to_string(&mut self, expr: Js<'a>) -> String {
+        collect_helpers(&expr, &mut self.helpers);
+        let helpers = self.helpers.clone();
+        CodeWriter::expr_to_string(expr, self.option.clone(), self.sfc_info, helpers)
+    }
+
+    fn write_output(&mut self) -> io::Result<()> {
+        let mut out = String::new();
+        self.write_preamble(&mut out)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "unexpected fmt error"))?;
+        self.writer.write_all(out.as_bytes())?;
+        self.writer.write_all(self.body.as_bytes())?;
+        let closing = if self.option.use_with_scope() {
+            "\n  }\n}"
+        } else {
+            "\n}"
+        };
+        self.writer.write_all(closing.as_bytes())
+    }
+    fn write_preamble(&self, out: &mut String) -> Output {
+        let helpers = self.helpers.clone().into_iter();
+        let vue_helpers: Vec<_> = helpers
+            .map(|h| h.helper_str(self.option.helper_strs))
+            .collect();
+        let ssr_helpers = &self.ssr_helpers;
+        match &self.option.mode {
+            ScriptMode::Module {
+                runtime_module_name,
+            } => {
+                write_import(out, &vue_helpers, runtime_module_name)?;
+                write_import(out, ssr_helpers, SERVER_RENDERER)?;
+                out.write_str("\nexport function ssrRender(")?;
+            }
+            ScriptMode::Function { .. } => {
+                write_require(out, &vue_helpers, "vue")?;
+                write_require(out, ssr_helpers, SERVER_RENDERER)?;
+                out.write_str("\nreturn function ssrRender(")?;
+            }
+        }
+        out.write_str("_ctx, _push, _parent, _attrs) {")?;
+        if self.option.use_with_scope() {
+            out.write_str("\n  with (_ctx) {")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_import(out: &mut String, helpers: &[&str], from: &str) -> Output {
+    if helpers.is_empty() {
+        return Ok(());
+    }
+    out.write_str("import { ")?;
+    let list: Vec<_> = helpers.iter().map(|h| format!("{} as _{}", h, h)).collect();
+    out.write_str(&list.join(", "))?;
+    writeln!(out, " }} from \"{}\"", from)
+}
+
+fn write_require(out: &mut String, helpers: &[&str], from: &str) -> Output {
+    if helpers.is_empty() {
+        return Ok(());
+    }
+    out.write_str("const { ")?;
+    let list: Vec<_> = helpers.iter().map(|h| format!("{}: _{}", h, h)).collect();
+    out.write_str(&list.join(", "))?;
+    writeln!(out, " }} = require(\"{}\")", from)
+}
+
+/// static slot name is written as object key, dynamic one as computed key
+fn slot_key<'a, F>(name: Js<'a>, to_str: F) -> String
+where
+    F: FnOnce(Js<'a>) -> String,
+{
+    match name {
+        Js::StrLit(n) if is_simple_identifier(n) => n.into_string(),
+        Js::StrLit(mut n) => n.be_js_str().into_string(),
+        name => format!("[{}]", to_str(name)),
+    }
+}
+
+/// join v-for params, filling the missing ones with placeholders
+fn gen_for_params<'a, F>(params: Vec<Option<Js<'a>>>, mut to_str: F) -> String
+where
+    F: FnMut(Js<'a>) -> String,
+{
+    const PLACE_HOLDER: &[&str] = &["_", "_1", "_2"];
+    let last = params
+        .iter()
+        .rposition(Option::is_some)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let list: Vec<_> = params
+        .into_iter()
+        .take(last)
+        .enumerate()
+        .map(|(i, o)| o.map_or_else(|| PLACE_HOLDER[i].to_string(), &mut to_str))
+        .collect();
+    list.join(", ")
+}
+
+fn collect_helpers(expr: &Js, helpers: &mut HelperCollector) {
+    match expr {
+        Js::Symbol(h) => helpers.collect(*h),
+        Js::Call(h, args) => {
+            helpers.collect(*h);
+            args.iter().for_each(|a| collect_helpers(a, helpers));
+        }
+        Js::Compound(v) | Js::Array(v) | Js::FuncCompound { body: v, .. } => {
+            v.iter().for_each(|e| collect_helpers(e, helpers));
+        }
+        Js::Props(props) => {
+            for (key, val) in props {
+                collect_helpers(key, helpers);
+                collect_helpers(val, helpers);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cast;
+    use crate::converter::test::base_convert;
+
+    fn ssr_gen(s: &str) -> String {
+        let info = SFCInfo::default();
+        let ir = base_convert(s);
+        let option = CodeGenerateOption {
+            ssr: true,
+            ..Default::default()
+        };
+        let mut writer = SsrCodeWriter::new(vec![], Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        String::from_utf8(writer.writer).unwrap()
+    }
+
+    #[test]
+    fn test_ssr_interpolation() {
+        let s = ssr_gen("<div>{{ msg }}</div>");
+        let expected = r#"const { ssrRenderAttrs: _ssrRenderAttrs, ssrInterpolate: _ssrInterpolate } = require("vue/server-renderer")
+
+return function ssrRender(_ctx, _push, _parent, _attrs) {
+  with (_ctx) {
+    _push(`<div${_ssrRenderAttrs(_attrs)}>${_ssrInterpolate(msg)}</div>`)
+  }
+}"#;
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn test_ssr_escape_static_attr() {
+        let s = ssr_gen("<div><p title='a &lt; b' class='c &amp; d' :class=\"'e'\"/></div>");
+        let expected = r#"<p title="a &lt; b" class="c &amp; d e"></p>"#;
+        assert!(s.contains(expected), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_hoisted_in_place() {
+        let info = SFCInfo::default();
+        let mut ir = base_convert("<div><p class='a'>static</p>{{ a }}</div>");
+        let div = cast!(&mut ir.body[0], IRNode::VNodeCall);
+        let p = std::mem::replace(&mut div.children[0], IRNode::Hoisted(0));
+        ir.top_scope.hoist(p);
+        let option = CodeGenerateOption {
+            ssr: true,
+            ..Default::default()
+        };
+        let mut writer = SsrCodeWriter::new(vec![], Rc::new(option), &info);
+        writer.generate_root(ir).unwrap();
+        let s = String::from_utf8(writer.writer).unwrap();
+        assert!(s.contains(r#"><p class="a">static</p>${"#), "{}", s);
+        assert!(!s.contains("_hoisted"), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_static_element() {
+        let s = ssr_gen("<p class='a'>hello <br/>`${a}`</p><span :id='foo' hidden/>");
+        let expected = r#"_push(`<!--[--><p class="a">hello <br>\`\${a}\`</p>"#;
+        assert!(s.contains(expected), "{}", s);
+        assert!(s.contains(r#"<span hidden${_ssrRenderAttrs({"#), "{}", s);
+        assert!(s.contains("id: foo"), "{}", s);
+        assert!(s.contains("})}></span><!--]-->`)"), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_v_if() {
+        let s = ssr_gen("<p v-if='ok'>a</p><span v-else>b</span>");
+        let expected = r#"
+  with (_ctx) {
+    if (ok) {
+      _push(`<p${_ssrRenderAttrs(_attrs)}>a</p>`)
+    } else {
+      _push(`<span${_ssrRenderAttrs(_attrs)}>b</span>`)
+    }
+  }
+}"#;
+        assert!(s.ends_with(expected), "{}", s);
+        let s = ssr_gen("<div><p v-if='a'/><p v-else-if='b'/></div>");
+        let expected = r#"
+    _push(`<div${_ssrRenderAttrs(_attrs)}>`)
+    if (a) {
+      _push(`<p></p>`)
+    } else if (b) {
+      _push(`<p></p>`)
+    } else {
+      _push(`<!---->`)
+    }
+    _push(`</div>`)"#;
+        assert!(s.contains(expected), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_v_for() {
+        let s = ssr_gen("<li v-for='(item, i) in list'>{{ item }}</li>");
+        assert!(s.contains("ssrRenderList: _ssrRenderList"), "{}", s);
+        let expected = r#"
+    _push(`<!--[-->`)
+    _ssrRenderList(list, (item, i) => {
+      _push(`<li>${_ssrInterpolate(item)}</li>`)
+    })
+    _push(`<!--]-->`)"#;
+        assert!(s.contains(expected), "{}", s);
+    }
+}
@@ -2,20 +2,21 @@ use super::{
     SFCInfo,
     codegen::{CodeGenerateOption, CodeGenerator, CodeGen, ScriptMode, CodeGenInfo},
     converter::{
-        no_op_directive_convert, BaseConvertInfo as BaseInfo, BaseConverter, BaseRoot,
-        ConvertOption, Converter, DirConvertFn, V_BIND, V_MODEL,
+        BaseConvertInfo as BaseInfo, BaseConverter, BaseRoot, ComponentResolution, ConvertOption,
+        Converter, DirConvertFn, DirectiveConverter, BASE_DIR_CONVERTERS,
     },
-    error::{NoopErrorHandler, RcErrHandle},
-    flags::RuntimeHelper,
+    error::{CompilationError, NoopErrorHandler, RcErrHandle, VecErrorHandler},
+    flags::BuiltinComponent,
     parser::{Element, ParseOption, Parser, WhitespaceStrategy, AstRoot},
-    scanner::{ScanOption, Scanner, TextMode, Tokens},
+    scanner::{Locatable, ScanOption, Scanner, TextMode, Token, Tokens},
     transformer::{BaseTransformer, CorePass, TransformOption, Transformer},
     util::{no, yes},
-    Namespace,
+    Namespace, SourceLocation,
     transformer::{
         collect_entities::EntityCollector,
         mark_patch_flag::PatchFlagMarker,
         mark_slot_flag::SlotFlagMarker,
+        node_transform::{apply_node_transforms, NodeTransform},
         optimize_text::TextOptimizer,
         pass::{Scope, SharedInfoPasses},
         process_expression::ExpressionProcessor,
@@ -39,11 +40,23 @@ pub struct CompileOption {
     /// The pairing runtime provides additional built-in elements,
     /// Platform developer can use this to mark them as built-in
     /// so the compiler will generate component vnodes for them.
-    pub get_builtin_component: fn(&str) -> Option<RuntimeHelper>,
+    /// Framework authors can also register components imported
+    /// from other modules, e.g. `RouterView` from vue-router.
+    pub get_builtin_component: fn(&str) -> Option<BuiltinComponent>,
 
     /// Separate option for end users to extend the native elements list
     pub is_custom_element: fn(&str) -> bool,
 
+    /// For build tools to import user components instead of resolving them
+    /// at runtime, e.g. `import Foo from "./Foo.vue"`. Only used in module mode.
+    /// Returns None to fall back to `resolveComponent`.
+    pub resolve_component_type: fn(&str) -> Option<ComponentResolution>,
+
+    /// Enables Vue 2 behavior where `is` on native elements means component
+    /// without the `vue:` prefix.
+    /// @default false
+    pub compat: bool,
+
     /// Get tag namespace
     pub get_namespace: fn(&str, Option<&Element<'_>>) -> Namespace,
 
@@ -68,9 +81,17 @@ pub struct CompileOption {
     /// An object of { name: transform } to be applied to every directive attribute
     /// node found on element nodes.
     pub directive_converters: FxHashMap<&'static str, DirConvertFn>,
-    /// Hoist static VNodes and props objects to `_hoisted_x` constants
-    /// @default false
+    /// Custom transforms applied to every IR node before built-in passes,
+    /// e.g. auto-importing components or stripping dev-only attributes.
+    pub node_transforms: Vec<NodeTransform>,
+    /// Hoist static VNodes to `_hoisted_x` constants. Disabling it keeps
+    /// static subtrees inline, which is easier to debug. Ignored in SSR.
+    /// @default false, true for DOM module mode
     pub hoist_static: bool,
+    /// Stringify at least this many adjacent static elements into one
+    /// `createStaticVNode` call. Requires `hoist_static`.
+    /// @default 20
+    pub stringify_threshold: usize,
     /// Cache v-on handlers to avoid creating new inline functions on each render,
     /// also avoids the need for dynamically patching the handlers by wrapping it.
     /// e.g `@click="foo"` by default is compiled to `{ onClick: foo }`. With this
@@ -98,10 +119,12 @@ pub struct CompileOption {
     /// e.g. SSR can set it to false since SSR is executed only once per request.
     /// @default true
     pub need_reactivity: bool,
+    /// Generate SSR render function that pushes html strings.
+    /// @default false
+    pub ssr: bool,
     /// Custom error reporter. Default is noop.
     pub error_handler: RcErrHandle,
     // deleted options
-    // nodeTransforms?: NodeTransform[]
     // transformHoist?: HoistTransform | null
     // expressionPlugins?: ParserPlugin[]
     // prefix_identifiers: bool,
@@ -115,7 +138,6 @@ pub struct CompileOption {
     // slotted?: boolean
 
     // moved to SSR or need_reactivity
-    // ssr: bool // will be false in fallback node
     // inSSR?: bool // always true in ssr build
     // ssrCssVars?: string
     // ssrRuntimeModuleName?: string
@@ -123,16 +145,15 @@ pub struct CompileOption {
 
 impl Default for CompileOption {
     fn default() -> Self {
-        let mut directive_converters = FxHashMap::default();
-        directive_converters.insert(V_BIND.0, V_BIND.1);
-        directive_converters.insert(V_MODEL.0, V_MODEL.1);
-        directive_converters.insert("on", no_op_directive_convert);
+        let directive_converters = BASE_DIR_CONVERTERS.iter().copied().collect();
         Self {
             is_native_tag: yes,
             is_void_tag: no,
             is_pre_tag: no,
             get_builtin_component: |_| None,
             is_custom_element: no,
+            resolve_component_type: |_| None,
+            compat: false,
             get_namespace: |_, _| Namespace::Html,
             get_text_mode: |_| TextMode::Data,
             delimiters: ("{{".into(), "}}".into()),
@@ -141,7 +162,9 @@ impl Default for CompileOption {
             preserve_comments: None,
             is_dev: true,
             directive_converters,
+            node_transforms: vec![],
             hoist_static: false,
+            stringify_threshold: 20,
             cache_handlers: false,
             mode: ScriptMode::Function {
                 prefix_identifier: false,
@@ -149,12 +172,27 @@ impl Default for CompileOption {
             },
             source_map: false,
             need_reactivity: true,
+            ssr: false,
             error_handler: Rc::new(NoopErrorHandler),
         }
     }
 }
 
 impl CompileOption {
+    /// See [`ConvertOption::with_converter`].
+    #[must_use]
+    pub fn with_converter(mut self, converter: DirectiveConverter) -> Self {
+        let converting = self.converting().with_converter(converter);
+        self.directive_converters = converting.directive_converters;
+        self
+    }
+    /// See [`ConvertOption::without_converter`].
+    #[must_use]
+    pub fn without_converter(mut self, name: &str) -> Self {
+        let converting = self.converting().without_converter(name);
+        self.directive_converters = converting.directive_converters;
+        self
+    }
     pub fn scanning(&self) -> ScanOption {
         ScanOption {
             delimiters: self.delimiters.clone(),
@@ -172,11 +210,19 @@ impl CompileOption {
             is_pre_tag: self.is_pre_tag,
             get_builtin_component: self.get_builtin_component,
             is_custom_element: self.is_custom_element,
+            compat: self.compat,
         }
     }
     pub fn converting(&self) -> ConvertOption {
         ConvertOption {
             get_builtin_component: self.get_builtin_component,
+            is_custom_element: self.is_custom_element,
+            // imports can only be generated in module mode
+            resolve_component_type: match self.mode {
+                ScriptMode::Module { .. } => self.resolve_component_type,
+                ScriptMode::Function { .. } => |_| None,
+            },
+            compat: self.compat,
             is_dev: self.is_dev,
             directive_converters: self.directive_converters.clone(),
             need_reactivity: self.need_reactivity,
@@ -200,6 +246,7 @@ impl CompileOption {
             mode: self.mode.clone(),
             source_map: self.source_map,
             helper_strs: self.helper_strs,
+            ssr: self.ssr,
         }
     }
 }
@@ -285,6 +332,7 @@ where
         self.get_converter().convert_ir(ast, info)
     }
     fn transform(&self, ir: &mut Self::IR, info: Self::Info) {
+        apply_node_transforms(ir, &self.option.node_transforms);
         let pass = (self.passes)(info, &self.option);
         BaseTransformer::transform(ir, pass)
     }
@@ -304,6 +352,45 @@ where
     }
 }
 
+/// AST of a template and the diagnostics reported while parsing it.
+pub struct ParseResult<'a> {
+    pub ast: AstRoot<'a>,
+    pub errors: Vec<CompilationError>,
+    pub warnings: Vec<CompilationError>,
+}
+
+/// Scans and parses the template source into an AST.
+/// Errors and warnings are collected and returned alongside the AST instead
+/// of being reported to the option's error handler, which suits tooling like linters.
+pub fn parse<'a>(source: &'a str, option: &CompileOption) -> ParseResult<'a> {
+    let eh = Rc::new(VecErrorHandler::default());
+    let tokens = Scanner::new(option.scanning()).scan(source, eh.clone());
+    let ast = Parser::new(option.parsing()).parse(tokens, eh.clone());
+    let errors = std::mem::take(&mut *eh.error_mut());
+    let warnings = std::mem::take(&mut *eh.warning_mut());
+    ParseResult {
+        ast,
+        errors,
+        warnings,
+    }
+}
+
+/// Scans the template source into tokens and their locations, for tools
+/// that build their own trees. Location offsets count chars, not bytes.
+/// Diagnostics are dropped here, use [parse] to collect them.
+/// NB: without the parser, text in elements like `<textarea>` is scanned as data.
+pub fn tokenize<'a>(
+    source: &'a str,
+    option: &CompileOption,
+) -> impl Iterator<Item = (Token<'a>, SourceLocation)> {
+    let mut tokens = Scanner::new(option.scanning()).scan(source, Rc::new(NoopErrorHandler));
+    std::iter::from_fn(move || {
+        let token = tokens.next()?;
+        let start = tokens.last_position();
+        Some((token, tokens.get_location_from(start)))
+    })
+}
+
 pub fn get_base_passes<'a>(
     sfc_info: &'a SFCInfo<'a>,
     opt: &CompileOption,
@@ -329,3 +416,332 @@ pub fn get_base_passes<'a>(
         },
     ]
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::flags::RuntimeHelper;
+    use crate::error::CompilationErrorKind as ErrorKind;
+    use crate::parser::AstNode;
+
+    #[test]
+    fn test_parse_api() {
+        let result = parse("<p>{{a}}</p>", &CompileOption::default());
+        assert!(result.errors.is_empty());
+        assert!(result.warnings.is_empty());
+        assert_eq!(result.ast.children.len(), 1);
+        assert!(matches!(result.ast.children[0], AstNode::Element(_)));
+    }
+
+    #[test]
+    fn test_parse_api_errors() {
+        let result = parse("<div>", &CompileOption::default());
+        assert_eq!(result.ast.children.len(), 1);
+        assert_eq!(result.errors.len(), 1);
+        assert!(matches!(result.errors[0].kind, ErrorKind::MissingEndTag));
+        let result = parse("<p/><!DOCTYPE html>", &CompileOption::default());
+        assert!(result.errors.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+        assert!(matches!(
+            result.warnings[0].kind,
+            ErrorKind::UnexpectedDoctype
+        ));
+    }
+
+    #[test]
+    fn test_tokenize_api() {
+        // offsets count chars so slice by char index
+        let src = "<p title='é'>你好{{ a }}</p>";
+        let chars: Vec<_> = src.chars().collect();
+        let spans: Vec<String> = tokenize(src, &CompileOption::default())
+            .map(|(_, loc)| chars[loc.start.offset..loc.end.offset].iter().collect())
+            .collect();
+        assert_eq!(spans, ["<p title='é'>", "你好", "{{ a }}", "</p>"]);
+        let kinds: Vec<_> = tokenize(src, &CompileOption::default())
+            .map(|(t, _)| match t {
+                Token::StartTag(_) => "start",
+                Token::EndTag(_) => "end",
+                Token::Text(_) => "text",
+                Token::Interpolation(_) => "interpolation",
+                Token::Comment(_) => "comment",
+            })
+            .collect();
+        assert_eq!(kinds, ["start", "text", "interpolation", "end"]);
+    }
+
+    fn ssr_compile(template: &str) -> String {
+        let option = CompileOption {
+            is_native_tag: |s| s != "comp",
+            ssr: true,
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let out = compiler.compile(template, &sfc_info).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_ssr_component() {
+        let s = ssr_compile("<comp :a='b'>hello</comp>");
+        assert!(
+            s.contains(r#"const _component_comp = _resolveComponent("comp")"#),
+            "{}",
+            s
+        );
+        assert!(
+            s.contains("_push(_ssrRenderComponent(_component_comp, _mergeProps({"),
+            "{}",
+            s
+        );
+        let slot = r#"
+      default: _withCtx((_, _push, _parent, _scopeId) => {
+        _push(`hello`)
+      }),
+      _: 1 /*Stable*/
+    }, _parent))"#;
+        assert!(s.contains(slot), "{}", s);
+        let s = ssr_compile("<div><comp/></div>");
+        assert!(
+            s.contains("_ssrRenderComponent(_component_comp, null, null, _parent)"),
+            "{}",
+            s
+        );
+    }
+
+    #[test]
+    fn test_ssr_alterable_slots() {
+        let template = "<comp><template v-if='ok' #a>a</template><template v-for='i in l' #[i]>{{i}}</template></comp>";
+        let s = ssr_compile(template);
+        assert!(s.contains("_createSlots({"), "{}", s);
+        assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+        assert!(s.contains("(ok) ? {"), "{}", s);
+        assert!(s.contains("name: \"a\","), "{}", s);
+        assert!(s.contains("} : undefined,"), "{}", s);
+        assert!(s.contains("_renderList(l, (i) => {"), "{}", s);
+        assert!(
+            s.contains("fn: _withCtx((_, _push, _parent, _scopeId) => {"),
+            "{}",
+            s
+        );
+    }
+
+    #[test]
+    fn test_ssr_slot_outlet() {
+        let s = ssr_compile("<div><slot name='a'>fallback</slot><slot/></div>");
+        assert!(
+            s.contains(r#"_ssrRenderSlot($slots, "a", {}, () => {"#),
+            "{}",
+            s
+        );
+        assert!(s.contains("_push(`fallback`)"), "{}", s);
+        let outlet = r#"_ssrRenderSlot($slots, "default", {}, null, _push, _parent)"#;
+        assert!(s.contains(outlet), "{}", s);
+    }
+
+    #[test]
+    fn test_ssr_builtin_components() {
+        let s = ssr_compile("<teleport to='#modal' disabled><p/></teleport>");
+        assert!(
+            s.contains("_ssrRenderTeleport(_push, (_push) => {"),
+            "{}",
+            s
+        );
+        assert!(s.contains(r##"}, "#modal", true, _parent)"##), "{}", s);
+        let s = ssr_compile("<Suspense><p/><template #fallback>loading</template></Suspense>");
+        assert!(s.contains("_ssrRenderSuspense(_push, {"), "{}", s);
+        assert!(s.contains("fallback: () => {"), "{}", s);
+        assert!(!s.contains("_withCtx"), "{}", s);
+        let s = ssr_compile("<keep-alive><comp/></keep-alive>");
+        assert!(
+            s.contains("_ssrRenderComponent(_component_comp, _attrs, null, _parent)"),
+            "{}",
+            s
+        );
+    }
+
+    #[test]
+    fn test_user_builtin_component() {
+        let option = CompileOption {
+            get_builtin_component: |tag| match tag {
+                "RouterView" | "router-view" => Some(BuiltinComponent::Import {
+                    name: "RouterView",
+                    path: "vue-router",
+                }),
+                _ => None,
+            },
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let template = "<div><RouterView/><router-view/></div>";
+        let out = compiler.compile(template, &sfc_info).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        let import = "import { RouterView as _imported_RouterView } from \"vue-router\"";
+        assert_eq!(s.matches(import).count(), 1, "{}", s);
+        assert_eq!(
+            s.matches("_createVNode(_imported_RouterView)").count(),
+            2,
+            "{}",
+            s
+        );
+        assert!(!s.contains("resolveComponent"), "{}", s);
+    }
+
+    fn compile(template: &str) -> String {
+        let option = CompileOption {
+            is_native_tag: |s| s != "comp",
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let out = compiler.compile(template, &sfc_info).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_component_type() {
+        fn resolve(tag: &str) -> Option<ComponentResolution> {
+            (tag == "Foo").then(|| ComponentResolution {
+                path: format!("./components/{}.vue", tag),
+            })
+        }
+        let option = CompileOption {
+            is_native_tag: |s| s != "comp",
+            resolve_component_type: resolve,
+            mode: ScriptMode::Module {
+                runtime_module_name: "vue".into(),
+            },
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let template = "<div><Foo/><Foo/><Bar/></div>";
+        let out = compiler.compile(template, &sfc_info).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        let import = "import _imported_Foo from \"./components/Foo.vue\"";
+        assert_eq!(s.matches(import).count(), 1, "{}", s);
+        assert_eq!(s.matches("_createVNode(_imported_Foo)").count(), 2, "{}", s);
+        assert!(!s.contains("_resolveComponent(\"Foo\")"), "{}", s);
+        assert!(s.contains("_resolveComponent(\"Bar\")"), "{}", s);
+    }
+
+    #[test]
+    fn test_converter_helper() {
+        use crate::converter::{
+            ConvertContext, CoreDirConvRet, Directive, DirectiveConvertResult, ErrorHandler,
+        };
+        fn convert_cap<'a>(
+            _: &mut Directive<'a>,
+            _: &mut Element<'a>,
+            _: &dyn ErrorHandler,
+            ctx: &ConvertContext<'a>,
+        ) -> CoreDirConvRet<'a> {
+            ctx.collect_helper(RuntimeHelper::CAPITALIZE);
+            DirectiveConvertResult::Dropped
+        }
+        let option = CompileOption::default().with_converter(("cap", convert_cap));
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let out = compiler.compile("<p v-cap/>", &sfc_info).unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert!(s.contains("capitalize: _capitalize"), "{}", s);
+    }
+
+    #[test]
+    fn test_converter_cache_and_hoist() {
+        use crate::converter::{
+            ConvertContext, CoreDirConvRet, Directive, DirectiveConvertResult, ErrorHandler,
+        };
+        use crate::ir::{IRNode, JsExpr};
+        fn convert_mark<'a>(
+            _: &mut Directive<'a>,
+            _: &mut Element<'a>,
+            _: &dyn ErrorHandler,
+            ctx: &ConvertContext<'a>,
+        ) -> CoreDirConvRet<'a> {
+            assert_eq!(ctx.next_cache_index(), 0);
+            assert_eq!(ctx.hoist(IRNode::CommentCall("mark")), 0);
+            ctx.collect_helper(RuntimeHelper::CREATE_COMMENT);
+            DirectiveConvertResult::Converted {
+                value: JsExpr::Src("_cache[0]"),
+                runtime: Err(false),
+            }
+        }
+        let option = CompileOption::default().with_converter(("mark", convert_mark));
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let out = compiler
+            .compile("<p v-mark/><p v-once/>", &sfc_info)
+            .unwrap();
+        let s = String::from_utf8(out).unwrap();
+        assert!(
+            s.contains("const _hoisted_1 = _createCommentVNode(\"mark\")"),
+            "{}",
+            s
+        );
+        assert!(!s.contains("_cache[0] = "), "{}", s);
+        assert!(s.contains("_cache[1] = "), "{}", s);
+    }
+
+    #[test]
+    fn test_slot_flag_codegen() {
+        let s = compile("<comp>a</comp>");
+        assert!(s.contains("_: 1 /*Stable*/"), "{}", s);
+        let s = compile("<comp><slot/></comp>");
+        assert!(s.contains("_: 3 /*Forwarded*/"), "{}", s);
+        assert!(!s.contains("DYNAMIC_SLOTS"), "{}", s);
+        let s = compile("<comp><template #a v-if='ok'><slot/></template></comp>");
+        assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+    }
+
+    #[test]
+    fn test_dynamic_slot_name() {
+        let s = compile("<comp><template #[name]>a</template></comp>");
+        assert!(!s.contains("_createSlots("), "{}", s);
+        assert!(s.contains("[name]: _withCtx(() => ["), "{}", s);
+        assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+        assert!(s.contains("1024 /*DYNAMIC_SLOTS*/"), "{}", s);
+    }
+
+    #[test]
+    fn test_conditional_slot_template() {
+        let s = compile("<comp><template v-if='ok' #a>a</template></comp>");
+        assert!(s.contains("_createSlots({"), "{}", s);
+        assert!(s.contains("_: 2 /*Dynamic*/"), "{}", s);
+        assert!(s.contains("(ok)"), "{}", s);
+        assert!(s.contains("name: \"a\","), "{}", s);
+        assert!(s.contains("fn: _withCtx(() => ["), "{}", s);
+        assert!(s.contains(": undefined"), "{}", s);
+        assert!(!s.contains("_createCommentVNode"), "{}", s);
+        assert!(s.contains("1024 /*DYNAMIC_SLOTS*/"), "{}", s);
+    }
+
+    #[test]
+    fn test_iterated_slot_template() {
+        let s = compile("<comp><template v-for='i in list' #[i]>{{i}}</template></comp>");
+        assert!(s.contains("_createSlots({"), "{}", s);
+        assert!(s.contains("_renderList(list, (i) => {"), "{}", s);
+        assert!(s.contains("name: i,"), "{}", s);
+        assert!(!s.contains("_Fragment"), "{}", s);
+        assert!(s.contains("1024 /*DYNAMIC_SLOTS*/"), "{}", s);
+    }
+
+    #[test]
+    fn test_mixed_dynamic_slots() {
+        let s = compile(
+            "<comp>\
+                <template #[name]>a</template>\
+                <template v-if='ok' #b>b</template>\
+                <template v-for='i in list' #[i]>c</template>\
+            </comp>",
+        );
+        assert_eq!(s.matches("_createSlots(").count(), 1, "{}", s);
+        assert!(s.contains("[name]: _withCtx("), "{}", s);
+        assert!(s.contains("name: \"b\","), "{}", s);
+        assert!(s.contains("_renderList(list, (i) => {"), "{}", s);
+    }
+}
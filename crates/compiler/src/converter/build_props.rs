@@ -1,12 +1,14 @@
 use super::{BaseConversion as BC, CoreConversion, Element, VStr};
 use crate::{
+    error::{CompilationError, CompilationErrorKind as ErrorKind},
     flags::{self, PatchFlag, RuntimeHelper},
-    ir::{JsExpr as Js, Prop},
-    parser::{Directive, ElemProp},
+    ir::{self, JsExpr as Js, Prop},
+    parser::{Directive, ElemProp, ElementType},
     scanner::Attribute,
-    util::{self, is_bind_key, is_component_tag, is_reserved_prop},
+    util::{self, is_bind_key, is_boolean_attr, is_component_tag, is_reserved_prop},
+    Namespace, SourceLocation,
 };
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
 use std::iter::IntoIterator;
 use std::mem;
 
@@ -14,7 +16,8 @@ pub struct BuildProps<'a> {
     pub props: Option<Js<'a>>,
     pub directives: Dirs<'a>,
     pub patch_flag: PatchFlag,
-    pub dynamic_props: FxHashSet<VStr<'a>>,
+    /// prop keys in source order, empty if FULL_PROPS is needed
+    pub dynamic_props: Vec<VStr<'a>>,
 }
 
 #[derive(Default)]
@@ -42,7 +45,7 @@ struct PropArgs<'a> {
 struct CollectProps<'a> {
     prop_args: PropArgs<'a>,
     runtime_dirs: Dirs<'a>,
-    dynamic_props: FxHashSet<VStr<'a>>,
+    dynamic_props: Vec<VStr<'a>>,
     prop_flags: PropFlags,
 }
 
@@ -68,13 +71,22 @@ where
         ElemProp::Dir(dir) => collect_dir(bc, e, dir, &mut cp),
         ElemProp::Attr(attr) => collect_attr(bc, e, attr, &mut cp),
     });
+    // components receive scope id from _withScopeId instead of attribute
+    if let (Some(id), ElementType::Plain) = (&bc.sfc_info.scope_id, &e.tag_type) {
+        let scope_attr = (Js::str_lit(id.as_str()), Js::str_lit(""));
+        cp.prop_args.pending_props.push(scope_attr);
+    }
     let prop_expr = compute_prop_expr(cp.prop_args);
     let CollectProps {
         runtime_dirs,
-        dynamic_props,
+        mut dynamic_props,
         ..
     } = cp;
     let patch_flag = build_patch_flag(cp.prop_flags, &runtime_dirs, &dynamic_props);
+    // runtime must diff all props if any key is unknown
+    if patch_flag.contains(PatchFlag::FULL_PROPS) {
+        dynamic_props.clear();
+    }
     BuildProps {
         props: prop_expr,
         directives: runtime_dirs,
@@ -84,33 +96,61 @@ where
 }
 
 fn collect_attr<'a>(bc: &BC, e: &Element<'a>, attr: Attribute<'a>, cp: &mut CollectProps<'a>) {
-    let Attribute { name, value, .. } = attr;
+    let Attribute {
+        name,
+        value,
+        name_loc,
+        location,
+    } = attr;
+    if e.tag_type == ElementType::Plain && e.namespace == Namespace::Html {
+        check_attr_case(bc, name, name_loc);
+    }
     let val = match value {
         Some(v) => v.content,
         None => VStr::raw(""),
     };
     // skip dynamic component is
-    if name == "is" && (is_component_tag(e.tag_name) || val.starts_with("vue:")) {
+    let is_component_is = is_component_tag(e.tag_name) || val.starts_with("vue:");
+    if name == "is" && (is_component_is || bc.option.compat) {
         return;
     }
-    let mut value_expr = Js::StrLit(val);
+    // bare boolean attribute on native element means true
+    let is_true_attr = e.tag_type == ElementType::Plain && is_boolean_attr(name);
+    let mut value_expr = if is_true_attr && val.is_empty() {
+        Js::Src("true")
+    } else {
+        Js::StrLit(val)
+    };
     if name == "ref" {
         cp.prop_flags.has_ref = true;
+        if bc.in_v_for() {
+            push_ref_for_marker(cp);
+        }
         if bc.sfc_info.inline && !val.is_empty() {
-            value_expr = process_inline_ref(val);
+            value_expr = process_inline_ref(bc, val, cp);
         }
     }
-    cp.prop_args
-        .pending_props
-        .push((Js::str_lit(name), value_expr));
+    let key = Js::str_lit(name);
+    check_duplicate_prop(bc, cp, &key, &location);
+    cp.prop_args.pending_props.push((key, value_expr));
+}
+
+// e.g. viewBox on html element will become viewbox in browser
+fn check_attr_case(bc: &BC, name: &str, loc: SourceLocation) {
+    if name.contains(|c: char| c.is_ascii_uppercase()) {
+        let warning = CompilationError::new(ErrorKind::AttributeCaseInsensitive).with_location(loc);
+        bc.emit_warning(warning);
+    }
 }
 
 #[inline]
 fn is_pre_convert_dir(s: &str) -> bool {
     match s.len() {
         2 => s == "if" || s == "is",
+        // v-pre is consumed in parser
+        3 => s == "for" || s == "pre",
         4 => ["slot", "memo", "once"].contains(&s),
-        _ => s == "for",
+        _ => false,
     }
 }
 
@@ -130,18 +170,25 @@ fn collect_dir<'a>(
     if is_bind_key(argument, "is") && is_component_tag(e.tag_name) {
         return; // skip <component :is="c"/>
     }
+    if is_bind_key(argument, "ref") && bc.in_v_for() {
+        push_ref_for_marker(cp);
+    }
     let (value, runtime) = match bc.convert_directive(&mut dir, e) {
         DirConv::Converted { value, runtime } => (value, runtime),
         DirConv::Preserve => return cp.runtime_dirs.push((dir, None)),
         DirConv::Dropped => return,
     };
+    let location = dir.location.clone();
     match runtime {
         Ok(helper) => cp.runtime_dirs.push((dir, Some(helper))),
         Err(true) => cp.runtime_dirs.push((dir, None)),
         Err(false) => (),
     }
     if let Js::Props(props) = value {
-        props.iter().for_each(|p| analyze_patch_flag(p, cp));
+        for p in props.iter() {
+            check_duplicate_prop(bc, cp, &p.0, &location);
+            analyze_patch_flag(p, cp);
+        }
         cp.prop_args.pending_props.extend(props);
         return;
     }
@@ -151,6 +198,26 @@ fn collect_dir<'a>(
     cp.prop_args.merge_args.push(value);
 }
 
+// e.g. id="a" :id="b". class, style and handlers are merged instead.
+fn check_duplicate_prop(bc: &BC, cp: &CollectProps, key: &Js, loc: &SourceLocation) {
+    let name = match key {
+        Js::StrLit(name) => name,
+        _ => return,
+    };
+    if util::is_mergeable_prop(name) || VStr::is_handler(name) {
+        return;
+    }
+    let pending = &cp.prop_args.pending_props;
+    if pending
+        .iter()
+        .any(|(k, _)| matches!(k, Js::StrLit(n) if n == name))
+    {
+        let warning =
+            CompilationError::new(ErrorKind::DuplicateProperty).with_location(loc.clone());
+        bc.emit_warning(warning);
+    }
+}
+
 fn flush_pending_props(prop_args: &mut PropArgs) {
     // flush existing props to an object
     if prop_args.pending_props.is_empty() {
@@ -161,8 +228,26 @@ fn flush_pending_props(prop_args: &mut PropArgs) {
     prop_args.merge_args.push(Js::Props(arg));
 }
 
-fn process_inline_ref(_val: VStr) -> Js {
-    todo!("setup binding is pending")
+// refs in v-for are collected into an array instead of overriding each other
+fn push_ref_for_marker(cp: &mut CollectProps) {
+    let marker = (Js::str_lit("ref_for"), Js::Src("true"));
+    cp.prop_args.pending_props.push(marker);
+}
+
+// inline mode has no setupState so string ref cannot be resolved.
+// pass the setup binding itself and keep the name in ref_key.
+fn process_inline_ref<'a>(bc: &BC, val: VStr<'a>, cp: &mut CollectProps<'a>) -> Js<'a> {
+    use crate::BindingTypes as BT;
+    let binding = bc.sfc_info.binding_metadata.get(val.raw);
+    if !matches!(
+        binding,
+        Some(BT::SetupRef | BT::SetupLet | BT::SetupMaybeRef)
+    ) {
+        return Js::StrLit(val);
+    }
+    let ref_key = (Js::str_lit("ref_key"), Js::StrLit(val));
+    cp.prop_args.pending_props.push(ref_key);
+    Js::Src(val.raw)
 }
 
 fn dedupe_properties(props: Props) -> Props {
@@ -177,10 +262,11 @@ fn dedupe_properties(props: Props) -> Props {
             }
         };
         if let Some(&i) = known_props.get(name) {
-            if util::is_mergeable_prop(name) {
+            // e.g. @click="a" @click="b" becomes onClick: [a, b]
+            if util::is_mergeable_prop(name) || VStr::is_handler(name) {
                 merge_as_array(&mut ret[i], val);
             }
-            // TODO: should remove by parser
+            // duplicate is reported in check_duplicate_prop
         } else {
             known_props.insert(*name, ret.len());
             ret.push((key, val));
@@ -195,7 +281,7 @@ fn merge_as_array<'a>(existing: &mut Prop<'a>, incoming: Js<'a>) {
         arr.push(incoming);
     } else {
         let v = mem::replace(val, Js::Src(""));
-        let mut arr = Js::Array(vec![v]);
+        let mut arr = Js::Array(vec![v, incoming]);
         mem::swap(val, &mut arr);
     }
 }
@@ -208,12 +294,98 @@ fn compute_prop_expr(mut prop_args: PropArgs) -> Option<Js> {
     } = prop_args;
     debug_assert!(pending_props.is_empty());
     if merge_args.len() <= 1 {
-        merge_args.into_iter().next()
+        let mut prop_expr = merge_args.into_iter().next();
+        if let Some(Js::Props(props)) = &mut prop_expr {
+            normalize_class_style(props);
+        }
+        prop_expr
     } else {
         Some(Js::Call(RuntimeHelper::MERGE_PROPS, merge_args))
     }
 }
 
+/// normalize dynamic class/style for faster runtime patching. e.g.
+/// class="a b" :class="cls" becomes class: normalizeClass(["a b", cls])
+fn normalize_class_style(props: &mut Props) {
+    let is_handler = |v: &Js| matches!(v, Js::FuncSimple { .. } | Js::FuncCompound { .. });
+    // props with dynamic keys are normalized by normalizeProps at runtime
+    let has_dynamic_key = props
+        .iter()
+        .any(|(k, v)| !matches!(k, Js::StrLit(_)) && !is_handler(v));
+    if has_dynamic_key {
+        return;
+    }
+    for (key, val) in props.iter_mut() {
+        let helper = match key {
+            Js::StrLit(k) if k.raw == "class" => RuntimeHelper::NORMALIZE_CLASS,
+            Js::StrLit(k) if k.raw == "style" => RuntimeHelper::NORMALIZE_STYLE,
+            _ => continue,
+        };
+        // static string or style object parsed from literal
+        if matches!(val, Js::StrLit(_) | Js::Props(_)) {
+            continue;
+        }
+        let sep = if helper == RuntimeHelper::NORMALIZE_CLASS {
+            " "
+        } else {
+            ";"
+        };
+        let v = mem::take(val);
+        *val = match fold_static_class_style(v, sep) {
+            Ok(folded) => folded,
+            Err(v) => Js::Call(helper, vec![v]),
+        };
+    }
+}
+
+/// static class/style merge needs no runtime normalization. e.g.
+/// class="a" :class="'b'" becomes class: "a b"
+fn fold_static_class_style<'a>(val: Js<'a>, sep: &'static str) -> Result<Js<'a>, Js<'a>> {
+    let all_static = match &val {
+        Js::Array(arr) => arr.iter().all(|v| as_str_lit(v).is_some()),
+        v => as_str_lit(v).is_some(),
+    };
+    if !all_static {
+        return Err(val);
+    }
+    let lits = match val {
+        Js::Array(arr) => arr,
+        v => vec![v],
+    };
+    let mut segments = vec![];
+    for lit in lits {
+        let lit = as_str_lit(&lit).expect("checked above");
+        if lit.raw.trim().is_empty() {
+            continue;
+        }
+        if !segments.is_empty() {
+            segments.push(Js::str_lit(sep));
+        }
+        segments.push(Js::StrLit(lit));
+    }
+    Ok(ir::fold_constant(Js::Compound(segments)))
+}
+
+/// string literal or a quoted string in expression, e.g. :class="'a'"
+fn as_str_lit<'a>(v: &Js<'a>) -> Option<VStr<'a>> {
+    match v {
+        Js::StrLit(s) => Some(*s),
+        Js::Simple(s, _) => {
+            let raw = s.raw.trim();
+            let quote = raw.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+            let inner = raw.strip_prefix(quote)?.strip_suffix(quote)?;
+            if inner.contains(|c| c == quote || c == '\\') {
+                return None;
+            }
+            Some(VStr {
+                raw: inner,
+                ops: s.ops,
+            })
+        }
+        _ => None,
+    }
+}
+
 fn analyze_patch_flag<'a>(p: &Prop<'a>, cp: &mut CollectProps<'a>) {
     let is_component = cp.prop_flags.is_component;
     let flags = &mut cp.prop_flags;
@@ -235,7 +407,8 @@ fn analyze_patch_flag<'a>(p: &Prop<'a>, cp: &mut CollectProps<'a>) {
     if is_event_handler && is_reserved_prop(name) {
         flags.has_vnode_hook = true;
     }
-    if val.static_level() > flags::StaticLevel::NotStatic {
+    // literal constant like :count="42" never changes
+    if val.static_level() > flags::StaticLevel::NotStatic || val.is_static() {
         return;
     }
     match name.raw {
@@ -243,19 +416,23 @@ fn analyze_patch_flag<'a>(p: &Prop<'a>, cp: &mut CollectProps<'a>) {
         "class" => flags.has_class_binding = true,
         "style" => flags.has_style_binding = true,
         "key" => (),
-        _ => {
-            cp.dynamic_props.insert(*name);
-        }
+        _ => add_dynamic_prop(cp, *name),
     }
     if is_component && (["class", "style"].contains(&name.raw)) {
-        cp.dynamic_props.insert(*name);
+        add_dynamic_prop(cp, *name);
+    }
+}
+
+fn add_dynamic_prop<'a>(cp: &mut CollectProps<'a>, name: VStr<'a>) {
+    if !cp.dynamic_props.contains(&name) {
+        cp.dynamic_props.push(name);
     }
 }
 
 fn build_patch_flag<'a>(
     f: PropFlags,
     runtime_dirs: &[Dir<'a>],
-    dynamic_names: &FxHashSet<VStr<'a>>,
+    dynamic_names: &[VStr<'a>],
 ) -> PatchFlag {
     if f.has_dynamic_keys {
         return PatchFlag::FULL_PROPS;
@@ -281,3 +458,440 @@ fn build_patch_flag<'a>(
     }
     patch_flag
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{base_convert, diagnose_convert, handler_convert, test_conversion};
+    use super::*;
+    use crate::{cast, ir::IRNode};
+
+    fn get_props(s: &str) -> Props {
+        let mut body = base_convert(s).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        cast!(vn.props.expect("should have props"), Js::Props)
+    }
+
+    #[test]
+    fn test_class_merge() {
+        let props = get_props("<p class='a b' :class='cls'/>");
+        assert_eq!(props.len(), 1);
+        let (helper, args) = match &props[0].1 {
+            Js::Call(h, args) => (h, args),
+            _ => panic!("class should be normalized"),
+        };
+        assert_eq!(*helper, RuntimeHelper::NORMALIZE_CLASS);
+        let arr = cast!(&args[0], Js::Array);
+        assert_eq!(arr.len(), 2);
+        assert!(matches!(arr[0], Js::StrLit(_)));
+        assert!(matches!(arr[1], Js::Simple(..)));
+        let props = get_props("<p class='a' :class='b' :class='c'/>");
+        let args = match &props[0].1 {
+            Js::Call(_, args) => args,
+            _ => panic!("class should be normalized"),
+        };
+        assert_eq!(cast!(&args[0], Js::Array).len(), 3);
+    }
+
+    #[test]
+    fn test_style_merge() {
+        let props = get_props("<p style='color: red' :style='stl'/>");
+        assert_eq!(props.len(), 1);
+        let (helper, args) = match &props[0].1 {
+            Js::Call(h, args) => (h, args),
+            _ => panic!("style should be normalized"),
+        };
+        assert_eq!(*helper, RuntimeHelper::NORMALIZE_STYLE);
+        assert_eq!(cast!(&args[0], Js::Array).len(), 2);
+    }
+
+    #[test]
+    fn test_static_class() {
+        let props = get_props("<p class='a b'/>");
+        assert!(matches!(props[0].1, Js::StrLit(_)));
+    }
+
+    #[test]
+    fn test_fold_static_class_style() {
+        let props =
+            get_props("<p class='a' :class=\"'b'\" style='color: red' :style='\"top: 0\"'/>");
+        assert_eq!(props.len(), 2);
+        let joined = |v: &Js| match v {
+            Js::Joined { text, quoted: true } => text.clone(),
+            _ => panic!("static class/style should be one literal"),
+        };
+        assert_eq!(joined(&props[0].1), "a b");
+        assert_eq!(joined(&props[1].1), "color: red;top: 0");
+        let props = Js::Props(props);
+        assert!(props.static_level() == flags::StaticLevel::CanStringify);
+        // single quoted class is folded into a plain string literal
+        let props = get_props("<p :class=\"'b'\"/>");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "b");
+        // dynamic class is still normalized
+        let props = get_props("<p class='a' :class=\"'b' + c\"/>");
+        assert!(matches!(
+            props[0].1,
+            Js::Call(RuntimeHelper::NORMALIZE_CLASS, _)
+        ));
+    }
+
+    #[test]
+    fn test_dynamic_key_with_static() {
+        let mut body = base_convert("<p id='x' :[k]='v'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, PatchFlag::FULL_PROPS);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 2);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "id");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "x");
+        // computed key is evaluated at runtime
+        let key = cast!(&props[1].0, Js::Compound);
+        assert_eq!(cast!(&key[1], Js::Simple).raw, "k");
+        assert_eq!(cast!(&props[1].1, Js::Simple).raw, "v");
+    }
+
+    #[test]
+    fn test_v_bind_spread() {
+        let mut body = base_convert("<p v-bind='obj'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let obj = cast!(vn.props.unwrap(), Js::Simple);
+        assert_eq!(obj.raw, "obj");
+        assert_eq!(vn.patch_flag, PatchFlag::FULL_PROPS);
+    }
+
+    #[test]
+    fn test_v_bind_spread_merge() {
+        let mut body = base_convert("<p v-bind='obj' :id='id'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, PatchFlag::FULL_PROPS);
+        let (helper, args) = match vn.props.unwrap() {
+            Js::Call(h, args) => (h, args),
+            _ => panic!("spread should be merged"),
+        };
+        assert_eq!(helper, RuntimeHelper::MERGE_PROPS);
+        assert_eq!(args.len(), 2);
+        assert!(matches!(args[0], Js::Simple(..)));
+        let props = cast!(&args[1], Js::Props);
+        let id = cast!(&props[0].0, Js::StrLit);
+        assert_eq!(id.raw, "id");
+    }
+
+    #[test]
+    fn test_no_props() {
+        let mut body = base_convert("<p/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+    }
+
+    #[test]
+    fn test_static_props() {
+        let props = get_props("<p id='a' title='b'/>");
+        let keys: Vec<_> = props
+            .iter()
+            .map(|(k, _)| cast!(k, Js::StrLit).raw)
+            .collect();
+        assert_eq!(keys, vec!["id", "title"]);
+        let vals: Vec<_> = props
+            .iter()
+            .map(|(_, v)| cast!(v, Js::StrLit).raw)
+            .collect();
+        assert_eq!(vals, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_props_source_order() {
+        let mut body = base_convert("<p id='a' :title='t' v-bind='obj' class='c'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let args = match vn.props.unwrap() {
+            Js::Call(RuntimeHelper::MERGE_PROPS, args) => args,
+            _ => panic!("spread should be merged"),
+        };
+        assert_eq!(args.len(), 3);
+        let before = cast!(&args[0], Js::Props);
+        assert_eq!(before.len(), 2);
+        assert_eq!(cast!(&before[0].0, Js::StrLit).raw, "id");
+        assert_eq!(cast!(&before[1].0, Js::StrLit).raw, "title");
+        assert!(matches!(before[1].1, Js::Simple(v, _) if v.raw == "t"));
+        assert!(matches!(args[1], Js::Simple(v, _) if v.raw == "obj"));
+        let after = cast!(&args[2], Js::Props);
+        assert_eq!(after.len(), 1);
+        assert_eq!(cast!(&after[0].0, Js::StrLit).raw, "class");
+    }
+
+    #[test]
+    fn test_merge_handlers() {
+        let mut body = handler_convert("<p @click='a' @click='b'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 1);
+        let key = cast!(&props[0].0, Js::StrLit);
+        assert_eq!(key.into_string(), "onClick");
+        let handlers = cast!(&props[0].1, Js::Array);
+        assert_eq!(handlers.len(), 2);
+        assert!(matches!(handlers[0], Js::FuncSimple { src, .. } if src.raw == "a"));
+        assert!(matches!(handlers[1], Js::FuncSimple { src, .. } if src.raw == "b"));
+    }
+
+    #[test]
+    fn test_static_ref() {
+        let mut body = base_convert("<p ref='foo'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, PatchFlag::NEED_PATCH);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 1);
+        let val = cast!(&props[0].1, Js::StrLit);
+        assert_eq!(val.raw, "foo");
+    }
+
+    #[test]
+    fn test_dynamic_ref() {
+        let mut body = base_convert("<p :ref='fooRef'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, PatchFlag::NEED_PATCH);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        let val = cast!(&props[0].1, Js::Simple);
+        assert_eq!(val.raw, "fooRef");
+    }
+
+    #[test]
+    fn test_ref_in_v_for() {
+        for s in [
+            "<p v-for='i in a' ref='foo'/>",
+            "<div v-for='i in a'><p :ref='foo'/></div>",
+        ] {
+            let mut body = base_convert(s).body;
+            let for_node = cast!(body.remove(0), IRNode::For);
+            let mut vn = cast!(*for_node.child, IRNode::VNodeCall);
+            if !vn.children.is_empty() {
+                vn = cast!(vn.children.remove(0), IRNode::VNodeCall);
+            }
+            let props = cast!(vn.props.unwrap(), Js::Props);
+            assert_eq!(props.len(), 2);
+            let key = cast!(&props[0].0, Js::StrLit);
+            assert_eq!(key.raw, "ref_for");
+            assert!(matches!(props[0].1, Js::Src("true")));
+        }
+        let mut body = base_convert("<div v-for='i in a'/><p ref='foo'/>").body;
+        let vn = cast!(body.remove(1), IRNode::VNodeCall);
+        assert_eq!(cast!(vn.props.unwrap(), Js::Props).len(), 1);
+    }
+
+    #[test]
+    fn test_ref_in_nested_v_for() {
+        fn ref_keys(props: Option<Js>) -> Vec<&str> {
+            let props = cast!(props.unwrap(), Js::Props);
+            props.iter().map(|p| cast!(&p.0, Js::StrLit).raw).collect()
+        }
+        let case = "<div v-for='i in a'><p v-for='j in i'><i ref='x'/></p><b ref='y'/></div>";
+        let mut body = base_convert(case).body;
+        let outer = cast!(body.remove(0), IRNode::For);
+        let mut div = cast!(*outer.child, IRNode::VNodeCall);
+        let b = cast!(div.children.remove(1), IRNode::VNodeCall);
+        assert_eq!(ref_keys(b.props), vec!["ref_for", "ref"]);
+        let inner = cast!(div.children.remove(0), IRNode::For);
+        let mut p = cast!(*inner.child, IRNode::VNodeCall);
+        let i = cast!(p.children.remove(0), IRNode::VNodeCall);
+        assert_eq!(ref_keys(i.props), vec!["ref_for", "ref"]);
+    }
+
+    #[test]
+    fn test_component_ref() {
+        let mut body = base_convert("<comp ref='foo'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.is_component);
+        assert_eq!(vn.patch_flag, PatchFlag::NEED_PATCH);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "ref");
+        let mut body = base_convert("<comp v-for='i in a' :ref='foo'/>").body;
+        let for_node = cast!(body.remove(0), IRNode::For);
+        let vn = cast!(*for_node.child, IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "ref_for");
+        assert!(matches!(props[1].1, Js::Simple(v, _) if v.raw == "foo"));
+    }
+
+    #[test]
+    fn test_inline_ref() {
+        use crate::parser::test::base_parse;
+        use crate::{BindingMetadata, BindingTypes, SFCInfo};
+        let mut map = FxHashMap::default();
+        map.insert("foo", BindingTypes::SetupRef);
+        let sfc_info = SFCInfo {
+            inline: true,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        let bc = test_conversion(&sfc_info, Default::default());
+        let mut body = bc.convert_core_ir(base_parse("<p ref='foo'/>")).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 2);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "ref_key");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "foo");
+        assert!(matches!(props[1].1, Js::Src("foo")));
+    }
+
+    #[test]
+    fn test_scope_id() {
+        use crate::{parser::test::base_parse, SFCInfo};
+        let sfc_info = SFCInfo {
+            scope_id: Some("data-v-xxx".into()),
+            ..Default::default()
+        };
+        let bc = test_conversion(&sfc_info, Default::default());
+        let mut body = bc.convert_core_ir(base_parse("<p id='a'/><comp/>")).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 2);
+        assert_eq!(cast!(&props[1].0, Js::StrLit).raw, "data-v-xxx");
+        assert_eq!(cast!(&props[1].1, Js::StrLit).raw, "");
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+    }
+
+    #[test]
+    fn test_boolean_attr() {
+        let case = r#"<input disabled checked="" readonly="false" :selected="a"/><comp disabled/>"#;
+        let mut body = base_convert(case).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert!(matches!(props[0].1, Js::Src("true")));
+        assert!(matches!(props[1].1, Js::Src("true")));
+        assert_eq!(cast!(&props[2].1, Js::StrLit).raw, "false");
+        assert_eq!(cast!(&props[3].1, Js::Simple).raw, "a");
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "");
+    }
+
+    #[test]
+    fn test_preserve_directive() {
+        use super::super::{
+            BaseConverter, ConvertContext, ConvertOption, Converter, CoreDirConvRet, ErrorHandler,
+        };
+        use crate::{error::test::TestErrorHandler, parser::test::base_parse, SFCInfo};
+        use std::rc::Rc;
+        fn preserve<'a>(
+            _: &mut Directive<'a>,
+            _: &mut Element<'a>,
+            _: &dyn ErrorHandler,
+            _: &ConvertContext<'a>,
+        ) -> CoreDirConvRet<'a> {
+            super::super::DirectiveConvertResult::Preserve
+        }
+        let option = ConvertOption::default().with_converter(("show", preserve));
+        let bc = BaseConverter::new(Rc::new(TestErrorHandler), option);
+        let sfc_info = SFCInfo::default();
+        let mut body = bc
+            .convert_ir(base_parse("<p id='a' v-show='b'/>"), &sfc_info)
+            .body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(cast!(vn.props.unwrap(), Js::Props).len(), 1);
+        assert_eq!(vn.directives.len(), 1);
+        let dir = &vn.directives[0];
+        assert_eq!(
+            cast!(&dir.name, Js::Simple).into_string(),
+            "_directive_show"
+        );
+        assert_eq!(cast!(dir.expr.as_ref().unwrap(), Js::Simple).raw, "b");
+    }
+
+    #[test]
+    fn test_attr_case() {
+        let case = "<div fooBar='x' :barBaz='y'/><comp fooBar='x'/>";
+        let (ir, eh) = diagnose_convert(case);
+        let mut body = ir.body;
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            warnings[0].kind,
+            ErrorKind::AttributeCaseInsensitive
+        ));
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "fooBar");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "x");
+    }
+
+    #[test]
+    fn test_duplicate_prop() {
+        let case = "<p id='x' :id='y'/>";
+        let (ir, eh) = diagnose_convert(case);
+        let mut body = ir.body;
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind, ErrorKind::DuplicateProperty));
+        assert_eq!(warnings[0].location.start.offset, case.find(":id").unwrap());
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 1);
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "x");
+    }
+
+    #[test]
+    fn test_no_duplicate_for_mergeable() {
+        let case = "<p class='a' :class='b' :style='c' :style='d' @click='e' @click='f'/>";
+        let (ir, eh) = diagnose_convert(case);
+        let mut body = ir.body;
+        assert!(eh.warnings().is_empty());
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 3);
+        assert_eq!(cast!(&props[2].1, Js::Array).len(), 2);
+        // a real duplicate among mergeable props still warns
+        let case = "<p class='a' :class='b' title='c' :title='d'/>";
+        let (_, eh) = diagnose_convert(case);
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind, ErrorKind::DuplicateProperty));
+        assert_eq!(
+            warnings[0].location.start.offset,
+            case.find(":title").unwrap()
+        );
+    }
+
+    fn get_dynamic_props(s: &str) -> Vec<String> {
+        let mut body = handler_convert(s).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        vn.dynamic_props
+            .into_iter()
+            .map(VStr::into_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_dynamic_props_keys() {
+        let props = get_dynamic_props("<p :id='a' @click='b' :foo-bar.camel='c' id='d'/>");
+        assert_eq!(props, ["id", "onClick", "fooBar"]);
+        let props = get_dynamic_props("<p :inner-html.prop='a' :width.attr='b'/>");
+        assert_eq!(props, [".inner-html", "^width"]);
+        let props = get_dynamic_props("<p @click='a' @click='b' :title='c'/>");
+        assert_eq!(props, ["onClick", "title"]);
+    }
+
+    #[test]
+    fn test_dynamic_props_constant() {
+        let props = get_dynamic_props("<p :count='42' :checked='true' :title='t'/>");
+        assert_eq!(props, ["title"]);
+        let mut body = base_convert("<p :class=\"'a'\" :id='null'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.patch_flag.is_empty());
+        assert!(vn.props.unwrap().is_static());
+    }
+
+    #[test]
+    fn test_dynamic_props_v_model() {
+        let props = get_dynamic_props("<comp v-model='a' @change='b'/>");
+        assert_eq!(props, ["modelValue", "onUpdate:modelValue", "onChange"]);
+        let props = get_dynamic_props("<comp v-model:title='a'/>");
+        assert_eq!(props, ["title", "onUpdate:title"]);
+    }
+
+    #[test]
+    fn test_dynamic_props_full_props() {
+        let mut body = handler_convert("<p :id='a' :[k]='b'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, PatchFlag::FULL_PROPS);
+        assert!(vn.dynamic_props.is_empty());
+        assert!(get_dynamic_props("<p :id='a' v-on='h'/>").is_empty());
+    }
+}
@@ -1,12 +1,22 @@
 // v-once / v-memo
-use super::{BaseConversion, BaseIR, CoreConversion, Directive, Element};
+use super::{BaseConversion, BaseIR, CompilationError, ConvertInfo, CoreConversion, Directive, Element};
 use crate::ir::{IRNode, CacheIR, CacheKind, JsExpr as Js};
 use crate::error::CompilationErrorKind as ErrorKind;
 use crate::util::find_dir_empty;
 
-pub fn pre_convert_memo<'a>(elem: &mut Element<'a>) -> Option<Directive<'a>> {
+pub fn pre_convert_memo<'a, T: ConvertInfo, C: CoreConversion<'a, T> + ?Sized>(
+    bc: &C,
+    elem: &mut Element<'a>,
+    in_for: bool,
+) -> Option<Directive<'a>> {
     let dir = find_dir_empty(&mut *elem, "memo")?;
     let b = dir.take();
+    // template without v-for renders a fragment that cannot be memoized
+    if elem.tag_name == "template" && !in_for {
+        let error = CompilationError::new(ErrorKind::VMemoOnTemplate).with_location(b.location);
+        bc.emit_error(error);
+        return None;
+    }
     Some(b)
 }
 
@@ -14,11 +24,18 @@ pub fn pre_convert_once<'a>(elem: &mut Element<'a>) -> Option<Directive<'a>> {
     let dir = find_dir_empty(&mut *elem, "once")?;
     let b = dir.take();
     // don't use cache if ancestor already in v-once/v-memo
+    strip_nested_once(elem);
+    Some(b)
+}
+
+fn strip_nested_once(elem: &mut Element) {
     let children = elem.children.iter_mut().filter_map(|c| c.get_element_mut());
     for child in children {
-        pre_convert_once(child);
+        if let Some(dir) = find_dir_empty(&mut *child, "once") {
+            dir.take();
+        }
+        strip_nested_once(child);
     }
-    Some(b)
 }
 
 pub fn convert_memo<'a>(bc: &BaseConversion, d: Directive<'a>, n: BaseIR<'a>) -> BaseIR<'a> {
@@ -50,7 +67,7 @@ pub fn convert_once<'a>(_bc: &BaseConversion, _: Directive<'a>, n: BaseIR<'a>) -
 
 #[cfg(test)]
 mod test {
-    use super::super::test::base_convert;
+    use super::super::test::{base_convert, error_convert};
     use super::*;
     use crate::cast;
 
@@ -90,12 +107,51 @@ mod test {
         let cn = cast!(vn.children.remove(0), IRNode::CacheNode);
         cast!(cn.kind, CacheKind::Memo);
     }
-    // fn test_once() {
-    //     let cases = [
-    //         "<template v-for='a in b'><p v-once/></template>",
-    //         "<p v-for='a in b' v-once/>",
-    //         "<p v-if='a' v-once/>",
-    //         "<p v-once/>",
-    //     ];
-    // }
+    #[test]
+    fn test_memo_on_template() {
+        let errors = error_convert("<template v-memo='a'><p/></template>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VMemoOnTemplate));
+        let errors = error_convert("<template v-for='a in b' v-memo='a'><p/></template>");
+        assert!(errors.is_empty());
+    }
+    #[test]
+    fn test_once() {
+        let mut body = base_convert("<p v-once/>").body;
+        let cn = cast!(body.remove(0), IRNode::CacheNode);
+        assert!(matches!(cn.kind, CacheKind::Once));
+        cast!(*cn.child, IRNode::VNodeCall);
+    }
+    #[test]
+    fn test_once_in_v_for() {
+        // v-once caches the whole list
+        let mut body = base_convert("<p v-for='a in b' v-once/>").body;
+        let cn = cast!(body.remove(0), IRNode::CacheNode);
+        assert!(matches!(cn.kind, CacheKind::Once));
+        cast!(*cn.child, IRNode::For);
+        let case = "<template v-for='a in b'><p v-once/></template>";
+        let mut body = base_convert(case).body;
+        let f = cast!(body.remove(0), IRNode::For);
+        let mut vn = cast!(*f.child, IRNode::VNodeCall);
+        let cn = cast!(vn.children.remove(0), IRNode::CacheNode);
+        assert!(matches!(cn.kind, CacheKind::Once));
+    }
+    #[test]
+    fn test_once_in_v_if() {
+        let mut body = base_convert("<p v-if='a' v-once/>").body;
+        let i = cast!(body.remove(0), IRNode::If);
+        let child = &*i.branches[0].child;
+        cast!(child, IRNode::CacheNode);
+    }
+    #[test]
+    fn test_nested_once() {
+        let case = "<div v-once><p><span v-once/></p><i v-once/></div>";
+        let mut body = base_convert(case).body;
+        let cn = cast!(body.remove(0), IRNode::CacheNode);
+        let mut div = cast!(*cn.child, IRNode::VNodeCall);
+        let i = div.children.pop().unwrap();
+        cast!(i, IRNode::VNodeCall);
+        let mut p = cast!(div.children.pop().unwrap(), IRNode::VNodeCall);
+        cast!(p.children.pop().unwrap(), IRNode::VNodeCall);
+    }
 }
@@ -1,11 +1,12 @@
 use super::{
     build_props::{build_props, BuildProps},
-    v_slot, BaseConvertInfo, BaseConversion as BC, BaseIR, CoreConversion, Element, VStr,
+    v_slot, BaseConvertInfo, BaseConversion as BC, BaseIR, CoreConversion, Element, ModelTarget,
+    VStr,
 };
 use crate::{
     converter::v_slot::check_wrong_slot,
     error::{CompilationError, CompilationErrorKind as ErrorKind},
-    flags::{PatchFlag, RuntimeHelper, StaticLevel},
+    flags::{BuiltinComponent, PatchFlag, RuntimeHelper, StaticLevel},
     ir::{IRNode, JsExpr as Js, RuntimeDir, VNodeIR},
     parser::{AstNode, Directive, ElemProp, ElementType},
     scanner::Attribute,
@@ -19,18 +20,36 @@ pub fn convert_element<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
         e.tag_type,
         ElementType::Plain | ElementType::Component
     ));
+    // custom elements are native elements even if they look like components,
+    // unless script setup has a binding for the tag
+    if (bc.option.is_custom_element)(e.tag_name) {
+        e.tag_type = if resolve_setup_component(bc, e.tag_name).is_some() {
+            ElementType::Component
+        } else {
+            ElementType::Plain
+        };
+    }
     let tag = resolve_element_tag(bc, &e);
     let is_block = should_use_block(&e, &tag);
-    // curiously, we should first build children instead of props
+    // curiously, we should first build component children instead of props
     // since we will pre-convert and consume v-slot here.
-    let (children, more_flags) = build_children(bc, &mut e, &tag);
-    let properties = mem::take(&mut e.properties);
+    let slots = if e.is_component() {
+        Some(build_children(bc, &mut e, &tag))
+    } else {
+        v_slot::check_wrong_slot(bc, &e, ErrorKind::VSlotMisplaced);
+        None
+    };
+    // directive converters may inspect sibling props, e.g. v-model checks type
+    let properties = e.properties.clone();
+    collect_model_targets(bc, &e);
     let BuildProps {
         props,
         directives,
         mut patch_flag,
         dynamic_props,
     } = build_props(bc, &mut e, properties);
+    // plain element children are built later since v-html/v-text may drop them
+    let (children, more_flags) = slots.unwrap_or_else(|| build_children(bc, &mut e, &tag));
     let directives = build_directive_args(bc, directives);
     patch_flag |= more_flags;
     let vnode = VNodeIR {
@@ -43,10 +62,27 @@ pub fn convert_element<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
         is_block,
         disable_tracking: false,
         is_component: e.is_component(),
+        loc: e.location.into(),
     };
     IRNode::VNodeCall(vnode)
 }
 
+fn collect_model_targets<'a>(bc: &BC<'a>, e: &Element<'a>) {
+    for prop in &e.properties {
+        let dir = match prop {
+            ElemProp::Dir(dir) if dir.name == "model" => dir,
+            _ => continue,
+        };
+        if let Some(expr) = &dir.expression {
+            bc.context.add_model_target(ModelTarget {
+                element: e.location.start.offset,
+                expr: expr.content,
+                loc: dir.location.clone(),
+            });
+        }
+    }
+}
+
 // NB: template from dynamic slot expr should have different handling
 pub fn convert_template<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
     debug_assert!(e.tag_type == ElementType::Template);
@@ -76,6 +112,7 @@ pub fn convert_template<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
         patch_flag,
         props,
         is_block: true, // only v-if/v-for(always block) or v-slot(as wrapper)
+        loc: e.location.into(),
         ..VNodeIR::default()
     })
 }
@@ -90,7 +127,7 @@ pub fn resolve_element_tag<'a>(bc: &BC<'a>, e: &Element<'a>) -> Js<'a> {
     }
     let is_explicit_dynamic = is_component_tag(e.tag_name);
     // 1. resolve dynamic component
-    let tag = match resolve_dynamic_component(e, is_explicit_dynamic) {
+    let tag = match resolve_dynamic_component(bc, e, is_explicit_dynamic) {
         Ok(call_expr) => return call_expr,
         Err(tag_name) => tag_name,
     };
@@ -101,21 +138,37 @@ pub fn resolve_element_tag<'a>(bc: &BC<'a>, e: &Element<'a>) -> Js<'a> {
     // 2. built-in components (Teleport, Transition, KeepAlive, Suspense...)
     let builtin = bc
         .get_builtin_component(tag)
-        .or_else(|| get_core_component(tag));
-    if let Some(builtin) = builtin {
+        .or_else(|| get_core_component(tag).map(BuiltinComponent::Helper));
+    match builtin {
         // TODO: ensure SSR does not collect this. since built-ins are simply fallthroughs
         // or have special handling during compilation so we don't need to import their runtime
-        return Js::Symbol(builtin);
+        Some(BuiltinComponent::Helper(helper)) => return Js::Symbol(helper),
+        // 2.5 built-ins from other modules, e.g. RouterView
+        Some(BuiltinComponent::Import { name, path }) => {
+            let local = *VStr::raw(name).be_imported();
+            bc.context.add_named_import(name, local, path.into());
+            return Js::Simple(local, StaticLevel::CanHoist);
+        }
+        None => (),
     }
     // 3. user component (from setup bindings)
     if let Some(from_setup) = resolve_setup_component(bc, tag) {
         return from_setup;
     }
+    // 3.5 user component imported by build tools
+    if let Some(resolved) = (bc.option.resolve_component_type)(tag) {
+        let local = *VStr::raw(tag).be_imported();
+        bc.context.add_import(local, resolved.path);
+        return Js::Simple(local, StaticLevel::CanHoist);
+    }
+    if split_namespace(tag).is_some() {
+        let error = CompilationError::new(ErrorKind::UnresolvedComponentNamespace)
+            .with_location(e.location.clone());
+        bc.emit_error(error);
+    }
     // 4. User component or Self referencing component (inferred from filename)
     let mut comp = VStr::raw(tag);
-    if !bc.sfc_info.self_name.is_empty()
-        && VStr::raw(tag).pascalize().into_string() == bc.sfc_info.self_name
-    {
+    if is_self_name(bc, tag) {
         // codegen special checks for __self postfix when generating component imports,
         // which will pass additional `maybeSelfReference` flag to `resolveComponent`.
         comp.suffix_self();
@@ -126,15 +179,32 @@ pub fn resolve_element_tag<'a>(bc: &BC<'a>, e: &Element<'a>) -> Js<'a> {
     Js::Simple(comp_name, StaticLevel::CanHoist)
 }
 
+fn is_self_name(bc: &BC, tag: &str) -> bool {
+    let self_name = &bc.sfc_info.self_name;
+    if self_name.is_empty() {
+        return false;
+    }
+    let pascal = |s| VStr::raw(s).pascalize().into_string();
+    pascal(tag) == pascal(self_name)
+}
+
 const MUST_NON_EMPTY: &str = "find_prop must return prop with non-empty value";
 /// Returns Ok if resolved as dynamic component call, Err if resolved as static string tag
 fn resolve_dynamic_component<'a>(
+    bc: &BC<'a>,
     e: &Element<'a>,
     is_explicit_dynamic: bool,
 ) -> Result<Js<'a>, &'a str> {
     let is_prop = prop_finder(e, "is").find();
     let prop = match is_prop {
         Some(prop) => prop,
+        None if is_explicit_dynamic => {
+            let error = CompilationError::new(ErrorKind::DynamicComponentNoIs)
+                .with_location(e.location.clone());
+            bc.emit_error(error);
+            // fallback to resolve <component> as a user component
+            return Err(e.tag_name);
+        }
         None => return Err(e.tag_name),
     };
     if is_explicit_dynamic {
@@ -158,10 +228,18 @@ fn resolve_dynamic_component<'a>(
     }) = prop.get_ref()
     {
         // if not <component>, e.g. <button is="vue:xxx">
-        // only `is` value that starts with "vue:" will be
-        // treated as component by the parse phase and reach here
-        debug_assert!(val.content.starts_with("vue:"));
-        return Err(&val.content.raw[4..]); // strip vue:
+        // only `is` value that starts with "vue:", or any `is` in compat
+        // mode, will be treated as component by the parse phase
+        if let Some(name) = val.content.raw.strip_prefix("vue:") {
+            return Err(name);
+        }
+        // Vue 2 compat: <button is="xxx"> is a dynamic component
+        if bc.option.compat {
+            return Ok(Js::Call(
+                RuntimeHelper::RESOLVE_DYNAMIC_COMPONENT,
+                vec![Js::StrLit(val.content)],
+            ));
+        }
     }
     Err(e.tag_name)
 }
@@ -225,10 +303,8 @@ fn build_directive_arg<'a>(
     dir: Directive<'a>,
     helper: Option<RuntimeHelper>,
 ) -> BaseDir<'a> {
-    let resolve_setup_dir = || {
-        // TODO: should resolve "v-" + dir.name here
-        resolve_setup_reference(bc, dir.name)
-    };
+    // v-focus can be provided by setup binding vFocus
+    let resolve_setup_dir = || resolve_setup_reference(bc, *VStr::raw(dir.name).prefix_v_dir());
     let name = if let Some(rh) = helper {
         Js::Symbol(rh)
     } else if let Some(from_setup) = resolve_setup_dir() {
@@ -247,7 +323,7 @@ fn build_directive_arg<'a>(
     let mods = if dir.modifiers.is_empty() {
         None
     } else {
-        let mapper = |v| (Js::simple(v), Js::Src("true"));
+        let mapper = |v| (Js::str_lit(v), Js::Src("true"));
         let props = dir.modifiers.into_iter().map(mapper);
         Some(Js::Props(props.collect()))
     };
@@ -264,10 +340,6 @@ fn build_children<'a>(
     e: &mut Element<'a>,
     tag: &Js<'a>,
 ) -> (Vec<BaseIR<'a>>, PatchFlag) {
-    // check slot should precede return
-    if !e.is_component() {
-        v_slot::check_wrong_slot(bc, e, ErrorKind::VSlotMisplaced);
-    }
     let mut more_flag = PatchFlag::empty();
     if e.children.is_empty() {
         return (vec![], more_flag);
@@ -290,26 +362,31 @@ fn build_children<'a>(
         let slots = v_slot::convert_v_slot(bc, e);
         return (vec![slots], more_flag);
     }
-    let children = std::mem::take(&mut e.children);
+    let children = mem::take(&mut e.children);
     let children = bc.convert_children(children);
     (children, more_flag)
 }
 
 fn resolve_setup_component<'a>(bc: &BC<'a>, tag: &'a str) -> Option<Js<'a>> {
-    if let Some(from_setup) = resolve_setup_reference(bc, tag) {
+    if let Some(from_setup) = resolve_setup_reference(bc, VStr::raw(tag)) {
         return Some(from_setup);
     }
-    // handle <obj.Tag/>
-    let no_leading_trailing = |&i: &usize| i != 0 && i < tag.len() - 1;
-    let dot_index = tag.find('.').filter(no_leading_trailing)?; // exclude .tag or obj.
-    let (ns, access) = tag.split_at(dot_index);
-    let ns = resolve_setup_reference(bc, ns)?;
+    // handle <obj.Tag/> or <obj.Nested.Tag/>
+    let (ns, access) = split_namespace(tag)?;
+    let ns = resolve_setup_reference(bc, VStr::raw(ns))?;
     Some(Js::Compound(vec![ns, Js::Src(access)]))
 }
 
+/// Splits `obj.Tag` into `obj` and `.Tag`. Excludes `.tag` or `obj.`
+fn split_namespace(tag: &str) -> Option<(&str, &str)> {
+    let no_leading_trailing = |&i: &usize| i != 0 && i < tag.len() - 1;
+    let dot_index = tag.find('.').filter(no_leading_trailing)?;
+    Some(tag.split_at(dot_index))
+}
+
 // TODO: externalize this into the CoreConverter trait
 /// returns the specific name created in script setup, modulo camel/pascal case
-fn resolve_setup_reference<'a>(bc: &BC<'a>, name: &'a str) -> Option<Js<'a>> {
+fn resolve_setup_reference<'a>(bc: &BC<'a>, name: VStr<'a>) -> Option<Js<'a>> {
     let bindings = &bc.sfc_info.binding_metadata;
     if bindings.is_empty() || !bindings.is_setup() {
         return None;
@@ -342,28 +419,41 @@ fn resolve_setup_reference<'a>(bc: &BC<'a>, name: &'a str) -> Option<Js<'a>> {
             ])
         });
     }
+    // props are accessed via the props object since they are not exposed in setup scope
+    if let Some(from_props) = variety_by_type(BindingTypes::Props) {
+        let props_obj = if is_inline { "__props[" } else { "$props[" };
+        let prop = Js::Compound(vec![
+            Js::Src(props_obj),
+            Js::StrLit(from_props),
+            Js::Src("]"),
+        ]);
+        return Some(Js::Call(RuntimeHelper::UNREF, vec![prop]));
+    }
     None
 }
 
 #[inline(always)]
 fn get_variety_from_binding<'a: 'b, 'b>(
-    name: &'a str,
+    name: VStr<'a>,
     bindings: &'b BindingMetadata,
 ) -> impl Fn(BindingTypes) -> Option<VStr<'a>> + 'b {
     use crate::util::Lazy;
-    let camel_name = *VStr::raw(name).camelize();
-    let pascal_name = *VStr::raw(name).pascalize();
-    let name = VStr::raw(name);
+    let mut camel_name = name;
+    camel_name.camelize();
+    let mut pascal_name = name;
+    pascal_name.pascalize();
+    // name with ops, e.g. v-focus, is never a valid binding identifier
+    let name = Some(name).filter(|n| n.ops.is_empty());
     // TODO: remove the lazy using a better VStr instead
     let camel = Lazy::new(move || camel_name.into_string());
     let pascal = Lazy::new(move || pascal_name.into_string());
     move |tpe: BindingTypes| {
-        let is_match = |n: &str| Some(bindings.get(n)? == &tpe);
-        if is_match(&name)? {
-            Some(name)
-        } else if is_match(&camel)? {
+        let is_match = |n: &str| bindings.get(n) == Some(&tpe);
+        if name.map_or(false, |n| is_match(&n)) {
+            name
+        } else if is_match(&camel) {
             Some(camel_name)
-        } else if is_match(&pascal)? {
+        } else if is_match(&pascal) {
             Some(pascal_name)
         } else {
             None
@@ -373,7 +463,7 @@ fn get_variety_from_binding<'a: 'b, 'b>(
 
 #[cfg(test)]
 mod test {
-    use super::super::test::base_convert;
+    use super::super::test::{base_convert, handler_convert, test_conversion};
     use super::*;
     use crate::cast;
     #[test]
@@ -385,4 +475,567 @@ mod test {
         assert_eq!(tag.into_string(), "_component_comp");
         assert!(vn.is_component);
     }
+
+    #[test]
+    fn test_component_ir() {
+        let tpl = "<comp :msg='m' @click='go' v-focus>\n  <p>hi</p>\n</comp>";
+        let mut body = handler_convert(tpl).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(cast!(&vn.tag, Js::Simple).into_string(), "_component_comp");
+        let props = cast!(vn.props.as_ref().unwrap(), Js::Props);
+        let keys: Vec<_> = props
+            .iter()
+            .map(|(k, _)| cast!(k, Js::StrLit).into_string())
+            .collect();
+        assert_eq!(keys, ["msg", "onClick"]);
+        // handlers passed to components are dynamic props as well
+        let dynamic_props: Vec<_> = vn.dynamic_props.iter().map(|p| p.into_string()).collect();
+        assert_eq!(dynamic_props, ["msg", "onClick"]);
+        assert_eq!(vn.patch_flag, PatchFlag::PROPS);
+        assert_eq!(vn.directives.len(), 1);
+        assert_eq!(vn.children.len(), 1);
+        assert!(matches!(vn.children[0], IRNode::VSlotUse(_)));
+        assert!(vn.is_component);
+        assert!(!vn.is_block);
+        assert!(!vn.disable_tracking);
+        assert_eq!(vn.loc.start.offset, 0);
+        assert_eq!(vn.loc.end.offset, tpl.len());
+        assert_eq!(vn.loc.end.line, 3);
+    }
+
+    fn render_tag(tag: &Js) -> String {
+        match tag {
+            Js::Src(s) => s.to_string(),
+            Js::Simple(v, _) => v.into_string(),
+            Js::StrLit(v) => format!("\"{}\"", v.into_string()),
+            Js::Compound(v) => v.iter().map(render_tag).collect(),
+            Js::Call(RuntimeHelper::UNREF, args) => format!("unref({})", render_tag(&args[0])),
+            _ => panic!("unexpected component tag"),
+        }
+    }
+
+    fn convert_setup_component(tpl: &str, name: &str, ty: BindingTypes, inline: bool) -> String {
+        use crate::parser::{ParseOption, Parser};
+        use crate::{error::test::TestErrorHandler, scanner::test::base_scan, util::no};
+        use crate::SFCInfo;
+        use rustc_hash::FxHashMap;
+        use std::rc::Rc;
+        let mut map = FxHashMap::default();
+        map.insert(name, ty);
+        let sfc_info = SFCInfo {
+            inline,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        // parse every tag as component
+        let parser = Parser::new(ParseOption {
+            is_native_element: no,
+            ..Default::default()
+        });
+        let ast = parser.parse(base_scan(tpl), Rc::new(TestErrorHandler));
+        let bc = test_conversion(&sfc_info, Default::default());
+        let mut body = bc.convert_core_ir(ast).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.is_component);
+        render_tag(&vn.tag)
+    }
+
+    #[test]
+    fn test_setup_component() {
+        use BindingTypes::*;
+        let convert = |name, ty, inline| convert_setup_component("<foo-bar/>", name, ty, inline);
+        assert_eq!(convert("FooBar", SetupConst, true), "FooBar");
+        assert_eq!(convert("fooBar", SetupConst, true), "fooBar");
+        assert_eq!(convert("foo-bar", SetupConst, true), "foo-bar");
+        assert_eq!(convert("FooBar", SetupConst, false), r#"$setup["FooBar"]"#);
+        assert_eq!(convert("FooBar", SetupLet, true), "unref(FooBar)");
+        assert_eq!(convert("FooBar", SetupRef, true), "unref(FooBar)");
+        assert_eq!(convert("FooBar", SetupMaybeRef, true), "unref(FooBar)");
+        assert_eq!(
+            convert("FooBar", SetupMaybeRef, false),
+            r#"$setup["FooBar"]"#
+        );
+        assert_eq!(
+            convert("FooBar", Props, true),
+            r#"unref(__props["FooBar"])"#
+        );
+        assert_eq!(
+            convert("FooBar", Props, false),
+            r#"unref($props["FooBar"])"#
+        );
+        // non-setup bindings fall back to resolveComponent
+        assert_eq!(convert("FooBar", Data, true), "_component_foo_bar");
+        assert_eq!(convert("FooBar", Options, true), "_component_foo_bar");
+        // unrelated bindings
+        assert_eq!(convert("Foo", SetupConst, true), "_component_foo_bar");
+    }
+
+    fn convert_self_reference<'a>(tpl: &'a str, sfc_info: &'a crate::SFCInfo<'a>) -> Vec<VStr<'a>> {
+        use crate::parser::test::base_parse;
+
+        let bc = test_conversion(sfc_info, Default::default());
+        let body = bc.convert_core_ir(base_parse(tpl)).body;
+        body.into_iter()
+            .map(|ir| cast!(cast!(ir, IRNode::VNodeCall).tag, Js::Simple))
+            .collect()
+    }
+
+    #[test]
+    fn test_self_reference_component() {
+        use crate::SFCInfo;
+        let info = SFCInfo::default().with_filename("src/foo-bar.vue");
+        let tags = convert_self_reference("<FooBar/><Foo-bar/><Foo/>", &info);
+        assert!(VStr::is_self_suffixed(&tags[0]));
+        assert_eq!(tags[0].into_string(), "_component_FooBar");
+        assert!(VStr::is_self_suffixed(&tags[1]));
+        assert!(!VStr::is_self_suffixed(&tags[2]));
+        // without self name, a tag of the same name is a normal component
+        let info = SFCInfo::default();
+        let tags = convert_self_reference("<FooBar/>", &info);
+        assert!(!VStr::is_self_suffixed(&tags[0]));
+        assert_eq!(tags[0].into_string(), "_component_FooBar");
+    }
+
+    #[test]
+    fn test_namespaced_component() {
+        use BindingTypes::*;
+        let convert = convert_setup_component;
+        assert_eq!(
+            convert("<Form.Input/>", "Form", SetupConst, true),
+            "Form.Input"
+        );
+        assert_eq!(
+            convert("<Form.Input/>", "Form", SetupMaybeRef, true),
+            "unref(Form).Input"
+        );
+        assert_eq!(
+            convert("<Form.Input/>", "Form", SetupConst, false),
+            r#"$setup["Form"].Input"#
+        );
+        assert_eq!(convert("<A.B.C/>", "A", SetupConst, true), "A.B.C");
+        assert_eq!(convert("<A.B.C/>", "A", SetupLet, true), "unref(A).B.C");
+    }
+
+    #[test]
+    fn test_unresolved_namespace() {
+        use crate::converter::test::error_convert;
+        let errors = error_convert("<Form.Input/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::UnresolvedComponentNamespace
+        ));
+        let mut body = base_convert("<Form.Input/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let tag = cast!(vn.tag, Js::Simple);
+        assert_eq!(tag.into_string(), "_component_Form46Input");
+        // leading or trailing dot is not namespace
+        assert!(split_namespace(".Input").is_none());
+        assert!(split_namespace("Form.").is_none());
+        assert_eq!(split_namespace("A.B.C"), Some(("A", ".B.C")));
+    }
+
+    #[test]
+    fn test_setup_component_casing() {
+        use BindingTypes::SetupConst;
+        let cases = [
+            // (template, binding, resolved)
+            ("<foo-bar/>", "FooBar", "FooBar"),
+            ("<FooBar/>", "FooBar", "FooBar"),
+            ("<fooBar/>", "FooBar", "FooBar"),
+            ("<foo-bar/>", "fooBar", "fooBar"),
+            ("<FooBar/>", "fooBar", "_component_FooBar"),
+            ("<fooBar/>", "fooBar", "fooBar"),
+            ("<foo-bar/>", "foo-bar", "foo-bar"),
+            ("<FooBar/>", "foo-bar", "_component_FooBar"),
+            ("<fooBar/>", "foo-bar", "_component_fooBar"),
+        ];
+        for (tpl, name, expected) in cases {
+            let resolved = convert_setup_component(tpl, name, SetupConst, true);
+            assert_eq!(resolved, expected, "{} with {}", tpl, name);
+        }
+    }
+
+    #[test]
+    fn test_custom_element_binding() {
+        use crate::parser::test::base_parse;
+        use crate::{converter::ConvertOption, BindingMetadata, SFCInfo};
+        use rustc_hash::FxHashMap;
+        let mut map = FxHashMap::default();
+        map.insert("FooBar", BindingTypes::SetupConst);
+        let sfc_info = SFCInfo {
+            inline: true,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        };
+        let option = ConvertOption {
+            is_custom_element: |tag| tag.contains('-'),
+            ..Default::default()
+        };
+        let bc = test_conversion(&sfc_info, option);
+        let mut body = bc.convert_core_ir(base_parse("<foo-bar/><foo-baz/>")).body;
+        // binding takes precedence over custom element
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.is_component);
+        assert_eq!(render_tag(&vn.tag), "FooBar");
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(!vn.is_component);
+        assert_eq!(cast!(vn.tag, Js::StrLit).raw, "foo-baz");
+    }
+
+    #[test]
+    fn test_custom_element() {
+        use crate::parser::test::base_parse;
+        use crate::{converter::ConvertOption, SFCInfo};
+        let sfc_info = SFCInfo::default();
+        let option = ConvertOption {
+            is_custom_element: |tag| tag.starts_with("Ion"),
+            ..Default::default()
+        };
+        let bc = test_conversion(&sfc_info, option);
+        let case = "<IonButton :fooBar='a' baz/><FooButton/>";
+        let mut body = bc.convert_core_ir(base_parse(case)).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(!vn.is_component);
+        assert_eq!(cast!(vn.tag, Js::StrLit).raw, "IonButton");
+        // unknown props are passed as is
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "fooBar");
+        assert_eq!(cast!(&props[1].0, Js::StrLit).raw, "baz");
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.is_component);
+        let tag = cast!(vn.tag, Js::Simple);
+        assert_eq!(tag.into_string(), "_component_FooButton");
+    }
+
+    #[test]
+    fn test_dynamic_component() {
+        let mut body = base_convert("<component :is='cmp' :foo='bar'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let (helper, args) = match vn.tag {
+            Js::Call(h, args) => (h, args),
+            _ => panic!("dynamic component should be resolved at runtime"),
+        };
+        assert_eq!(helper, RuntimeHelper::RESOLVE_DYNAMIC_COMPONENT);
+        assert_eq!(cast!(&args[0], Js::Simple).raw, "cmp");
+        assert!(vn.is_block);
+        // is should not be passed as prop
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 1);
+        let mut body = base_convert("<component is='foo'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        match vn.tag {
+            Js::Call(_, args) => assert_eq!(cast!(&args[0], Js::StrLit).raw, "foo"),
+            _ => panic!("dynamic component should be resolved at runtime"),
+        }
+    }
+
+    #[test]
+    fn test_dynamic_component_no_is() {
+        use crate::converter::test::error_convert;
+        let errors = error_convert("<component/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::DynamicComponentNoIs));
+        let errors = error_convert("<component :is/>");
+        assert!(matches!(errors[0].kind, ErrorKind::DynamicComponentNoIs));
+        assert!(error_convert("<component :is='a'/><component is='b'/>").is_empty());
+        // <comp is> is not dynamic component
+        assert!(error_convert("<comp/>").is_empty());
+    }
+
+    #[test]
+    fn test_builtin_teleport() {
+        let mut body = base_convert("<Teleport to='#modal'><p/></Teleport>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(matches!(vn.tag, Js::Symbol(RuntimeHelper::TELEPORT)));
+        assert!(vn.is_block);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "#modal");
+        // children are passed as raw vnodes instead of slots
+        assert!(matches!(vn.children[0], IRNode::VNodeCall(_)));
+    }
+
+    #[test]
+    fn test_builtin_keep_alive() {
+        let mut body = base_convert("<KeepAlive><comp/></KeepAlive>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(matches!(vn.tag, Js::Symbol(RuntimeHelper::KEEP_ALIVE)));
+        assert!(vn.is_block);
+        assert!(vn.patch_flag.contains(PatchFlag::DYNAMIC_SLOTS));
+        assert!(matches!(vn.children[0], IRNode::VNodeCall(_)));
+        let mut body = base_convert("<Suspense><comp/></Suspense>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(matches!(vn.tag, Js::Symbol(RuntimeHelper::SUSPENSE)));
+        assert!(matches!(vn.children[0], IRNode::VSlotUse(_)));
+    }
+
+    #[test]
+    fn test_raw_children() {
+        let mut body = base_convert("<KeepAlive><comp/></KeepAlive>").body;
+        let keep_alive = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut body = base_convert("<SomeComp><comp/></SomeComp>").body;
+        let some_comp = cast!(body.remove(0), IRNode::VNodeCall);
+        // KeepAlive has the child vnode directly
+        assert_eq!(keep_alive.children.len(), 1);
+        let child = cast!(&keep_alive.children[0], IRNode::VNodeCall);
+        assert!(child.is_component);
+        // normal component wraps the child in a default slot
+        assert_eq!(some_comp.children.len(), 1);
+        let slots = cast!(&some_comp.children[0], IRNode::VSlotUse);
+        assert_eq!(slots.stable_slots.len(), 1);
+        let slot = &slots.stable_slots[0];
+        assert!(matches!(slot.body[0], IRNode::VNodeCall(_)));
+        assert!(keep_alive.is_block && !some_comp.is_block);
+        assert!(keep_alive.patch_flag.contains(PatchFlag::DYNAMIC_SLOTS));
+        assert!(!some_comp.patch_flag.contains(PatchFlag::DYNAMIC_SLOTS));
+    }
+
+    #[test]
+    fn test_keep_alive_children() {
+        use crate::converter::test::error_convert;
+        let errors = error_convert("<KeepAlive><comp/><comp/></KeepAlive>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::KeepAliveInvalidChildren
+        ));
+        assert!(error_convert("<KeepAlive><comp/></KeepAlive>").is_empty());
+        assert!(error_convert("<Teleport to='a'><p/><p/></Teleport>").is_empty());
+    }
+
+    #[test]
+    fn test_builtin_spellings() {
+        use RuntimeHelper as RH;
+        let cases = [
+            ("Teleport", "teleport", RH::TELEPORT),
+            ("Suspense", "suspense", RH::SUSPENSE),
+            ("KeepAlive", "keep-alive", RH::KEEP_ALIVE),
+            ("BaseTransition", "base-transition", RH::BASE_TRANSITION),
+        ];
+        for (pascal, kebab, helper) in cases {
+            for tag in [pascal, kebab] {
+                let tpl = format!("<{0}><p/></{0}>", tag);
+                let mut body = base_convert(&tpl).body;
+                let vn = cast!(body.remove(0), IRNode::VNodeCall);
+                assert!(vn.is_component, "{}", tag);
+                match vn.tag {
+                    Js::Symbol(h) => assert_eq!(h, helper, "{}", tag),
+                    _ => panic!("{} should not be resolved at runtime", tag),
+                }
+                // Teleport and KeepAlive take raw children instead of slots
+                let is_raw = matches!(helper, RH::TELEPORT | RH::KEEP_ALIVE);
+                let is_slot = matches!(vn.children[0], IRNode::VSlotUse(_));
+                assert_eq!(is_raw, !is_slot, "{}", tag);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_builtin() {
+        use crate::parser::test::base_parse;
+        use crate::{converter::ConvertOption, SFCInfo};
+        let sfc_info = SFCInfo::default();
+        let option = ConvertOption {
+            get_builtin_component: |tag| match tag {
+                "RouterView" => Some(BuiltinComponent::Import {
+                    name: "RouterView",
+                    path: "vue-router",
+                }),
+                _ => None,
+            },
+            ..Default::default()
+        };
+        let bc = test_conversion(&sfc_info, option);
+        let case = "<RouterView/><RouterView/><RouterLink/><KeepAlive/>";
+        let mut body = bc.convert_core_ir(base_parse(case)).body;
+        for _ in 0..2 {
+            let vn = cast!(body.remove(0), IRNode::VNodeCall);
+            let tag = cast!(vn.tag, Js::Simple);
+            assert_eq!(tag.into_string(), "_imported_RouterView");
+        }
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let tag = cast!(vn.tag, Js::Simple);
+        assert_eq!(tag.into_string(), "_component_RouterLink");
+        // core components are still built-in
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(matches!(vn.tag, Js::Symbol(RuntimeHelper::KEEP_ALIVE)));
+        let imports = bc.context.imports.borrow();
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].name, Some("RouterView"));
+        assert_eq!(imports[0].path, "vue-router");
+    }
+
+    #[test]
+    fn test_vue_prefix_is() {
+        let mut body = base_convert("<div is='vue:foo'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let tag = cast!(vn.tag, Js::Simple);
+        assert_eq!(tag.into_string(), "_component_foo");
+        assert!(vn.is_component);
+        assert!(vn.props.is_none());
+    }
+
+    #[test]
+    fn test_is_on_native_element() {
+        use crate::{
+            error::test::TestErrorHandler,
+            parser::{Parser, ParseOption},
+            scanner::test::base_scan,
+        };
+        use crate::{converter::ConvertOption, SFCInfo};
+        use std::rc::Rc;
+        let sfc_info = SFCInfo::default();
+        let convert = |s, compat| {
+            let parser = Parser::new(ParseOption {
+                compat,
+                ..Default::default()
+            });
+            let eh = Rc::new(TestErrorHandler);
+            let ast = parser.parse(base_scan(s), eh.clone());
+            let option = ConvertOption {
+                compat,
+                ..Default::default()
+            };
+            let bc = BC::new(eh, &sfc_info, Rc::new(option));
+            let mut body = bc.convert_core_ir(ast).body;
+            cast!(body.remove(0), IRNode::VNodeCall)
+        };
+        // vue: prefix forces component
+        for compat in [false, true] {
+            let vn = convert("<tr is='vue:my-row'/>", compat);
+            assert!(vn.is_component);
+            assert_eq!(cast!(vn.tag, Js::Simple).into_string(), "_component_my_row");
+            assert!(vn.props.is_none());
+        }
+        // customized built-in element keeps is attribute
+        let vn = convert("<button is='plastic-button'/>", false);
+        assert!(!vn.is_component);
+        assert_eq!(cast!(vn.tag, Js::StrLit).raw, "button");
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "is");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "plastic-button");
+        // Vue 2 compat: bare is means component
+        let vn = convert("<button is='plastic-button'/>", true);
+        assert!(vn.is_component);
+        assert!(vn.is_block);
+        assert!(vn.props.is_none());
+        let args = match vn.tag {
+            Js::Call(RuntimeHelper::RESOLVE_DYNAMIC_COMPONENT, args) => args,
+            _ => panic!("compat is should resolve dynamic component"),
+        };
+        assert_eq!(cast!(&args[0], Js::StrLit).raw, "plastic-button");
+    }
+
+    #[test]
+    fn test_custom_directive() {
+        let mut body = base_convert("<p v-focus:arg.fast='val'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+        assert_eq!(vn.directives.len(), 1);
+        let dir = &vn.directives[0];
+        let name = cast!(&dir.name, Js::Simple);
+        assert_eq!(name.into_string(), "_directive_focus");
+        assert_eq!(cast!(dir.expr.as_ref().unwrap(), Js::Simple).raw, "val");
+        assert_eq!(cast!(dir.arg.as_ref().unwrap(), Js::StrLit).raw, "arg");
+        let mods = cast!(dir.mods.as_ref().unwrap(), Js::Props);
+        assert_eq!(cast!(&mods[0].0, Js::StrLit).raw, "fast");
+        assert!(matches!(mods[0].1, Js::Src("true")));
+    }
+
+    #[test]
+    fn test_multiple_custom_directives() {
+        let mut body = base_convert("<p v-c v-a='x' v-b/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let names: Vec<_> = vn
+            .directives
+            .iter()
+            .map(|d| cast!(&d.name, Js::Simple).into_string())
+            .collect();
+        assert_eq!(names, ["_directive_c", "_directive_a", "_directive_b"]);
+    }
+
+    #[test]
+    fn test_setup_directive() {
+        use crate::parser::test::base_parse;
+        use crate::{BindingMetadata, SFCInfo};
+        use rustc_hash::FxHashMap;
+        let convert = |inline| {
+            let mut map = FxHashMap::default();
+            map.insert("vFocus", BindingTypes::SetupConst);
+            let sfc_info = SFCInfo {
+                inline,
+                binding_metadata: BindingMetadata::new(map, true),
+                ..Default::default()
+            };
+            let bc = test_conversion(&sfc_info, Default::default());
+            let mut body = bc.convert_core_ir(base_parse("<p v-focus/>")).body;
+            let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+            match vn.directives.remove(0).name {
+                Js::Simple(n, _) => n.into_string(),
+                Js::Compound(v) => match &v[..] {
+                    [Js::Src(a), Js::StrLit(n), Js::Src(b)] => {
+                        format!("{}{}{}", a, n.into_string(), b)
+                    }
+                    _ => panic!("unexpected setup reference"),
+                },
+                _ => panic!("directive should be resolved from setup"),
+            }
+        };
+        assert_eq!(convert(true), "vFocus");
+        assert_eq!(convert(false), "$setup[vFocus]");
+    }
+
+    #[test]
+    fn test_setup_directive_unref() {
+        use crate::parser::test::base_parse;
+        use crate::{BindingMetadata, SFCInfo};
+        use rustc_hash::FxHashMap;
+        let convert = |ty, from_setup| {
+            let mut map = FxHashMap::default();
+            map.insert("vFocus", ty);
+            let sfc_info = SFCInfo {
+                inline: true,
+                binding_metadata: BindingMetadata::new(map, from_setup),
+                ..Default::default()
+            };
+            let bc = test_conversion(&sfc_info, Default::default());
+            let mut body = bc.convert_core_ir(base_parse("<p v-focus/>")).body;
+            let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+            match vn.directives.remove(0).name {
+                Js::Simple(n, _) => n.into_string(),
+                Js::Call(RuntimeHelper::UNREF, args) => {
+                    let arg = cast!(&args[0], Js::Simple);
+                    format!("unref({})", arg.into_string())
+                }
+                _ => panic!("unexpected directive name"),
+            }
+        };
+        assert_eq!(convert(BindingTypes::SetupConst, true), "vFocus");
+        assert_eq!(convert(BindingTypes::SetupLet, true), "unref(vFocus)");
+        assert_eq!(convert(BindingTypes::SetupMaybeRef, true), "unref(vFocus)");
+        // options api bindings fall back to resolveDirective
+        assert_eq!(convert(BindingTypes::Data, true), "_directive_focus");
+        assert_eq!(convert(BindingTypes::SetupConst, false), "_directive_focus");
+    }
+
+    #[test]
+    fn test_v_pre() {
+        let mut body = base_convert("<span v-pre>{{ raw }}</span>").body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+        assert!(vn.directives.is_empty());
+        let text = cast!(vn.children.remove(0), IRNode::TextCall);
+        let raw: String = text
+            .texts
+            .iter()
+            .map(|t| cast!(t, Js::StrLit).into_string())
+            .collect();
+        assert_eq!(raw, "{{ raw }}");
+        let mut body = base_convert("<div v-pre><p v-if='a'/></div>").body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let p = cast!(vn.children.remove(0), IRNode::VNodeCall);
+        let props = cast!(p.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "v-if");
+        assert_eq!(cast!(&props[0].1, Js::StrLit).raw, "a");
+    }
 }
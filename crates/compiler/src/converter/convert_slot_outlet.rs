@@ -34,7 +34,9 @@ pub fn convert_slot_outlet<'a>(bc: &BC<'a>, mut e: Element<'a>) -> BaseIR<'a> {
 type NameAndProps<'a> = (Js<'a>, Option<Js<'a>>);
 
 fn process_slot_outlet<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> NameAndProps<'a> {
-    let mut slot_name = Js::str_lit("default");
+    // bound :name takes precedence over static name regardless of order
+    let mut static_name = None;
+    let mut dynamic_name = None;
     let mapper = |mut prop| {
         match &mut prop {
             ElemProp::Dir(dir @ Directive { name: "bind", .. })
@@ -42,7 +44,7 @@ fn process_slot_outlet<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> NameAndProps<'a>
             {
                 if !dir.has_empty_expr() {
                     let content = dir.expression.as_ref().unwrap().content;
-                    slot_name = Js::simple(content);
+                    dynamic_name = Some(Js::simple(content));
                 }
                 None
             }
@@ -66,7 +68,7 @@ fn process_slot_outlet<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> NameAndProps<'a>
                 if v.content.is_empty() {
                     None
                 } else if *name == "name" {
-                    slot_name = Js::StrLit(v.content);
+                    static_name = Some(Js::StrLit(v.content));
                     None
                 } else {
                     // TODO: handle camelize
@@ -79,8 +81,11 @@ fn process_slot_outlet<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> NameAndProps<'a>
     };
 
     let props = mem::take(&mut e.properties);
-    let mut non_name_props = props.into_iter().filter_map(mapper).peekable();
-    if non_name_props.peek().is_none() {
+    let non_name_props: Vec<_> = props.into_iter().filter_map(mapper).collect();
+    let slot_name = dynamic_name
+        .or(static_name)
+        .unwrap_or_else(|| Js::str_lit("default"));
+    if non_name_props.is_empty() {
         return (slot_name, None);
     }
     let BuildProps {
@@ -93,3 +98,74 @@ fn process_slot_outlet<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> NameAndProps<'a>
     }
     (slot_name, props)
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{assert_str_lit, base_convert, error_convert};
+    use super::super::BaseConvertInfo;
+    use super::*;
+    use crate::{cast, error::CompilationErrorKind as ErrorKind};
+
+    fn convert_outlet(s: &str) -> RenderSlotIR<BaseConvertInfo> {
+        let mut body = base_convert(s).body;
+        assert_eq!(body.len(), 1);
+        cast!(body.remove(0), IRNode::RenderSlotCall)
+    }
+
+    #[test]
+    fn test_default_outlet() {
+        let r = convert_outlet("<slot/>");
+        assert!(matches!(r.slot_obj, Js::Simple(s, _) if s.raw == "$slots"));
+        assert_str_lit(&r.slot_name, "default");
+        assert!(r.slot_props.is_none());
+        assert!(r.fallbacks.is_empty());
+    }
+
+    #[test]
+    fn test_named_outlet() {
+        let r = convert_outlet("<slot name='header'/>");
+        assert_str_lit(&r.slot_name, "header");
+        assert!(r.slot_props.is_none());
+        let r = convert_outlet("<slot :name='n'/>");
+        assert!(matches!(r.slot_name, Js::Simple(s, _) if s.raw == "n"));
+    }
+
+    #[test]
+    fn test_dynamic_name_precedence() {
+        for case in ["<slot :name='n' name='a'/>", "<slot name='a' :name='n'/>"] {
+            let r = convert_outlet(case);
+            assert!(matches!(r.slot_name, Js::Simple(s, _) if s.raw == "n"));
+            assert!(r.slot_props.is_none());
+        }
+    }
+
+    #[test]
+    fn test_outlet_props() {
+        let r = convert_outlet("<slot name='header' :user='user' id='a'/>");
+        assert_str_lit(&r.slot_name, "header");
+        let props = cast!(r.slot_props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 2);
+        assert_str_lit(&props[0].0, "user");
+        assert_str_lit(&props[1].0, "id");
+    }
+
+    #[test]
+    fn test_outlet_fallback() {
+        let r = convert_outlet("<slot name='header'>fallback</slot>");
+        assert_str_lit(&r.slot_name, "header");
+        assert!(matches!(r.slot_props, Some(Js::Src("{}"))));
+        assert_eq!(r.fallbacks.len(), 1);
+        assert!(matches!(r.fallbacks[0], IRNode::TextCall(_)));
+    }
+
+    #[test]
+    fn test_outlet_custom_dir() {
+        let errors = error_convert("<slot v-foo/>");
+        assert_eq!(errors.len(), 1);
+        let kind = &errors[0].kind;
+        assert!(matches!(
+            kind,
+            ErrorKind::VSlotUnexpectedDirectiveOnSlotOutlet
+        ));
+    }
+}
@@ -34,11 +34,12 @@ pub mod v_on;
 mod v_slot;
 
 use crate::{
-    flags::{HelperCollector, RuntimeHelper},
+    error::CompilationErrorKind as ErrorKind,
+    flags::{BuiltinComponent, HelperCollector, RuntimeHelper, StaticLevel},
     ir::{ConvertInfo, IRNode, IRRoot, JsExpr, TextIR},
     parser::{SourceNode, TextNode},
-    util::{get_core_component, VStr},
-    SFCInfo,
+    util::{get_core_component, is_literal_constant, no, VStr},
+    SFCInfo, SourceLocation,
 };
 pub use v_bind::V_BIND;
 pub use v_model::V_MODEL;
@@ -47,6 +48,7 @@ pub use crate::error::{CompilationError, ErrorHandler, RcErrHandle};
 pub use crate::parser::{AstNode, AstRoot, Directive, Element};
 use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::{smallvec, SmallVec};
+use std::cell::{Cell, RefCell};
 use std::marker::PhantomData;
 use std::rc::Rc;
 
@@ -55,7 +57,7 @@ use serde::Serialize;
 
 use cache_dir::{pre_convert_memo, pre_convert_once};
 use v_for::pre_convert_for;
-use v_if::{pre_group_v_if, PreGroup};
+use v_if::{check_v_if_with_v_for, pre_group_v_if, PreGroup};
 
 /// Converts template ast node to intermediate representation.
 /// It defines the most generic Converter interface.
@@ -73,6 +75,7 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
     fn convert_core_ir(&self, ast: AstRoot<'a>) -> IRRoot<T> {
         let body = self.convert_children(ast.children);
         IRRoot {
+            is_fragment: has_multiple_roots(&body),
             body,
             top_scope: T::TopType::default(),
         }
@@ -106,7 +109,7 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         // in non reactive build, we can skip cache related dir
         if !self.is_reactive_build() {
             let vfor = pre_convert_for(self, &mut e);
-            let mut n = self.dispatch_element(e);
+            let mut n = self.dispatch_in_scope(e, vfor.is_some());
             if let Some(d) = vfor {
                 n = self.convert_for(d, n);
             }
@@ -115,8 +118,8 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         // order is defined as @vue/compiler-core/src/compile.ts
         let once = pre_convert_once(&mut e);
         let vfor = pre_convert_for(self, &mut e);
-        let memo = pre_convert_memo(&mut e);
-        let mut n = self.dispatch_element(e);
+        let memo = pre_convert_memo(self, &mut e, vfor.is_some());
+        let mut n = self.dispatch_in_scope(e, vfor.is_some());
         if let Some(d) = memo {
             n = self.convert_memo(d, n);
         }
@@ -129,6 +132,19 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
         // reverse order
         n
     }
+    fn dispatch_in_scope(&self, e: Element<'a>, in_for: bool) -> IRNode<T> {
+        if !in_for {
+            return self.dispatch_element(e);
+        }
+        self.enter_v_for();
+        let n = if check_v_if_with_v_for(self, &e) {
+            self.convert_if(vec![e], 0)
+        } else {
+            self.dispatch_element(e)
+        };
+        self.exit_v_for();
+        n
+    }
     fn dispatch_element(&self, e: Element<'a>) -> IRNode<T> {
         use super::parser::ElementType::{SlotOutlet, Template};
         match e.tag_type {
@@ -155,12 +171,37 @@ pub trait CoreConversion<'a, T: ConvertInfo> {
     fn convert_template(&self, e: Element<'a>) -> IRNode<T>;
     fn convert_comment(&self, c: SourceNode<'a>) -> IRNode<T>;
 
+    // entities registered while converting, e.g. helpers, cache slots and hoists
+    type Context;
+    fn context(&self) -> &Self::Context;
+    /// number of `_cache` slots reserved by converters so far
+    fn cache_count(&self) -> usize;
+
     // emit error
     fn emit_error(&self, error: CompilationError);
+    fn emit_warning(&self, warning: CompilationError);
     // platform specific options
-    fn get_builtin_component(&self, tag: &str) -> Option<RuntimeHelper>;
+    fn get_builtin_component(&self, tag: &str) -> Option<BuiltinComponent>;
     // is reactive
     fn is_reactive_build(&self) -> bool;
+    // track v-for scope for descendants, e.g. ref_for
+    fn enter_v_for(&self) {}
+    fn exit_v_for(&self) {}
+}
+
+/// Counts renderable root nodes. Comments count as roots but
+/// consecutive texts are merged into one text call later.
+fn has_multiple_roots<T: ConvertInfo>(body: &[IRNode<T>]) -> bool {
+    let mut prev_text = false;
+    let mut count = 0;
+    for n in body {
+        let is_text = matches!(n, IRNode::TextCall(_));
+        if !(is_text && prev_text) {
+            count += 1;
+        }
+        prev_text = is_text;
+    }
+    count > 1
 }
 
 /// Directive's prop argument passed to VNodeCall after conversion.
@@ -180,8 +221,9 @@ pub enum DirectiveConvertResult<Expr> {
 
 pub fn no_op_directive_convert<'a>(
     _: &mut Directive<'a>,
-    _: &Element<'a>,
+    _: &mut Element<'a>,
     _: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> DirectiveConvertResult<JsExpr<'a>> {
     DirectiveConvertResult::Dropped
 }
@@ -194,7 +236,18 @@ pub struct BaseConvertInfo<'a>(PhantomData<&'a ()>);
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ImportItem<'a> {
     pub exp: JsExpr<'a>,
-    pub path: &'a str,
+    pub path: String,
+    /// named export bound to `exp`. None for default import
+    pub name: Option<&'a str>,
+}
+
+/// A component imported by build tools instead of `resolveComponent`.
+/// `<foo-bar>` generates `import _imported_foo_bar from "path"` and
+/// uses the imported identifier as vnode type.
+#[derive(Clone)]
+pub struct ComponentResolution {
+    /// module specifier, e.g. `./components/FooBar.vue`
+    pub path: String,
 }
 
 #[derive(Default)]
@@ -212,6 +265,65 @@ pub struct TopScope<'a> {
     pub imports: Vec<ImportItem<'a>>,
     /// counters for temporary variables created in template
     pub temps: usize,
+    /// indexed variables allocated by conversion, passes and codegen
+    pub counter: EntityCounter,
+    /// v-model expressions to check against scope variables in transform
+    pub model_targets: Vec<ModelTarget<'a>>,
+}
+
+impl<'a> TopScope<'a> {
+    /// Moves a node out of render function. Returns the index used in
+    /// `IRNode::Hoisted`, which is generated as `_hoisted_{index + 1}`.
+    pub fn hoist(&mut self, node: BaseIR<'a>) -> usize {
+        let index = self.counter.next_hoist();
+        debug_assert_eq!(index, self.hoists.len());
+        self.hoists.push(node);
+        index
+    }
+}
+
+/// Scope variables are only known in transform, which reports misused v-model.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ModelTarget<'a> {
+    /// start offset of the element that has the v-model
+    pub element: usize,
+    pub expr: VStr<'a>,
+    /// location of the v-model directive
+    pub loc: SourceLocation,
+}
+
+/// Counters of indexed variables in generated code, e.g. `_hoisted_1` and
+/// `_cache[0]`. Every pass allocates from the one stored in TopScope so
+/// the names are deterministic and never collide across passes.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EntityCounter {
+    pub hoists: usize,
+    pub caches: usize,
+    /// components that need `resolveComponent`
+    pub components: usize,
+    /// directives that need `resolveDirective`
+    pub directives: usize,
+}
+
+impl EntityCounter {
+    pub fn next_hoist(&mut self) -> usize {
+        let index = self.hoists;
+        self.hoists += 1;
+        index
+    }
+    pub fn next_cache(&mut self) -> usize {
+        let index = self.caches;
+        self.caches += 1;
+        index
+    }
+    /// Adds entities counted elsewhere, e.g. by a ConvertContext.
+    pub fn merge(&mut self, other: &Self) {
+        self.hoists += other.hoists;
+        self.caches += other.caches;
+        self.components += other.components;
+        self.directives += other.directives;
+    }
 }
 
 impl<'a> ConvertInfo for BaseConvertInfo<'a> {
@@ -219,24 +331,119 @@ impl<'a> ConvertInfo for BaseConvertInfo<'a> {
     type TextType = SmallVec<[JsExpr<'a>; 1]>;
     type IfBranchType = usize;
     type CommentType = &'a str;
+    /// index of the hoisted node in TopScope::hoists
+    type HoistedType = usize;
     type JsExpression = JsExpr<'a>;
     type StrType = VStr<'a>;
 }
 
+/// Entities registered during conversion. They are merged into TopScope
+/// after the whole template is converted.
+#[derive(Default)]
+pub struct ConvertContext<'a> {
+    helpers: RefCell<HelperCollector>,
+    imports: RefCell<Vec<ImportItem<'a>>>,
+    hoists: RefCell<Vec<BaseIR<'a>>>,
+    counter: Cell<EntityCounter>,
+    model_targets: RefCell<Vec<ModelTarget<'a>>>,
+}
+
+impl<'a> ConvertContext<'a> {
+    /// Registers a runtime helper that is not referenced by the converted IR,
+    /// e.g. one only used in a handler expression built by a converter.
+    pub fn collect_helper(&self, helper: RuntimeHelper) {
+        self.helpers.borrow_mut().collect(helper);
+    }
+    /// Imports `local` from `path`. Importing the same local again is a no-op.
+    pub fn add_import(&self, local: VStr<'a>, path: String) {
+        self.push_import(local, path, None)
+    }
+    /// Imports a named export as `local`, e.g. `import { RouterView as local }`.
+    pub fn add_named_import(&self, name: &'a str, local: VStr<'a>, path: String) {
+        self.push_import(local, path, Some(name))
+    }
+    fn push_import(&self, local: VStr<'a>, path: String, name: Option<&'a str>) {
+        let mut imports = self.imports.borrow_mut();
+        let imported = imports
+            .iter()
+            .any(|i| matches!(i.exp, JsExpr::Simple(l, _) if l == local));
+        if !imported {
+            let exp = JsExpr::Simple(local, StaticLevel::CanHoist);
+            imports.push(ImportItem { exp, path, name });
+        }
+    }
+    /// Reserves a `_cache` slot, e.g. for a cached handler. Slots used
+    /// by codegen are allocated after the ones reserved here.
+    pub fn next_cache_index(&self) -> usize {
+        let mut counter = self.counter.get();
+        let index = counter.next_cache();
+        self.counter.set(counter);
+        index
+    }
+    pub fn cache_count(&self) -> usize {
+        self.counter.get().caches
+    }
+    /// Moves a static node out of render function. The returned index
+    /// should be referenced by `IRNode::Hoisted` in the converted IR.
+    /// Passes do not visit hoists so their helpers must be collected here.
+    pub fn hoist(&self, node: BaseIR<'a>) -> usize {
+        let mut counter = self.counter.get();
+        let index = counter.next_hoist();
+        self.counter.set(counter);
+        self.hoists.borrow_mut().push(node);
+        index
+    }
+    pub fn add_model_target(&self, target: ModelTarget<'a>) {
+        self.model_targets.borrow_mut().push(target);
+    }
+    fn merge_into(self, scope: &mut TopScope<'a>) {
+        scope.helpers.merge(&self.helpers.into_inner());
+        scope.imports.extend(self.imports.into_inner());
+        scope.hoists.extend(self.hoists.into_inner());
+        scope.model_targets.extend(self.model_targets.into_inner());
+        scope.counter.merge(&self.counter.get());
+    }
+}
+
 pub type CoreDirConvRet<'a> = DirectiveConvertResult<JsExpr<'a>>;
 /// Returns the conversion of a directive. Value could be props or object.
 // NB: we pass &dyn ErrorHandler to monomorphize the dir converter to pay
 // the minimal cost of dynamism only when error occurs. otherwise we will
 // incur the overhead of dyn DirectiveConvert in the ConvertOption.
-pub type DirConvertFn =
-    for<'a> fn(&mut Directive<'a>, &Element<'a>, &dyn ErrorHandler) -> CoreDirConvRet<'a>;
+pub type DirConvertFn = for<'a> fn(
+    &mut Directive<'a>,
+    &mut Element<'a>,
+    &dyn ErrorHandler,
+    &ConvertContext<'a>,
+) -> CoreDirConvRet<'a>;
 pub type DirectiveConverter = (&'static str, DirConvertFn);
 
+/// Directives every platform understands. v-cloak is only meaningful to
+/// the CSS of in-DOM templates so it compiles to nothing.
+pub const BASE_DIR_CONVERTERS: &[DirectiveConverter] = &[
+    V_BIND,
+    v_on::V_ON,
+    V_MODEL,
+    ("cloak", no_op_directive_convert),
+];
+
 #[derive(Clone)]
 pub struct ConvertOption {
     /// For platform developers. Registers platform specific components written in JS.
     /// e.g. transition, transition-group. Components that require code in Vue runtime.
-    pub get_builtin_component: fn(&str) -> Option<RuntimeHelper>,
+    pub get_builtin_component: fn(&str) -> Option<BuiltinComponent>,
+    /// For end users. Marks tags as native custom elements, e.g. web components.
+    /// They are never resolved as Vue components.
+    pub is_custom_element: fn(&str) -> bool,
+    /// For build tools. Resolves a user component to an import, e.g. in
+    /// unplugin-vue-components. None falls back to `resolveComponent`.
+    pub resolve_component_type: fn(&str) -> Option<ComponentResolution>,
+    /// Vue 2 compatible conversion: static `is` on native elements
+    /// resolves a dynamic component instead of a customized built-in element.
+    pub compat: bool,
+    /// Converters keyed by directive name so each directive is an O(1) lookup.
+    /// Registering a name again replaces the previous converter. Directives
+    /// without a converter are preserved as runtime directives.
     pub directive_converters: FxHashMap<&'static str, DirConvertFn>,
     pub is_dev: bool,
     pub need_reactivity: bool,
@@ -245,14 +452,32 @@ pub struct ConvertOption {
 impl Default for ConvertOption {
     fn default() -> Self {
         Self {
-            get_builtin_component: get_core_component,
+            get_builtin_component: |tag| get_core_component(tag).map(BuiltinComponent::Helper),
+            is_custom_element: no,
+            resolve_component_type: |_| None,
+            compat: false,
             is_dev: true,
             need_reactivity: true,
-            directive_converters: FxHashMap::default(),
+            directive_converters: BASE_DIR_CONVERTERS.iter().copied().collect(),
         }
     }
 }
 
+impl ConvertOption {
+    /// Registers a directive converter, replacing the builtin one of the same name.
+    #[must_use]
+    pub fn with_converter(mut self, (name, convert): DirectiveConverter) -> Self {
+        self.directive_converters.insert(name, convert);
+        self
+    }
+    /// Unregisters a directive converter so the directive is preserved as a runtime one.
+    #[must_use]
+    pub fn without_converter(mut self, name: &str) -> Self {
+        self.directive_converters.remove(name);
+        self
+    }
+}
+
 pub struct BaseConverter {
     err_handle: RcErrHandle,
     option: Rc<ConvertOption>,
@@ -269,12 +494,10 @@ impl Converter for BaseConverter {
     type IR<'a> = BaseRoot<'a>;
     type Info<'a> = &'a SFCInfo<'a>;
     fn convert_ir<'a>(&self, ast: AstRoot<'a>, info: Self::Info<'a>) -> Self::IR<'a> {
-        let conversion = BaseConversion {
-            err_handle: self.err_handle.clone(),
-            sfc_info: info,
-            option: self.option.clone(),
-        };
-        conversion.convert_core_ir(ast)
+        let conversion = BaseConversion::new(self.err_handle.clone(), info, self.option.clone());
+        let mut root = conversion.convert_core_ir(ast);
+        conversion.context.merge_into(&mut root.top_scope);
+        root
     }
 }
 
@@ -282,19 +505,55 @@ pub struct BaseConversion<'a> {
     pub err_handle: RcErrHandle,
     pub sfc_info: &'a SFCInfo<'a>,
     pub option: Rc<ConvertOption>,
+    /// nesting depth of v-for that encloses the current element
+    pub v_for_depth: Cell<usize>,
+    /// helpers and imports registered by directive converters
+    /// and components resolved by `resolve_component_type`
+    pub context: ConvertContext<'a>,
+}
+impl<'a> BaseConversion<'a> {
+    pub fn new(
+        err_handle: RcErrHandle,
+        sfc_info: &'a SFCInfo<'a>,
+        option: Rc<ConvertOption>,
+    ) -> Self {
+        Self {
+            err_handle,
+            sfc_info,
+            option,
+            v_for_depth: Cell::new(0),
+            context: Default::default(),
+        }
+    }
 }
 pub type BaseRoot<'a> = IRRoot<BaseConvertInfo<'a>>;
 pub type BaseIR<'a> = IRNode<BaseConvertInfo<'a>>;
 impl<'a> CoreConversion<'a, BaseConvertInfo<'a>> for BaseConversion<'a> {
+    type Context = ConvertContext<'a>;
+    fn context(&self) -> &ConvertContext<'a> {
+        &self.context
+    }
+    fn cache_count(&self) -> usize {
+        self.context.cache_count()
+    }
     fn emit_error(&self, error: CompilationError) {
         self.err_handle.on_error(error)
     }
+    fn emit_warning(&self, warning: CompilationError) {
+        self.err_handle.on_warn(warning)
+    }
     fn is_reactive_build(&self) -> bool {
         self.option.need_reactivity
     }
+    fn enter_v_for(&self) {
+        self.v_for_depth.set(self.v_for_depth.get() + 1);
+    }
+    fn exit_v_for(&self) {
+        self.v_for_depth.set(self.v_for_depth.get() - 1);
+    }
 
     // platform specific methods
-    fn get_builtin_component(&self, tag: &str) -> Option<RuntimeHelper> {
+    fn get_builtin_component(&self, tag: &str) -> Option<BuiltinComponent> {
         (self.option.get_builtin_component)(tag)
     }
 
@@ -305,7 +564,7 @@ impl<'a> CoreConversion<'a, BaseConvertInfo<'a>> for BaseConversion<'a> {
         e: &mut Element<'a>,
     ) -> CoreDirConvRet<'a> {
         if let Some(convert) = self.option.directive_converters.get(dir.name) {
-            convert(dir, e, self.err_handle.as_ref())
+            convert(dir, e, self.err_handle.as_ref(), &self.context)
         } else {
             DirectiveConvertResult::Preserve
         }
@@ -347,12 +606,27 @@ impl<'a> CoreConversion<'a, BaseConvertInfo<'a>> for BaseConversion<'a> {
         })
     }
     fn convert_interpolation(&self, interp: SourceNode<'a>) -> BaseIR<'a> {
-        let expr = JsExpr::simple(interp.source);
-        let call = JsExpr::Call(RuntimeHelper::TO_DISPLAY_STRING, vec![expr]);
+        // newlines inside expression are kept since they can be significant
+        let src = interp.source.trim();
+        let text = if src.is_empty() {
+            let error = CompilationError::new(ErrorKind::InterpolationNoExpression)
+                .with_location(interp.location);
+            self.emit_error(error);
+            JsExpr::str_lit("")
+        } else {
+            // literal is constant even if identifiers are not prefixed
+            let level = if is_literal_constant(src) {
+                StaticLevel::CanStringify
+            } else {
+                StaticLevel::NotStatic
+            };
+            let expr = JsExpr::Simple(VStr::raw(src), level);
+            JsExpr::Call(RuntimeHelper::TO_DISPLAY_STRING, vec![expr])
+        };
         IRNode::TextCall(TextIR {
             fast_path: false,
             need_patch: false,
-            texts: smallvec![call],
+            texts: smallvec![text],
         })
     }
     fn convert_template(&self, e: Element<'a>) -> BaseIR<'a> {
@@ -367,12 +641,20 @@ impl<'a> BaseConversion<'a> {
     fn no_slotted(&self) -> bool {
         self.sfc_info.scope_id.is_some() && !self.sfc_info.slotted
     }
+    fn in_v_for(&self) -> bool {
+        self.v_for_depth.get() > 0
+    }
 }
 
 #[cfg(test)]
 pub mod test {
     use super::*;
-    use crate::{cast, error::test::TestErrorHandler, ir::VNodeIR, parser::test::base_parse};
+    use crate::{
+        cast,
+        error::{test::TestErrorHandler, VecErrorHandler},
+        ir::VNodeIR,
+        parser::test::base_parse,
+    };
     use std::rc::Rc;
     use BaseConverter as BC;
     use JsExpr as Js;
@@ -399,13 +681,9 @@ pub mod test {
         bc.convert_ir(ast, &SFC_INFO)
     }
     pub fn handler_convert(s: &str) -> BaseRoot {
-        let convs = vec![
-            v_bind::V_BIND,
-            v_on::V_ON,
-            ("model", v_model::convert_v_model_event),
-        ]
-        .into_iter()
-        .collect();
+        let convs = vec![v_bind::V_BIND, v_on::V_ON, v_model::V_MODEL]
+            .into_iter()
+            .collect();
         let option = ConvertOption {
             directive_converters: convs,
             ..Default::default()
@@ -418,6 +696,39 @@ pub mod test {
         bc.convert_ir(ast, &SFC_INFO)
     }
 
+    /// converts with core directive converters, keeping reported diagnostics
+    pub fn diagnose_convert(s: &str) -> (BaseRoot, Rc<VecErrorHandler>) {
+        let convs = vec![v_bind::V_BIND, v_on::V_ON, v_model::V_MODEL]
+            .into_iter()
+            .collect();
+        let option = ConvertOption {
+            directive_converters: convs,
+            ..Default::default()
+        };
+        let eh = Rc::new(VecErrorHandler::default());
+        let bc = BC {
+            err_handle: eh.clone(),
+            option: Rc::new(option),
+        };
+        let ast = base_parse(s);
+        (bc.convert_ir(ast, &SFC_INFO), eh)
+    }
+
+    /// converts with core directive converters and returns reported errors
+    pub fn error_convert(s: &str) -> Vec<CompilationError> {
+        let (_, eh) = diagnose_convert(s);
+        let mut errors = eh.error_mut();
+        std::mem::take(&mut *errors)
+    }
+
+    /// conversion reporting to TestErrorHandler, for custom SFCInfo or option
+    pub fn test_conversion<'a>(
+        sfc_info: &'a SFCInfo<'a>,
+        option: ConvertOption,
+    ) -> BaseConversion<'a> {
+        BaseConversion::new(Rc::new(TestErrorHandler), sfc_info, Rc::new(option))
+    }
+
     pub fn assert_str_lit(expr: &Js, s: &str) {
         let v = cast!(expr, Js::StrLit);
         assert_eq!(v.raw, s);
@@ -437,8 +748,184 @@ pub mod test {
         assert_str_lit(&t.texts[0], "hello world");
     }
 
+    #[test]
+    fn test_comment() {
+        let body = base_convert("<!-- hi --><p><!--c--></p>").body;
+        assert_eq!(body.len(), 2);
+        assert_eq!(*cast!(&body[0], IRNode::CommentCall), " hi ");
+        let vn = cast!(&body[1], IRNode::VNodeCall);
+        assert_eq!(*cast!(&vn.children[0], IRNode::CommentCall), "c");
+    }
+
+    #[test]
+    fn test_fragment_root() {
+        let is_fragment = |s| base_convert(s).is_fragment;
+        assert!(!is_fragment(""));
+        assert!(!is_fragment("<p/>"));
+        assert!(!is_fragment("<p><span/><span/></p>"));
+        assert!(!is_fragment("hello {{world}}"));
+        assert!(!is_fragment("<p v-if='a'/><p v-else/>"));
+        assert!(is_fragment("<p/><span/>"));
+        assert!(is_fragment("hello <p/>"));
+        assert!(is_fragment("<p/><!--comment-->"));
+        assert!(is_fragment("<!--a--><!--b-->"));
+    }
+
+    #[test]
+    fn test_interpolation_trim() {
+        let expr = |s| {
+            let mut body = base_convert(s).body;
+            let mut t = cast!(body.remove(0), IRNode::TextCall);
+            match t.texts.remove(0) {
+                Js::Call(_, mut args) => cast!(args.remove(0), Js::Simple).into_string(),
+                _ => panic!("interpolation should call toDisplayString"),
+            }
+        };
+        assert_eq!(expr("{{  msg  }}"), "msg");
+        assert_eq!(expr("{{\n  a &&\n  b\n}}"), "a &&\n  b");
+    }
+
+    #[test]
+    fn test_interpolation_constant() {
+        let level = |s| {
+            let mut body = base_convert(s).body;
+            let t = cast!(body.remove(0), IRNode::TextCall);
+            t.texts[0].static_level()
+        };
+        assert!(level("{{ 1 }}") == StaticLevel::CanStringify);
+        assert!(level("{{ 'a.b' }}") == StaticLevel::CanStringify);
+        assert!(level("{{ msg }}") == StaticLevel::NotStatic);
+        assert!(level("{{ 1 + a }}") == StaticLevel::NotStatic);
+    }
+
+    #[test]
+    fn test_empty_interpolation() {
+        let errors = error_convert("<p>{{ }}</p>{{}}");
+        assert_eq!(errors.len(), 2);
+        let kind = &errors[0].kind;
+        assert!(matches!(kind, ErrorKind::InterpolationNoExpression));
+        let body = base_convert("{{ }}").body;
+        let t = cast!(&body[0], IRNode::TextCall);
+        assert_str_lit(&t.texts[0], "");
+    }
+
     #[test]
     fn test_abort() {
         base_convert("hello <p/> {{world}}");
     }
+
+    #[test]
+    fn test_default_converters() {
+        let convert = |s, option: ConvertOption| {
+            let bc = BaseConverter::new(Rc::new(TestErrorHandler), option);
+            let mut ir = bc.convert_ir(base_parse(s), &SFC_INFO);
+            cast!(ir.body.remove(0), IRNode::VNodeCall)
+        };
+        let vn = convert("<p v-cloak/>", ConvertOption::default());
+        assert!(vn.props.is_none());
+        assert!(vn.directives.is_empty());
+        let option = ConvertOption::default().without_converter("cloak");
+        let vn = convert("<p v-cloak/>", option);
+        assert_eq!(vn.directives.len(), 1);
+        let option = ConvertOption::default().with_converter(("bind", no_op_directive_convert));
+        let vn = convert("<p :a='b'/>", option);
+        assert!(vn.props.is_none());
+    }
+
+    #[test]
+    fn test_remove_converter() {
+        let option = ConvertOption::default().without_converter("model");
+        let bc = BaseConverter::new(Rc::new(TestErrorHandler), option);
+        let mut ir = bc.convert_ir(base_parse("<input v-model='a'/>"), &SFC_INFO);
+        let vn = cast!(ir.body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+        assert_eq!(vn.directives.len(), 1);
+        let name = cast!(&vn.directives[0].name, Js::Simple);
+        assert_eq!(name.into_string(), "_directive_model");
+    }
+
+    fn convert_foo<'a>(
+        _: &mut Directive<'a>,
+        _: &mut Element<'a>,
+        _: &dyn ErrorHandler,
+        _: &ConvertContext<'a>,
+    ) -> CoreDirConvRet<'a> {
+        DirectiveConvertResult::Converted {
+            value: Js::Props(vec![(Js::str_lit("autofocus"), Js::str_lit(""))]),
+            runtime: Err(false),
+        }
+    }
+
+    #[test]
+    fn test_custom_converter() {
+        let option = ConvertOption::default().with_converter(("foo", convert_foo));
+        let bc = BaseConverter::new(Rc::new(TestErrorHandler), option);
+        let mut ir = bc.convert_ir(base_parse("<p v-foo v-bar/>"), &SFC_INFO);
+        let vn = cast!(ir.body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 1);
+        assert_str_lit(&props[0].0, "autofocus");
+        // unregistered directive still falls through to withDirectives
+        assert_eq!(vn.directives.len(), 1);
+        let name = cast!(&vn.directives[0].name, Js::Simple);
+        assert_eq!(name.into_string(), "_directive_bar");
+    }
+
+    fn convert_capitalize<'a>(
+        _: &mut Directive<'a>,
+        _: &mut Element<'a>,
+        _: &dyn ErrorHandler,
+        ctx: &ConvertContext<'a>,
+    ) -> CoreDirConvRet<'a> {
+        ctx.collect_helper(RuntimeHelper::CAPITALIZE);
+        ctx.add_import(*VStr::raw("Cap").be_imported(), "./cap".into());
+        DirectiveConvertResult::Dropped
+    }
+
+    #[test]
+    fn test_converter_context() {
+        let option = ConvertOption::default().with_converter(("cap", convert_capitalize));
+        let bc = BaseConverter::new(Rc::new(TestErrorHandler), option);
+        let ir = bc.convert_ir(base_parse("<p v-cap/><i v-cap/>"), &SFC_INFO);
+        let top = &ir.top_scope;
+        assert!(top.helpers.contains(RuntimeHelper::CAPITALIZE));
+        assert_eq!(top.imports.len(), 1);
+        assert_eq!(top.imports[0].path, "./cap");
+    }
+
+    fn convert_cached<'a>(
+        _: &mut Directive<'a>,
+        _: &mut Element<'a>,
+        _: &dyn ErrorHandler,
+        ctx: &ConvertContext<'a>,
+    ) -> CoreDirConvRet<'a> {
+        ctx.next_cache_index();
+        DirectiveConvertResult::Dropped
+    }
+
+    #[test]
+    fn test_conversion_cache_count() {
+        let option = ConvertOption::default().with_converter(("cached", convert_cached));
+        let conversion = test_conversion(&SFC_INFO, option);
+        assert_eq!(conversion.cache_count(), 0);
+        let ir = conversion.convert_core_ir(base_parse("<p v-cached/><i v-cached/>"));
+        assert_eq!(ir.body.len(), 2);
+        assert_eq!(conversion.cache_count(), 2);
+        assert_eq!(conversion.context().next_cache_index(), 2);
+    }
+
+    #[test]
+    fn test_merge_counter() {
+        let mut counter = EntityCounter::default();
+        counter.next_hoist();
+        counter.next_cache();
+        let mut other = EntityCounter::default();
+        other.next_cache();
+        other.components = 2;
+        counter.merge(&other);
+        assert_eq!(counter.hoists, 1);
+        assert_eq!(counter.caches, 2);
+        assert_eq!(counter.components, 2);
+        assert_eq!(counter.directives, 0);
+    }
 }
@@ -1,18 +1,23 @@
-use crate::error::CompilationErrorKind as ErrorKind;
+use crate::error::{CompilationError, CompilationErrorKind as ErrorKind};
 use crate::flags::RuntimeHelper;
 
 use super::{
-    CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element, ErrorHandler,
-    JsExpr as Js,
+    ConvertContext, CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element,
+    ErrorHandler, JsExpr as Js,
 };
 use crate::parser::DirectiveArg;
 
 // this module process v-bind without arg and with arg.
 pub fn convert_v_bind<'a>(
     dir: &mut Directive<'a>,
-    _: &Element<'a>,
+    _: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
+    if let Some(error) = dir.check_dynamic_arg() {
+        eh.on_error(error);
+        return DirectiveConvertResult::Dropped;
+    }
     let expr = if let Some(error) = dir.check_empty_expr(ErrorKind::VBindNoExpression) {
         eh.on_error(error);
         if dir.argument.is_none() {
@@ -28,6 +33,12 @@ pub fn convert_v_bind<'a>(
             .expect("dir without value should be dropped");
         Js::simple(expr.content)
     };
+    if dir.modifiers.contains(&"sync") {
+        // keep the binding so the prop is still passed down
+        let error = CompilationError::new(ErrorKind::VBindSyncModifierRemoved)
+            .with_location(dir.location.clone());
+        eh.on_error(error);
+    }
     let Directive {
         modifiers,
         argument,
@@ -41,7 +52,6 @@ pub fn convert_v_bind<'a>(
                 Js::Compound(vec![Js::Src("("), e, Js::Src(") || ''")])
             }
         };
-        // TODO: handle .attr, .prop, modifiers in DOM
         if modifiers.contains(&"camel") {
             arg = match arg {
                 Js::StrLit(ref mut s) => {
@@ -51,6 +61,12 @@ pub fn convert_v_bind<'a>(
                 a => Js::Call(RuntimeHelper::CAMELIZE, vec![a]),
             }
         }
+        if modifiers.contains(&"prop") {
+            arg = inject_prefix(arg, true);
+        }
+        if modifiers.contains(&"attr") {
+            arg = inject_prefix(arg, false);
+        }
         Js::Props(vec![(arg, expr)])
     } else {
         expr
@@ -61,7 +77,66 @@ pub fn convert_v_bind<'a>(
     }
 }
 
+// runtime reads .prop key as DOM property and ^attr key as attribute
+fn inject_prefix(arg: Js, is_prop: bool) -> Js {
+    match arg {
+        Js::StrLit(mut s) => {
+            if is_prop {
+                s.prefix_prop();
+            } else {
+                s.prefix_attr();
+            }
+            Js::StrLit(s)
+        }
+        a => {
+            let prefix = if is_prop { r#""." + ("# } else { r#""^" + ("# };
+            Js::Compound(vec![Js::Src(prefix), a, Js::Src(")")])
+        }
+    }
+}
+
 pub const V_BIND: DirectiveConverter = ("bind", convert_v_bind);
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::super::test::{error_convert, handler_convert};
+    use super::*;
+    use crate::{cast, ir::IRNode};
+
+    #[test]
+    fn test_dynamic_arg() {
+        let mut body = handler_convert("<p :[a+b]='c'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut props = cast!(vn.props.unwrap(), Js::Props);
+        let (key, _) = props.remove(0);
+        let key = cast!(key, Js::Compound);
+        let arg = cast!(&key[1], Js::Simple);
+        assert_eq!(arg.into_string(), "a+b");
+    }
+
+    #[test]
+    fn test_empty_dynamic_arg() {
+        let errors = error_convert("<p :[]='a'/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::EmptyDynamicArgument));
+        let mut body = handler_convert("<p :[]='a'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+    }
+
+    #[test]
+    fn test_sync_modifier() {
+        let case = "<comp :foo.sync='a'/>";
+        let errors = error_convert(case);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::VBindSyncModifierRemoved
+        ));
+        assert_eq!(errors[0].location.start.offset, case.find(":foo").unwrap());
+        let mut body = handler_convert(case).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(cast!(&props[0].0, Js::StrLit).raw, "foo");
+    }
+}
@@ -175,6 +175,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_v_for_ir() {
+        use super::super::test::base_convert;
+        let case = "<p v-for='({ id, name }, key, i) in list' :key='id'/>";
+        let mut body = base_convert(case).body;
+        let v_for = cast!(body.remove(0), IRNode::For);
+        assert_eq!(cast!(v_for.source, Js::Simple).raw, "list");
+        let ForParseResult { value, key, index } = v_for.parse_result;
+        assert_eq!(to_str(value), "{ id, name }");
+        assert_eq!(key.map(to_str), Some("key"));
+        assert_eq!(index.map(to_str), Some("i"));
+        assert!(v_for.key.is_none());
+        assert!(!v_for.is_stable);
+        assert!(v_for.fragment_flag.is_empty());
+        let vn = cast!(*v_for.child, IRNode::VNodeCall);
+        assert!(vn.props.is_some());
+    }
+
+    #[test]
+    fn test_v_for_memo_ir() {
+        use super::super::test::base_convert;
+        use crate::ir::{CacheIR, CacheKind};
+        let case = "<p v-for='[a, b], k in list' v-memo='[a]'/>";
+        let mut body = base_convert(case).body;
+        let v_for = cast!(body.remove(0), IRNode::For);
+        assert_eq!(to_str(v_for.parse_result.value), "[a, b]");
+        let CacheIR { kind, child } = cast!(*v_for.child, IRNode::CacheNode);
+        let (v_for_key, expr) = match kind {
+            CacheKind::MemoInVFor { v_for_key, expr } => (v_for_key, expr),
+            _ => panic!("v-memo in v-for should be cached with key"),
+        };
+        assert_eq!(v_for_key.map(to_str), Some("k"));
+        assert_eq!(cast!(expr, Js::Simple).raw, "[a]");
+        assert!(matches!(*child, IRNode::VNodeCall(_)));
+    }
+
     #[test]
     fn test_parse_invalid_for() {
         for src in &["", "           in             "] {
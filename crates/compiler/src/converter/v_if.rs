@@ -1,6 +1,6 @@
 use super::{
     super::parser::ElemProp, AstNode, BaseConvertInfo, BaseConversion as BC, BaseIR,
-    CompilationError, Directive, Element, IRNode,
+    CompilationError, ConvertInfo, Directive, Element, IRNode,
 };
 use crate::{
     converter::{CoreConversion, JsExpr as Js},
@@ -47,6 +47,7 @@ impl<'a> PreGroupIter<'a> {
             .inner
             .peek()
             .and_then(|n| n.get_element())
+            .filter(|e| find_dir_empty(*e, "for").is_none())
             .and_then(|e| find_dir_empty(e, ["if", "else", "else-if"]))
             .is_none());
         self.inner.next().map(PreGroup::StandAlone)
@@ -57,8 +58,12 @@ impl<'a> Iterator for PreGroupIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(n) = self.inner.peek() {
             // group elements if they have v-if/v-else
+            // v-if with v-for is converted inside v-for instead
             let found = n
                 .get_element()
+                .filter(|e| {
+                    find_dir_empty(*e, "for").is_none() || find_dir_empty(*e, "if").is_none()
+                })
                 .and_then(|e| find_dir_empty(e, ["if", "else-if", "else"]));
             if let Some(d) = found {
                 // separate v-if into different groups
@@ -101,6 +106,23 @@ pub fn pre_group_v_if(children: Vec<AstNode>) -> impl Iterator<Item = PreGroup>
     PreGroupIter::new(children)
 }
 
+/// Reports v-if on an element whose v-for is already taken out.
+/// Returns true if the element should be converted as a v-if inside v-for.
+pub fn check_v_if_with_v_for<'a, T, C>(c: &C, elem: &Element<'a>) -> bool
+where
+    T: ConvertInfo,
+    C: CoreConversion<'a, T> + ?Sized,
+{
+    let dir = match find_dir_empty(elem, "if") {
+        Some(found) => found,
+        None => return false,
+    };
+    let loc = dir.get_ref().location.clone();
+    let warning = CompilationError::new(ErrorKind::VIfWithVFor).with_location(loc);
+    c.emit_warning(warning);
+    true
+}
+
 /// key is Vue-generated default key based on the number of sibling v-if.
 pub fn convert_if<'a>(c: &BC<'a>, elems: Vec<Element<'a>>, key: usize) -> BaseIR<'a> {
     debug_assert!(!elems.is_empty());
@@ -191,10 +213,12 @@ fn convert_if_branch<'a>(
         .take();
     report_duplicate_v_if(c, &mut e);
     let condition = convert_if_condition(c, dir);
+    let loc = e.location.clone();
     IfBranch {
         child: Box::new(c.pre_convert_element(e)),
         condition,
         info: key,
+        loc,
     }
 }
 fn convert_if_condition<'a>(c: &BC<'a>, dir: Directive<'a>) -> Option<Js<'a>> {
@@ -251,4 +275,75 @@ mod test {
         let cond = cast!(condition, Js::Simple);
         assert_eq!(cond.into_string(), "true");
     }
+
+    #[test]
+    fn test_v_if_chain() {
+        let case = "<p v-if='a'/>\n<p v-else-if='b'/>\n<p v-else/>";
+        let body = base_convert(case).body;
+        assert_eq!(body.len(), 1);
+        let v_if = cast!(&body[0], IRNode::If);
+        assert_eq!(v_if.branches.len(), 3);
+        let conds: Vec<_> = v_if
+            .branches
+            .iter()
+            .map(|b| b.condition.as_ref().map(|c| cast!(c, Js::Simple).raw))
+            .collect();
+        assert_eq!(conds, [Some("a"), Some("b"), None]);
+        let keys: Vec<_> = v_if.branches.iter().map(|b| b.info).collect();
+        assert_eq!(keys, [0, 1, 2]);
+        let lines: Vec<_> = v_if.branches.iter().map(|b| b.loc.start.line).collect();
+        assert_eq!(lines, [1, 2, 3]);
+        let start = v_if.branches[1].loc.start.offset;
+        assert_eq!(start, case.find("<p v-else-if").unwrap());
+        for branch in &v_if.branches {
+            assert!(matches!(*branch.child, IRNode::VNodeCall(_)));
+        }
+    }
+
+    #[test]
+    fn test_dangling_else() {
+        let errors = error_convert("<p v-else/><p v-else-if='a'/>");
+        assert_eq!(errors.len(), 2);
+        for error in errors {
+            assert!(matches!(error.kind, ErrorKind::VElseNoAdjacentIf));
+        }
+        let errors = error_convert("<p v-if='a'/>text<p v-else/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VElseNoAdjacentIf));
+        let errors = error_convert("<p v-if='a'/><!--c--> <p v-else/>");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_v_if_with_v_for() {
+        let case = "<p v-for='i in list' v-if='i.ok'/>";
+        let (ir, eh) = diagnose_convert(case);
+        let mut body = ir.body;
+        assert!(eh.errors().is_empty());
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind, ErrorKind::VIfWithVFor));
+        assert_eq!(
+            warnings[0].location.start.offset,
+            case.find("v-if").unwrap()
+        );
+        assert_eq!(body.len(), 1);
+        let v_for = cast!(body.remove(0), IRNode::For);
+        let v_if = cast!(*v_for.child, IRNode::If);
+        assert_eq!(v_if.branches.len(), 1);
+        let cond = cast!(v_if.branches[0].condition.as_ref().unwrap(), Js::Simple);
+        assert_eq!(cond.into_string(), "i.ok");
+        assert!(matches!(*v_if.branches[0].child, IRNode::VNodeCall(_)));
+    }
+
+    #[test]
+    fn test_warning_and_error() {
+        let (_, eh) = diagnose_convert("<p v-for='i' v-if='i'/>");
+        let errors = eh.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VForMalformedExpression));
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind, ErrorKind::VIfWithVFor));
+    }
 }
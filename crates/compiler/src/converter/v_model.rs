@@ -9,12 +9,12 @@ use crate::{
 };
 
 use super::{
-    v_on::is_member_expression, CoreDirConvRet, Directive, DirectiveConvertResult,
-    DirectiveConverter, Element, ErrorHandler,
+    v_on::is_assignable_member_expression, ConvertContext, CoreDirConvRet, Directive,
+    DirectiveConvertResult, DirectiveConverter, Element, ErrorHandler,
 };
 pub fn convert_v_model_core<'a>(
     dir: &mut Directive<'a>,
-    element: &Element<'a>,
+    _: &mut Element<'a>,
     eh: &dyn ErrorHandler,
 ) -> CoreDirConvRet<'a> {
     if let Some(error) = dir.check_empty_expr(ErrorKind::VModelNoExpression) {
@@ -26,29 +26,28 @@ pub fn convert_v_model_core<'a>(
         argument,
         ..
     } = dir;
-    let attr_value = expression.take().expect("empty dir should be dropped");
+    // keep the expression in dir since native v-model needs it as runtime value
+    let attr_value = expression.as_ref().expect("empty dir should be dropped");
     let val = attr_value.content;
     // TODO: looks like pattern can also work?
-    if !is_member_expression(val) {
-        let error =
-            Error::new(ErrorKind::VModelMalformedExpression).with_location(attr_value.location);
+    if !is_assignable_member_expression(val) {
+        let loc = attr_value.location.clone();
+        let error = Error::new(ErrorKind::VModelMalformedExpression).with_location(loc);
         eh.on_error(error);
         return DirectiveConvertResult::Dropped;
     }
-    // TODO: add scope variable check
+    // scope variable and props binding are checked in process_expression
 
     let prop_name = if let Some(arg) = argument {
         match arg {
             DirectiveArg::Static(s) => Js::str_lit(*s),
-            DirectiveArg::Dynamic(d) => Js::simple(*d),
+            // fallback to empty string so nullish name isn't stringified
+            DirectiveArg::Dynamic(d) => Js::Compound(vec![Js::simple(*d), Js::Src(r#" || """#)]),
         }
     } else {
         Js::str_lit("modelValue")
     };
-    let mut props = vec![(prop_name, Js::Simple(val, StaticLevel::NotStatic))];
-    if let Some(mods) = component_mods_prop(dir, element) {
-        props.push(mods);
-    }
+    let props = vec![(prop_name, Js::Simple(val, StaticLevel::NotStatic))];
     DirectiveConvertResult::Converted {
         value: Js::Props(props),
         runtime: Err(false),
@@ -69,9 +68,11 @@ fn component_mods_prop<'a>(dir: &Directive<'a>, elem: &Element<'a>) -> Option<Pr
     let modifiers_key = if let Some(arg) = argument {
         match arg {
             DirectiveArg::Static(s) => Js::StrLit(*VStr::raw(s).suffix_mod()),
-            DirectiveArg::Dynamic(d) => {
-                Js::Compound(vec![Js::simple(*d), Js::Src(" + 'Modifiers'")])
-            }
+            DirectiveArg::Dynamic(d) => Js::Compound(vec![
+                Js::Src("("),
+                Js::simple(*d),
+                Js::Src(r#" || "") + "Modifiers""#),
+            ]),
         }
     } else {
         Js::str_lit("modelModifiers")
@@ -83,10 +84,13 @@ fn component_mods_prop<'a>(dir: &Directive<'a>, elem: &Element<'a>) -> Option<Pr
     Some((modifiers_key, Js::Props(mod_value)))
 }
 
-pub fn convert_v_model_event<'a>(
+/// Converts v-model to the value prop, the `onUpdate` handler and,
+/// on components, the modifiers object.
+pub fn convert_v_model<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     let mut converted = convert_v_model_core(dir, e, eh);
     use DirectiveConvertResult as DirRet;
@@ -99,13 +103,149 @@ pub fn convert_v_model_event<'a>(
     let (prop_name, val) = &mut props[0];
     let event_name = match prop_name {
         Js::StrLit(v) => Js::StrLit(*v.clone().be_vmodel()),
-        _ => Js::Compound(vec![Js::Src("'onUpdate:' + "), prop_name.clone()]),
+        _ => Js::Compound(vec![
+            Js::Src(r#""onUpdate:" + ("#),
+            prop_name.clone(),
+            Js::Src(")"),
+        ]),
     };
     let val_expr = *cast!(val, Js::Simple).clone().assign_event();
     let assignment = Js::func(val_expr);
     // TODO, cache assignment expr
     props.push((event_name, assignment));
+    if let Some(mods) = component_mods_prop(dir, e) {
+        props.push(mods);
+    }
     converted
 }
 
-pub const V_MODEL: DirectiveConverter = ("model", convert_v_model_core);
+pub const V_MODEL: DirectiveConverter = ("model", convert_v_model);
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{assert_str_lit, error_convert, handler_convert};
+    use super::*;
+    use crate::ir::IRNode;
+
+    fn model_props(s: &str) -> Vec<Prop> {
+        let mut body = handler_convert(s).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        cast!(vn.props.expect("v-model should have props"), Js::Props)
+    }
+
+    #[test]
+    fn test_component_v_model() {
+        let props = model_props("<comp v-model='foo'/>");
+        assert_eq!(props.len(), 2);
+        assert_str_lit(&props[0].0, "modelValue");
+        let val = cast!(&props[0].1, Js::Simple);
+        assert_eq!(val.raw, "foo");
+        let key = cast!(&props[1].0, Js::StrLit);
+        assert_eq!(key.into_string(), "onUpdate:modelValue");
+        if let Js::FuncSimple { src, .. } = &props[1].1 {
+            assert_eq!(src.into_string(), "(foo) = $event");
+        } else {
+            panic!("v-model handler should be a function");
+        }
+    }
+
+    #[test]
+    fn test_v_model_with_arg() {
+        let props = model_props("<comp v-model:title='foo'/>");
+        assert_eq!(props.len(), 2);
+        assert_str_lit(&props[0].0, "title");
+        let key = cast!(&props[1].0, Js::StrLit);
+        assert_eq!(key.into_string(), "onUpdate:title");
+    }
+
+    #[test]
+    fn test_v_model_with_modifiers() {
+        let props = model_props("<comp v-model.lazy.trim='foo'/>");
+        assert_eq!(props.len(), 3);
+        assert_str_lit(&props[2].0, "modelModifiers");
+        let mods = cast!(&props[2].1, Js::Props);
+        assert_eq!(mods.len(), 2);
+        assert_str_lit(&mods[0].0, "lazy");
+        assert_str_lit(&mods[1].0, "trim");
+        let props = model_props("<comp v-model:title.number='foo'/>");
+        let key = cast!(&props[2].0, Js::StrLit);
+        assert_eq!(key.into_string(), "titleModifiers");
+    }
+
+    #[test]
+    fn test_multiple_v_model() {
+        let props = model_props("<comp v-model:a.trim='foo' v-model:b.number.lazy='bar'/>");
+        let keys: Vec<_> = props
+            .iter()
+            .map(|(k, _)| cast!(k, Js::StrLit).into_string())
+            .collect();
+        let expected = [
+            "a",
+            "onUpdate:a",
+            "aModifiers",
+            "b",
+            "onUpdate:b",
+            "bModifiers",
+        ];
+        assert_eq!(keys, expected);
+        assert_eq!(cast!(&props[0].1, Js::Simple).raw, "foo");
+        assert_eq!(cast!(&props[3].1, Js::Simple).raw, "bar");
+        let a_mods = cast!(&props[2].1, Js::Props);
+        assert_eq!(a_mods.len(), 1);
+        assert_str_lit(&a_mods[0].0, "trim");
+        let b_mods = cast!(&props[5].1, Js::Props);
+        assert_eq!(b_mods.len(), 2);
+        assert_str_lit(&b_mods[0].0, "number");
+        assert_str_lit(&b_mods[1].0, "lazy");
+    }
+
+    #[test]
+    fn test_v_model_dynamic_arg_modifiers() {
+        let props = model_props("<comp v-model:[name].trim='foo'/>");
+        assert_eq!(props.len(), 3);
+        let key = cast!(&props[2].0, Js::Compound);
+        assert_eq!(cast!(key[0], Js::Src), "(");
+        let arg = cast!(key[1], Js::Simple);
+        assert_eq!(arg.raw, "name");
+        assert_eq!(cast!(key[2], Js::Src), r#" || "") + "Modifiers""#);
+        let mods = cast!(&props[2].1, Js::Props);
+        assert_str_lit(&mods[0].0, "trim");
+    }
+
+    #[test]
+    fn test_v_model_dynamic_arg() {
+        let mut body = handler_convert("<comp v-model:[name]='foo'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.patch_flag, crate::flags::PatchFlag::FULL_PROPS);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        assert_eq!(props.len(), 2);
+        let key = cast!(&props[0].0, Js::Compound);
+        assert_eq!(cast!(key[0], Js::Simple).raw, "name");
+        assert_eq!(cast!(key[1], Js::Src), r#" || """#);
+        let event = cast!(&props[1].0, Js::Compound);
+        assert_eq!(cast!(event[0], Js::Src), r#""onUpdate:" + ("#);
+        let arg = cast!(&event[1], Js::Compound);
+        assert_eq!(cast!(arg[0], Js::Simple).raw, "name");
+        assert_eq!(cast!(arg[1], Js::Src), r#" || """#);
+        assert_eq!(cast!(event[2], Js::Src), ")");
+    }
+
+    #[test]
+    fn test_v_model_malformed() {
+        let cases = [
+            "<comp v-model='a + b'/>",
+            "<comp v-model=\"'literal'\"/>",
+            "<comp v-model='a?.b'/>",
+            "<comp v-model='a?.[b]'/>",
+            "<comp v-model='a.b?.c'/>",
+        ];
+        for case in cases {
+            let errs = error_convert(case);
+            assert_eq!(errs.len(), 1, "{}", case);
+            assert!(matches!(errs[0].kind, ErrorKind::VModelMalformedExpression));
+        }
+        let errs = error_convert("<comp v-model/>");
+        assert!(matches!(errs[0].kind, ErrorKind::VModelNoExpression));
+        assert!(error_convert("<comp v-model='obj.list[idx]'/>").is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use super::{
-    CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element, ErrorHandler,
+    CompilationError, ConvertContext, CoreDirConvRet, Directive, DirectiveConvertResult,
+    DirectiveConverter, Element, ErrorHandler,
 };
 use crate::{
     error::CompilationErrorKind as ErrorKind,
@@ -13,9 +14,14 @@ use crate::{
 // this module process v-on without arg and with arg.
 pub fn convert_v_on<'a>(
     dir: &mut Directive<'a>,
-    _: &Element<'a>,
+    _: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
+    if let Some(error) = dir.check_dynamic_arg() {
+        eh.on_error(error);
+        return DirectiveConvertResult::Dropped;
+    }
     if let Some(error) = dir.check_empty_expr(ErrorKind::VOnNoExpression) {
         // no argument no expr, just return
         if dir.argument.is_none() {
@@ -30,11 +36,24 @@ pub fn convert_v_on<'a>(
     let Directive {
         expression,
         argument,
+        modifiers,
+        head_loc,
         ..
     } = dir;
     let value = if let Some(arg) = argument {
         let event_name = match arg {
-            DirectiveArg::Static(s) => Js::StrLit(*VStr::raw(s).be_handler()),
+            DirectiveArg::Static(s) => {
+                if let Some(hook) = get_vnode_hook(s) {
+                    if s.starts_with("vnode-") {
+                        let warning = CompilationError::new(ErrorKind::VOnVnodeHookDeprecated)
+                            .with_location(head_loc.clone());
+                        eh.on_warn(warning);
+                    }
+                    Js::StrLit(*VStr::raw(hook).camelize().be_vnode_hook())
+                } else {
+                    Js::StrLit(*VStr::raw(s).be_handler())
+                }
+            }
             DirectiveArg::Dynamic(s) => {
                 let e = Js::simple(*s);
                 Js::Call(RuntimeHelper::TO_HANDLER_KEY, vec![e])
@@ -44,6 +63,11 @@ pub fn convert_v_on<'a>(
         Js::Props(vec![(event_name, exp)])
     } else {
         // bare v-on="" does not have mods
+        if !modifiers.is_empty() {
+            let error = CompilationError::new(ErrorKind::VOnObjectWithModifiers)
+                .with_location(head_loc.clone());
+            eh.on_error(error);
+        }
         let exp = expression
             .as_ref()
             .expect("v-on with no expr nor arg should be dropped.");
@@ -56,6 +80,14 @@ pub fn convert_v_on<'a>(
     }
 }
 
+/// Returns the hook name if the event listens to vnode lifecycle,
+/// e.g. `@vue:mounted` or the deprecated `@vnode-mounted`.
+pub fn get_vnode_hook(event: &str) -> Option<&str> {
+    event
+        .strip_prefix("vue:")
+        .or_else(|| event.strip_prefix("vnode-"))
+}
+
 pub fn convert_v_on_expr<'a>(expr: Option<&AttributeValue<'a>>) -> Js<'a> {
     let val = match expr {
         Some(val) => val.content,
@@ -104,13 +136,22 @@ fn is_fn_exp(raw: &str) -> bool {
 }
 
 pub fn is_member_expression(expr: VStr) -> bool {
+    is_member_expr_with(expr, rslint::is_member_expression)
+}
+
+/// like is_member_expression but rejects optional chaining, e.g. a?.b = $event
+pub fn is_assignable_member_expression(expr: VStr) -> bool {
+    is_member_expr_with(expr, rslint::is_assignable_member_expression)
+}
+
+fn is_member_expr_with(expr: VStr, check: fn(&str) -> bool) -> bool {
     if VStr::has_affix(&expr) {
         return false;
     }
     if !expr.raw.starts_with(char::is_alphabetic) {
         return false;
     }
-    is_simple_identifier(expr) || rslint::is_member_expression(&expr)
+    is_simple_identifier(expr) || check(&expr)
 }
 
 pub fn get_handler_type(val: VStr) -> HandlerType {
@@ -127,7 +168,35 @@ pub const V_ON: DirectiveConverter = ("on", convert_v_on);
 
 #[cfg(test)]
 mod test {
+    use super::super::test::{error_convert, handler_convert};
     use super::*;
+    use crate::{cast, ir::IRNode};
+
+    #[test]
+    fn test_dynamic_event() {
+        let mut body = handler_convert("<p @[ev]='a'/>").body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut props = cast!(vn.props.unwrap(), Js::Props);
+        let (key, _) = props.remove(0);
+        let mut args = match key {
+            Js::Call(RuntimeHelper::TO_HANDLER_KEY, args) => args,
+            _ => panic!("dynamic event should call toHandlerKey"),
+        };
+        assert_eq!(cast!(args.remove(0), Js::Simple).into_string(), "ev");
+        let errors = error_convert("<p @[]='a'/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::EmptyDynamicArgument));
+    }
+
+    #[test]
+    fn test_object_syntax_with_modifiers() {
+        let errors = error_convert("<p v-on.stop='handlers'/>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VOnObjectWithModifiers));
+        let errors = error_convert("<p v-on='handlers' @click.stop='a'/>");
+        assert!(errors.is_empty());
+    }
+
     #[test]
     fn test_is_fn_expr() {
         let positive_cases = [
@@ -27,12 +27,13 @@ pub fn check_wrong_slot(bc: &BC, e: &Element, kind: ErrorKind) -> bool {
 
 pub fn check_build_as_slot(e: &Element, tag: &Js) -> bool {
     debug_assert!(e.tag_type != ElementType::Template);
+    e.is_component() && !should_use_raw_children(tag)
+}
+
+/// Teleport and KeepAlive take raw children instead of slot functions
+pub fn should_use_raw_children(tag: &Js) -> bool {
     use RuntimeHelper as RH;
-    match tag {
-        Js::Symbol(RH::KEEP_ALIVE) => true,
-        Js::Symbol(RH::TELEPORT) => true,
-        _ => e.is_component(),
-    }
+    matches!(tag, Js::Symbol(RH::KEEP_ALIVE | RH::TELEPORT))
 }
 
 type BaseVSlot<'a> = VSlotIR<BaseConvertInfo<'a>>;
@@ -51,7 +52,7 @@ pub fn convert_v_slot<'a>(bc: &BC<'a>, e: &mut Element<'a>) -> BaseIR<'a> {
     // 2. traverse children and check template slots
     let mut v_slot_ir = build_explicit_slots(bc, explicit_slots);
     // 3. merge stable slot and alterable ones if available
-    if !implicit_default.is_empty() {
+    if implicit_default.iter().any(is_non_whitespace_content) {
         if has_named_default(&v_slot_ir) {
             let first_child = &implicit_default[0];
             let error = CompilationError::new(ErrorKind::VSlotExtraneousDefaultSlotChildren)
@@ -238,6 +239,15 @@ fn is_template_slot(e: &Element) -> bool {
     dir_finder(e, "slot").allow_empty().find().is_some()
 }
 
+// whitespace and comments alone do not make an implicit default slot
+fn is_non_whitespace_content(n: &AstNode) -> bool {
+    match n {
+        AstNode::Text(t) => !t.is_all_whitespace(),
+        AstNode::Comment(_) => false,
+        _ => true,
+    }
+}
+
 fn has_named_default(v_slot_ir: &BaseVSlot) -> bool {
     v_slot_ir.stable_slots.iter().any(|p| match p.name {
         Js::StrLit(s) => s.raw == "default",
@@ -247,10 +257,27 @@ fn has_named_default(v_slot_ir: &BaseVSlot) -> bool {
 
 #[cfg(test)]
 mod test {
-    use super::super::test::{assert_str_lit, base_convert};
+    use super::super::test::{assert_str_lit, base_convert, error_convert};
     use super::*;
     use crate::cast;
     #[test]
+    fn test_misplaced_slot() {
+        let case = "<div v-slot='a'/>";
+        let errors = error_convert(case);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VSlotMisplaced));
+        assert_eq!(
+            errors[0].location.start.offset,
+            case.find("v-slot").unwrap()
+        );
+        let mut body = base_convert(case).body;
+        let vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+        assert!(vn.directives.is_empty());
+        let errors = error_convert("<comp v-slot='a'/><comp><template v-slot:b/></comp>");
+        assert!(errors.is_empty());
+    }
+    #[test]
     fn test_implicit_default_slot() {
         let mut body = base_convert("<comp>hello</comp>").body;
         let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
@@ -261,7 +288,111 @@ mod test {
         assert_str_lit(&text.texts[0], "hello");
     }
     #[test]
-    fn test_implicit_named_slot() {}
+    fn test_on_component_slot() {
+        let mut body = base_convert("<comp v-slot>hello</comp>").body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        assert!(matches!(v_slot.slot_flag, SlotFlag::Stable));
+        assert_eq!(v_slot.stable_slots.len(), 1);
+        assert!(v_slot.alterable_slots.is_empty());
+        let slot = v_slot.stable_slots.remove(0);
+        assert_str_lit(&slot.name, "default");
+        assert!(slot.param.is_none());
+        assert!(matches!(slot.body[0], IRNode::TextCall(_)));
+    }
     #[test]
-    fn test_template_slot() {}
+    fn test_on_component_slot_param() {
+        let mut body = base_convert("<comp v-slot='{ item }'>{{item}}</comp>").body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        let slot = v_slot.stable_slots.remove(0);
+        assert_str_lit(&slot.name, "default");
+        assert!(matches!(slot.param, Some(Js::Param("{ item }"))));
+    }
+    #[test]
+    fn test_mixed_slot_usage() {
+        let case = "<comp v-slot='a'>a<template v-slot:b>b</template></comp>";
+        let errors = error_convert(case);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VSlotMixedSlotUsage));
+        assert_eq!(
+            errors[0].location.start.offset,
+            case.find("v-slot:b").unwrap()
+        );
+        let mut body = base_convert(case).body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        assert_eq!(v_slot.stable_slots.len(), 1);
+        assert_eq!(v_slot.stable_slots[0].body.len(), 1);
+    }
+    #[test]
+    fn test_implicit_named_slot() {
+        let case = "<comp>a<template #header='props'>b</template><p/></comp>";
+        let mut body = base_convert(case).body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        let slots = &v_slot.stable_slots;
+        assert_eq!(slots.len(), 2);
+        assert_str_lit(&slots[0].name, "header");
+        assert!(matches!(slots[0].param, Some(Js::Param("props"))));
+        assert_str_lit(&slots[1].name, "default");
+        assert!(slots[1].param.is_none());
+        assert_eq!(slots[1].body.len(), 2);
+    }
+    #[test]
+    fn test_template_slot() {
+        let case = "<comp>
+            <template #header='props'>a</template>
+            <template #footer>b</template>
+        </comp>";
+        let mut body = base_convert(case).body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        assert_eq!(vn.children.len(), 1);
+        let v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        // whitespace between templates does not form a default slot
+        let names: Vec<_> = v_slot
+            .stable_slots
+            .iter()
+            .map(|s| cast!(&s.name, Js::StrLit).raw)
+            .collect();
+        assert_eq!(names, vec!["header", "footer"]);
+        assert!(v_slot.stable_slots[1].param.is_none());
+        assert!(v_slot.alterable_slots.is_empty());
+        let case = "<comp><template #a>a</template> <!--c--> <template #b>b</template></comp>";
+        let mut body = base_convert(case).body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        assert_eq!(v_slot.stable_slots.len(), 2);
+        assert!(!has_named_default(&v_slot));
+    }
+    #[test]
+    fn test_duplicate_slot_names() {
+        let case = "<comp><template #a>1</template><template #a>2</template></comp>";
+        let errors = error_convert(case);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VSlotDuplicateSlotNames));
+        assert_eq!(errors[0].location.start.offset, case.rfind("#a").unwrap());
+        let mut body = base_convert(case).body;
+        let mut vn = cast!(body.remove(0), IRNode::VNodeCall);
+        let mut v_slot = cast!(vn.children.remove(0), IRNode::VSlotUse);
+        assert_eq!(v_slot.stable_slots.len(), 1);
+        let mut slot = v_slot.stable_slots.remove(0);
+        let text = cast!(slot.body.remove(0), IRNode::TextCall);
+        assert_str_lit(&text.texts[0], "1");
+    }
+    #[test]
+    fn test_same_slot_names_in_branches() {
+        let case = "<comp>\
+            <template v-if='x' #a>1</template>\
+            <template v-else #a>2</template>\
+        </comp>\
+        <comp><template #a>3</template></comp>";
+        assert!(error_convert(case).is_empty());
+    }
+    #[test]
+    fn test_non_template_slot_child() {
+        let errors = error_convert("<comp><p v-slot:a>a</p></comp>");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::VSlotMisplaced));
+    }
 }
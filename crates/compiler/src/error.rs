@@ -1,4 +1,4 @@
-use super::SourceLocation;
+use super::{line_column, SourceLocation};
 use std::cell::{Ref, RefMut, RefCell};
 use std::fmt;
 use std::rc::Rc;
@@ -28,6 +28,7 @@ pub enum CompilationErrorKind {
     UnexpectedEqualsSignBeforeAttributeName,
     UnexpectedCharacterInAttributeName,
     UnexpectedCharacterInUnquotedAttributeValue,
+    UnexpectedDoctype,
     UnexpectedNullCharacter, // TODO
     UnexpectedQuestionMarkInsteadOfTagName,
     UnexpectedSolidusInTag,
@@ -47,12 +48,18 @@ pub enum CompilationErrorKind {
     VIfNoExpression,
     VIfSameKey,
     VIfDuplicateDir,
+    VIfWithVFor,
     VElseNoAdjacentIf,
     VForNoExpression,
     VForMalformedExpression,
     VForTemplateKeyPlacement,
     VBindNoExpression,
+    VBindSyncModifierRemoved,
     VOnNoExpression,
+    VOnVnodeHookDeprecated,
+    VOnObjectWithModifiers,
+    AttributeCaseInsensitive,
+    DuplicateProperty,
     VSlotUnexpectedDirectiveOnSlotOutlet,
     VSlotMixedSlotUsage,
     VSlotTemplateMisplaced,
@@ -60,13 +67,19 @@ pub enum CompilationErrorKind {
     VSlotExtraneousDefaultSlotChildren,
     VSlotMisplaced,
     VMemoNoExpression,
+    VMemoOnTemplate,
+    InterpolationNoExpression,
     VModelNoExpression,
     VModelMalformedExpression,
     VModelOnScopeVariable,
+    VModelOnProps,
     InvalidExpression,
+    EmptyDynamicArgument,
 
     UnexpectedDirExpression,
     KeepAliveInvalidChildren,
+    DynamicComponentNoIs,
+    UnresolvedComponentNamespace,
 
     // generic errors
     PrefixIdNotSupported,
@@ -121,6 +134,23 @@ impl CompilationError {
     pub fn msg(&self) -> &'static str {
         msg(&self.kind)
     }
+
+    /// Renders the message with the source line and carets under the error.
+    pub fn render_snippet(&self, source: &str) -> String {
+        let start = self.location.start.offset;
+        let (line, column) = line_column(source, start);
+        let text = source.lines().nth(line as usize - 1).unwrap_or("");
+        // carets do not go beyond the first line of the location
+        let rest = text.chars().count().saturating_sub(column as usize - 1);
+        let len = self.location.end.offset.saturating_sub(start);
+        let carets = "^".repeat(len.min(rest).max(1));
+        let pad = " ".repeat(line.to_string().len());
+        let indent = " ".repeat(column as usize - 1);
+        format!(
+            "{}\n{} --> {}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self, pad, line, column, pad, line, text, pad, indent, carets
+        )
+    }
 }
 
 #[cold]
@@ -150,6 +180,7 @@ fn msg(kind: &CompilationErrorKind) -> &'static str {
          "Attribute name cannot contain U+0022 (\"), U+0027 ('), and U+003C (<).",
         UnexpectedCharacterInUnquotedAttributeValue =>
             "Unquoted attribute value cannot contain U+0022 (\"), U+0027 (\'), U+003C (<), U+003D (=), and U+0060 (`).",
+        UnexpectedDoctype => "DOCTYPE is only allowed at the start of the template.",
         UnexpectedQuestionMarkInsteadOfTagName => "'<?' is allowed only in XML context.",
         UnexpectedNullCharacter => "Unexpected null character.",
         UnexpectedSolidusInTag => "Illegal '/' in tags.",
@@ -171,12 +202,23 @@ fn msg(kind: &CompilationErrorKind) -> &'static str {
         VIfNoExpression => "v-if/v-else-if is missing expression.",
         VIfSameKey => "v-if/else branches must use unique keys.",
         VIfDuplicateDir => "Duplicate v-if/else-if/else. Use v-else-if instead.",
+        VIfWithVFor =>
+            "v-if and v-for on the same element is discouraged. Vue 3 evaluates v-if before v-for, but this element is converted with v-if inside v-for. Move v-for to a wrapping <template> to make the order explicit.",
         VElseNoAdjacentIf => "v-else/v-else-if has no adjacent v-if.",
         VForNoExpression => "v-for is missing expression.",
         VForMalformedExpression => "v-for has invalid expression.",
         VForTemplateKeyPlacement => "<template v-for> key should be placed on the <template> tag.",
         VBindNoExpression => "v-bind is missing expression.",
+        VBindSyncModifierRemoved =>
+            "The .sync modifier of v-bind has been removed in Vue 3. Use v-model with an argument instead. For example, :foo.sync should be changed to v-model:foo.",
         VOnNoExpression => "v-on is missing expression.",
+        VOnVnodeHookDeprecated =>
+            "@vnode-* hooks in templates are deprecated. Use the vue: prefix instead. For example, @vnode-mounted should be changed to @vue:mounted.",
+        VOnObjectWithModifiers => "v-on object syntax does not support modifiers.",
+        AttributeCaseInsensitive =>
+            "Attribute name with uppercase letters will be lowercased by the browser. Use v-bind if the casing is intended.",
+        DuplicateProperty =>
+            "Duplicate property binding. Only the first one takes effect.",
         VSlotUnexpectedDirectiveOnSlotOutlet => "Unexpected custom directive on <slot> outlet.",
         VSlotMixedSlotUsage =>
             "Mixed v-slot usage on both the component and nested <template>. When there are multiple named slots, all slots should use <template> syntax to avoid scope ambiguity.",
@@ -186,13 +228,21 @@ fn msg(kind: &CompilationErrorKind) -> &'static str {
         VSlotMisplaced => "v-slot can only be used on components or <template> tags.",
         VSlotTemplateMisplaced => "<template v-slot> can only be used as a component's direct child.",
         VMemoNoExpression => "v-memo is missing expression.",
+        VMemoOnTemplate => "v-memo on <template> can only be used together with v-for.",
+        InterpolationNoExpression => "Interpolation is missing expression.",
         VModelNoExpression => "v-model is missing expression.",
         VModelMalformedExpression => "v-model value must be a valid JavaScript member expression.",
         VModelOnScopeVariable =>
             "v-model cannot be used on v-for or v-slot scope variables because they are not writable.",
+        VModelOnProps =>
+            "v-model cannot be used on a prop, because local prop bindings are not writable. Use a v-bind binding combined with a v-on listener that emits update:x event instead.",
         InvalidExpression => "Error parsing JavaScript expression: ",
+        EmptyDynamicArgument => "Dynamic directive argument cannot be empty.",
         UnexpectedDirExpression => "This directive does not accept any epxression.",
         KeepAliveInvalidChildren => "<KeepAlive> expects exactly one child component.",
+        DynamicComponentNoIs => "<component> is missing `is` prop to specify which component to render.",
+        UnresolvedComponentNamespace =>
+            "Namespaced component tag must start with a binding from <script setup>.",
 
         // generic errors
         PrefixIdNotSupported =>
@@ -230,6 +280,11 @@ pub trait ErrorHandler {
     // cannot use mut ref due to borrow semantics
     // use RefCell as implementation
     fn on_error(&self, _: CompilationError) {}
+    /// Reports issues that do not break compilation, e.g. deprecated usage.
+    /// Forwards to on_error by default.
+    fn on_warn(&self, warning: CompilationError) {
+        self.on_error(warning)
+    }
 }
 
 pub type RcErrHandle = Rc<dyn ErrorHandler>;
@@ -239,12 +294,11 @@ impl ErrorHandler for NoopErrorHandler {}
 
 pub struct VecErrorHandler {
     errors: RefCell<Vec<CompilationError>>,
+    warnings: RefCell<Vec<CompilationError>>,
 }
 impl VecErrorHandler {
     pub fn new() -> Self {
-        Self {
-            errors: RefCell::new(vec![]),
-        }
+        Self::default()
     }
     pub fn errors(&self) -> Ref<Vec<CompilationError>> {
         self.errors.borrow()
@@ -252,11 +306,18 @@ impl VecErrorHandler {
     pub fn error_mut(&self) -> RefMut<Vec<CompilationError>> {
         self.errors.borrow_mut()
     }
+    pub fn warnings(&self) -> Ref<Vec<CompilationError>> {
+        self.warnings.borrow()
+    }
+    pub fn warning_mut(&self) -> RefMut<Vec<CompilationError>> {
+        self.warnings.borrow_mut()
+    }
 }
 impl Default for VecErrorHandler {
     fn default() -> Self {
         Self {
             errors: RefCell::new(vec![]),
+            warnings: RefCell::new(vec![]),
         }
     }
 }
@@ -265,12 +326,39 @@ impl ErrorHandler for VecErrorHandler {
     fn on_error(&self, e: CompilationError) {
         self.errors.borrow_mut().push(e);
     }
+    fn on_warn(&self, w: CompilationError) {
+        self.warnings.borrow_mut().push(w);
+    }
 }
 
 #[cfg(test)]
 pub mod test {
-    use super::ErrorHandler;
+    use super::*;
+    use crate::converter::test::error_convert;
     #[derive(Clone)]
     pub struct TestErrorHandler;
     impl ErrorHandler for TestErrorHandler {}
+
+    #[test]
+    fn test_line_column() {
+        let source = "<div>\n  <p>\n    {{ }}\n  </p>\n</div>";
+        let errors = error_convert(source);
+        assert_eq!(errors.len(), 1);
+        let start = &errors[0].location.start;
+        assert_eq!(start.offset, source.find("{{").unwrap());
+        assert_eq!(line_column(source, start.offset), (3, 5));
+        assert_eq!((start.line, start.column), (3, 5));
+        assert_eq!(line_column("ab", 0), (1, 1));
+        assert_eq!(line_column("a\nb", 2), (2, 1));
+    }
+
+    #[test]
+    fn test_render_snippet() {
+        let source = "<div>\n  <p>\n    {{ }}\n  </p>\n</div>";
+        let errors = error_convert(source);
+        let snippet = errors[0].render_snippet(source);
+        let expected =
+            "Interpolation is missing expression.\n  --> 3:5\n  |\n3 |     {{ }}\n  |     ^^^^^\n";
+        assert_eq!(snippet, expected);
+    }
 }
@@ -228,6 +228,26 @@ impl Serialize for RuntimeHelper {
     }
 }
 
+/// Runtime value of a built-in component, used as vnode type
+/// without calling `resolveComponent`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BuiltinComponent {
+    /// component provided by vue runtime, e.g. Transition
+    Helper(RuntimeHelper),
+    /// named export from another module, e.g. RouterView from vue-router.
+    /// It generates `import { RouterView as _imported_RouterView } from "vue-router"`
+    Import {
+        name: &'static str,
+        path: &'static str,
+    },
+}
+
+impl From<RuntimeHelper> for BuiltinComponent {
+    fn from(helper: RuntimeHelper) -> Self {
+        Self::Helper(helper)
+    }
+}
+
 pub const HELPERS_IN_HOISTED: &[RH] = &[
     RH::CREATE_COMMENT,
     RH::CREATE_ELEMENT_VNODE,
@@ -252,6 +272,9 @@ impl HelperCollector {
     pub fn contains(&self, helper: RuntimeHelper) -> bool {
         (self.0 & (1 << helper.0)) != 0
     }
+    pub fn merge(&mut self, other: &Self) {
+        self.0 |= other.0;
+    }
     #[must_use]
     pub fn hoist_helpers(&self) -> Self {
         let mut n = Self(0);
@@ -1,10 +1,9 @@
 use crate::{
     flags::{PatchFlag, RuntimeHelper, SlotFlag, StaticLevel},
-    util::VStr,
-    Name,
+    util::{is_literal_constant, VStr},
+    Name, SourceLocation,
 };
-use rustc_hash::FxHashSet;
-use std::hash::Hash;
+use std::{hash::Hash, mem};
 
 #[cfg(feature = "serde")]
 use serde::Serialize;
@@ -15,7 +14,8 @@ pub trait ConvertInfo {
     // TextType should be a slice of JsExpressions
     type TextType: AsMut<[Self::JsExpression]> + Serialize;
     type IfBranchType: Serialize;
-    type CommentType: Serialize;
+    type CommentType: Default + Serialize;
+    type HoistedType: Serialize;
     type JsExpression: Default + Serialize;
     type StrType: Serialize + Eq + Hash;
 }
@@ -25,7 +25,8 @@ pub trait ConvertInfo {
     // TextType should be a slice of JsExpressions
     type TextType: AsMut<[Self::JsExpression]>;
     type IfBranchType;
-    type CommentType;
+    type CommentType: Default;
+    type HoistedType;
     type JsExpression: Default;
     type StrType: Eq + Hash;
 }
@@ -50,6 +51,10 @@ pub enum IRNode<T: ConvertInfo> {
     CacheNode(CacheIR<T>),
     /// comment
     CommentCall(T::CommentType),
+    /// adjacent static nodes stringified into html
+    StaticCall(StaticIR),
+    /// reference to a static node hoisted to top scope
+    Hoisted(T::HoistedType),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -59,6 +64,13 @@ pub struct TextIR<T: ConvertInfo> {
     pub texts: T::TextType,
 }
 #[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StaticIR {
+    /// serialized html of the static nodes
+    pub content: String,
+    /// number of top level nodes in content
+    pub count: usize,
+}
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct IfNodeIR<T: ConvertInfo> {
     pub branches: Vec<IfBranch<T>>,
 }
@@ -67,6 +79,7 @@ pub struct IfBranch<T: ConvertInfo> {
     pub condition: Option<T::JsExpression>,
     pub child: Box<IRNode<T>>,
     pub info: T::IfBranchType,
+    pub loc: SourceLocation,
 }
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ForNodeIR<T: ConvertInfo> {
@@ -107,11 +120,13 @@ pub struct VNodeIR<T: ConvertInfo> {
     pub props: Option<T::JsExpression>,
     pub children: Vec<IRNode<T>>,
     pub patch_flag: PatchFlag,
-    pub dynamic_props: FxHashSet<T::StrType>,
+    pub dynamic_props: Vec<T::StrType>,
     pub directives: Vec<RuntimeDir<T>>,
     pub is_block: bool,
     pub disable_tracking: bool,
     pub is_component: bool,
+    /// boxed since locations are only read when reporting errors
+    pub loc: Box<SourceLocation>,
 }
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Slot<T: ConvertInfo> {
@@ -149,6 +164,8 @@ pub struct CacheIR<T: ConvertInfo> {
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct IRRoot<T: ConvertInfo> {
     pub body: Vec<IRNode<T>>,
+    /// multiple root nodes are wrapped in a Fragment in codegen
+    pub is_fragment: bool,
     /// entities to define/import in top level scope
     pub top_scope: T::TopType,
 }
@@ -182,6 +199,12 @@ pub enum JsExpr<'a> {
     // NB: StaticLevel = CanStringify does not imply StrLit. e.g.
     // in :num="4", 4 is stringifiable but not StrLit
     StrLit(VStr<'a>),
+    /// owned text joined from adjacent literal segments by flatten_compound.
+    /// output after quoted like StrLit if `quoted`, otherwise as is like Src.
+    Joined {
+        text: String,
+        quoted: bool,
+    },
     /// non-string js expression, will be processed like prefixing
     Simple(VStr<'a>, StaticLevel),
     /// variable in parameter
@@ -230,11 +253,26 @@ impl<'a> JsExpr<'a> {
             cache: false,
         }
     }
+    /// Returns true if the expression is a compile time constant.
+    /// Simple expression is static only if it is a literal, e.g. `42`.
+    pub fn is_static(&self) -> bool {
+        use JsExpr::*;
+        match self {
+            Num(_) | StrLit(_) | Symbol(_) => true,
+            Src(s) => is_literal_constant(s),
+            Joined { text, quoted } => *quoted || is_literal_constant(text),
+            Simple(v, lvl) => *lvl == StaticLevel::CanStringify || is_literal_constant(v.raw),
+            Compound(v) | Array(v) => v.iter().all(Self::is_static),
+            Props(ps) => ps.iter().all(|(k, v)| k.is_static() && v.is_static()),
+            Param(_) | FuncSimple { .. } | FuncCompound { .. } | Call(..) => false,
+        }
+    }
     pub fn static_level(&self) -> StaticLevel {
         use JsExpr::*;
         use StaticLevel as S;
         match self {
-            Num(_) | StrLit(_) => S::CanStringify,
+            Num(_) | StrLit(_) | Joined { quoted: true, .. } => S::CanStringify,
+            Joined { quoted: false, .. } => S::CanHoist,
             Simple(_, level) => *level,
             Symbol(_) | Src(_) | Param(_) => S::CanHoist,
             Compound(v) | Array(v) | Call(_, v) => vec_static_level(v),
@@ -245,6 +283,101 @@ impl<'a> JsExpr<'a> {
     }
 }
 
+/// Folds constant expressions at convert time so codegen emits plain literals.
+/// A Compound of string literals collapses into one string literal.
+pub fn fold_constant(e: JsExpr) -> JsExpr {
+    match e {
+        JsExpr::Compound(v) => {
+            let mut v = flatten_compound(v);
+            if !is_str_lit_compound(&v) {
+                return JsExpr::Compound(v);
+            }
+            // flattening joins adjacent string literals, so at most one is left
+            debug_assert!(v.len() <= 1);
+            v.pop().unwrap_or_else(|| JsExpr::str_lit(""))
+        }
+        JsExpr::Props(ps) => JsExpr::Props(
+            ps.into_iter()
+                .map(|(k, v)| (fold_constant(k), fold_constant(v)))
+                .collect(),
+        ),
+        e => e,
+    }
+}
+
+/// Returns true if all segments in a Compound are string literals.
+pub fn is_str_lit_compound(v: &[JsExpr]) -> bool {
+    v.iter().all(|e| literal_kind(e) == Some(true))
+}
+
+/// Collapses nested Compound expressions into a single level.
+/// Adjacent StrLit or Src segments are joined into one owned segment.
+pub fn flatten_compound(v: Vec<JsExpr>) -> Vec<JsExpr> {
+    let mut flat = Vec::with_capacity(v.len());
+    flatten_into(v, &mut flat);
+    flat
+}
+
+fn flatten_into<'a>(v: Vec<JsExpr<'a>>, flat: &mut Vec<JsExpr<'a>>) {
+    for e in v {
+        match e {
+            JsExpr::Compound(inner) => flatten_into(inner, flat),
+            e => push_joined(flat, e),
+        }
+    }
+}
+
+// Some(true) for quoted string literal, Some(false) for raw source
+fn literal_kind(e: &JsExpr) -> Option<bool> {
+    match e {
+        JsExpr::StrLit(_) => Some(true),
+        JsExpr::Src(_) => Some(false),
+        JsExpr::Joined { quoted, .. } => Some(*quoted),
+        _ => None,
+    }
+}
+
+fn is_empty_literal(e: &JsExpr) -> bool {
+    match e {
+        JsExpr::StrLit(s) => s.raw.is_empty(),
+        JsExpr::Src(s) => s.is_empty(),
+        JsExpr::Joined { text, .. } => text.is_empty(),
+        _ => false,
+    }
+}
+
+fn push_joined<'a>(flat: &mut Vec<JsExpr<'a>>, e: JsExpr<'a>) {
+    let kind = literal_kind(&e);
+    let last = match flat.last_mut() {
+        Some(last) if kind.is_some() && literal_kind(last) == kind => last,
+        _ => return flat.push(e),
+    };
+    // empty segment joins as is, no need to own the text
+    if is_empty_literal(&e) {
+        return;
+    } else if is_empty_literal(last) {
+        *last = e;
+        return;
+    }
+    let mut text = String::new();
+    write_literal(mem::take(last), &mut text);
+    write_literal(e, &mut text);
+    let quoted = kind == Some(true);
+    *last = JsExpr::Joined { text, quoted };
+}
+
+fn write_literal(e: JsExpr, text: &mut String) {
+    match e {
+        JsExpr::StrLit(s) => {
+            s.write_to(&mut *text)
+                .expect("writing to String never fails");
+        }
+        JsExpr::Src(s) => text.push_str(s),
+        JsExpr::Joined { text: t, .. } => text.push_str(&t),
+        _ => panic!("only literal can be joined"),
+    }
+}
+
 fn vec_static_level(v: &[JsExpr]) -> StaticLevel {
     v.iter()
         .map(JsExpr::static_level)
@@ -257,3 +390,90 @@ fn prop_level(prop: &Prop) -> StaticLevel {
     let val_level = prop.1.static_level();
     key_level.min(val_level)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cast;
+
+    #[test]
+    fn test_flatten_compound() {
+        let nested = vec![
+            JsExpr::Src("a"),
+            JsExpr::Compound(vec![
+                JsExpr::Src("b"),
+                JsExpr::Compound(vec![JsExpr::simple("c")]),
+            ]),
+            JsExpr::Src("d"),
+        ];
+        let flat = flatten_compound(nested);
+        assert_eq!(flat.len(), 3);
+        assert!(matches!(&flat[0], JsExpr::Joined { text, quoted: false } if text == "ab"));
+        assert_eq!(cast!(&flat[1], JsExpr::Simple).raw, "c");
+        assert!(matches!(flat[2], JsExpr::Src("d")));
+        // adjacent string literals are joined into one literal
+        let nested = vec![
+            JsExpr::str_lit("a"),
+            JsExpr::Compound(vec![JsExpr::str_lit("b"), JsExpr::simple("c")]),
+        ];
+        let flat = flatten_compound(nested);
+        assert_eq!(flat.len(), 2);
+        assert!(matches!(&flat[0], JsExpr::Joined { text, quoted: true } if text == "ab"));
+        assert_eq!(cast!(&flat[1], JsExpr::Simple).raw, "c");
+        // string literal and source are never joined
+        let flat = flatten_compound(vec![JsExpr::str_lit("a"), JsExpr::Src("b")]);
+        assert_eq!(flat.len(), 2);
+        // other expressions are not flattened
+        let call = JsExpr::Call(RuntimeHelper::UNREF, vec![JsExpr::Compound(vec![])]);
+        let flat = flatten_compound(vec![call]);
+        assert!(matches!(&flat[0], JsExpr::Call(_, args) if args.len() == 1));
+    }
+
+    #[test]
+    fn test_is_static() {
+        assert!(JsExpr::simple("42").is_static());
+        assert!(JsExpr::simple("true").is_static());
+        assert!(!JsExpr::simple("a").is_static());
+        assert!(!JsExpr::simple("a + 1").is_static());
+        let props = JsExpr::Props(vec![
+            (JsExpr::str_lit("id"), JsExpr::str_lit("a")),
+            (JsExpr::str_lit("count"), JsExpr::simple("42")),
+            (JsExpr::str_lit("checked"), JsExpr::Src("true")),
+        ]);
+        assert!(props.is_static());
+        let props = JsExpr::Props(vec![
+            (JsExpr::str_lit("id"), JsExpr::str_lit("a")),
+            (JsExpr::str_lit("title"), JsExpr::simple("msg")),
+        ]);
+        assert!(!props.is_static());
+    }
+
+    #[test]
+    fn test_fold_constant() {
+        let single = JsExpr::Compound(vec![
+            JsExpr::str_lit(""),
+            JsExpr::Compound(vec![JsExpr::str_lit("a")]),
+        ]);
+        let folded = fold_constant(single);
+        assert_eq!(cast!(folded, JsExpr::StrLit).raw, "a");
+        let multi = JsExpr::Compound(vec![
+            JsExpr::str_lit("a"),
+            JsExpr::str_lit(" "),
+            JsExpr::Compound(vec![JsExpr::str_lit("b")]),
+        ]);
+        let folded = fold_constant(multi);
+        assert!(matches!(folded, JsExpr::Joined { text, quoted: true } if text == "a b"));
+        let empty = fold_constant(JsExpr::Compound(vec![]));
+        assert_eq!(cast!(empty, JsExpr::StrLit).raw, "");
+        let mixed = JsExpr::Compound(vec![JsExpr::str_lit(""), JsExpr::simple("a")]);
+        let folded = fold_constant(mixed);
+        assert_eq!(cast!(folded, JsExpr::Compound).len(), 2);
+        let props = JsExpr::Props(vec![(
+            JsExpr::str_lit("id"),
+            JsExpr::Compound(vec![JsExpr::str_lit("x")]),
+        )]);
+        let props = cast!(fold_constant(props), JsExpr::Props);
+        assert_eq!(cast!(&props[0].1, JsExpr::StrLit).raw, "x");
+        assert!(props[0].1.static_level() == StaticLevel::CanStringify);
+    }
+}
@@ -65,6 +65,22 @@ impl Default for Position {
     }
 }
 
+/// Returns the 1-based (line, column) of an offset in the source.
+/// Like `Position::offset`, the offset is counted in chars.
+pub fn line_column(source: &str, offset: usize) -> (u32, u32) {
+    let mut line = 1;
+    let mut column = 1;
+    for c in source.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
 #[derive(Default, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct SourceLocation {
@@ -174,6 +190,23 @@ impl<'a> Default for SFCInfo<'a> {
     }
 }
 
+impl<'a> SFCInfo<'a> {
+    /// Infers self_name from the component file, e.g. `src/foo-bar.vue` is `FooBar`.
+    #[must_use]
+    pub fn with_filename(mut self, filename: &str) -> Self {
+        let path = filename.split('?').next().unwrap_or_default();
+        let base = path
+            .rsplit(|c| c == '/' || c == '\\')
+            .next()
+            .unwrap_or_default();
+        self.self_name = match base.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => VStr::raw(stem).pascalize().into_string(),
+            _ => String::new(),
+        };
+        self
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -181,4 +214,13 @@ mod test {
     fn test_source_size() {
         assert_eq!(std::mem::size_of::<Position>(), 16);
     }
+
+    #[test]
+    fn test_self_name_from_filename() {
+        let name = |f: &str| SFCInfo::default().with_filename(f).self_name;
+        assert_eq!(name("FooBar.vue"), "FooBar");
+        assert_eq!(name("src/components/foo-bar.vue"), "FooBar");
+        assert_eq!(name("C:\\app\\foo_bar.vue?vue&type=template"), "Foo_bar");
+        assert_eq!(name("anonymous"), "");
+    }
 }
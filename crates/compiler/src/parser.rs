@@ -16,9 +16,9 @@
 
 use super::{
     error::{CompilationError, CompilationErrorKind as ErrorKind, RcErrHandle},
-    flags::RuntimeHelper,
+    flags::BuiltinComponent,
     scanner::{Attribute, AttributeValue, Tag, TextMode, Token, TokenSource},
-    util::{find_dir, is_core_component, no, non_whitespace, yes, VStr},
+    util::{find_dir_empty, is_core_component, no, non_whitespace, yes, VStr},
     Name, Namespace, SourceLocation,
 };
 use smallvec::{smallvec, SmallVec};
@@ -126,6 +126,7 @@ impl<'a> TextNode<'a> {
     }
 }
 
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum ElemProp<'a> {
     Attr(Attribute<'a>),
@@ -175,6 +176,7 @@ impl<'a> Element<'a> {
 
 /// Directive supports two forms
 /// static and dynamic
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum DirectiveArg<'a> {
     // :static="val"
@@ -184,7 +186,7 @@ pub enum DirectiveArg<'a> {
 
 /// Directive has the form
 /// v-name:arg.mod1.mod2="expr"
-#[derive(Default)]
+#[derive(Default, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Directive<'a> {
     pub name: &'a str,
@@ -212,6 +214,16 @@ impl<'a> Directive<'a> {
             .map_or(self.head_loc.clone(), |v| v.location.clone());
         Some(CompilationError::new(kind).with_location(loc))
     }
+    /// Returns the error if dynamic argument is empty, e.g. `:[]`
+    pub fn check_dynamic_arg(&self) -> Option<CompilationError> {
+        match self.argument {
+            Some(DirectiveArg::Dynamic(arg)) if arg.trim().is_empty() => {
+                let error = CompilationError::new(ErrorKind::EmptyDynamicArgument);
+                Some(error.with_location(self.head_loc.clone()))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -220,6 +232,26 @@ pub struct AstRoot<'a> {
     pub location: SourceLocation,
 }
 
+impl<'a> AstRoot<'a> {
+    /// Returns all comments in document order, including nested ones.
+    /// Comments are only kept if `ParseOption::preserve_comment` is set.
+    pub fn comments(&self) -> Vec<&SourceNode<'a>> {
+        let mut comments = vec![];
+        collect_comments(&self.children, &mut comments);
+        comments
+    }
+}
+
+fn collect_comments<'a, 'b>(nodes: &'b [AstNode<'a>], comments: &mut Vec<&'b SourceNode<'a>>) {
+    for node in nodes {
+        match node {
+            AstNode::Comment(c) => comments.push(c),
+            AstNode::Element(e) => collect_comments(&e.children, comments),
+            _ => (),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum WhitespaceStrategy {
     Preserve,
@@ -248,9 +280,11 @@ pub struct ParseOption {
     pub is_custom_element: fn(&str) -> bool,
     /// For platform developers. Registers platform specific components written in JS.
     /// e.g. transition, transition-group. Components that require code in Vue runtime.
-    pub get_builtin_component: fn(&str) -> Option<RuntimeHelper>,
+    pub get_builtin_component: fn(&str) -> Option<BuiltinComponent>,
     /// For platform developer. Registers platform components written in host language like C++.
     pub is_native_element: fn(&str) -> bool,
+    /// Vue 2 compatible parsing: static `is` on native elements marks a component.
+    pub compat: bool,
 }
 
 impl Default for ParseOption {
@@ -265,6 +299,7 @@ impl Default for ParseOption {
             is_custom_element: no,
             get_builtin_component: |_| None,
             is_native_element: yes,
+            compat: false,
         }
     }
 }
@@ -446,6 +481,7 @@ where
         if is_v_pre_boundary(elem) {
             debug_assert!(self.v_pre_index.is_none());
             self.v_pre_index = Some(self.open_elems.len());
+            self.tokens.set_is_in_v_pre(true);
         }
     }
     fn parse_end_tag(&mut self, end_tag: &'a str) {
@@ -500,7 +536,7 @@ where
         if !pre_boundary {
             return;
         }
-        if let Some(AstNode::Text(tn)) = elem.children.last_mut() {
+        if let Some(AstNode::Text(tn)) = elem.children.first_mut() {
             tn.trim_leading_newline();
         }
         self.pre_count -= 1;
@@ -511,6 +547,7 @@ where
         // met v-pre boundary, switch back
         if idx == self.open_elems.len() {
             self.v_pre_index = None;
+            self.tokens.set_is_in_v_pre(false);
         }
     }
     fn parse_element(&mut self, mut elem: Element<'a>) -> AstNode<'a> {
@@ -633,7 +670,7 @@ where
                 name: "is",
                 value: Some(v),
                 ..
-            }) => v.content.starts_with("vue:"),
+            }) => opt.compat || v.content.starts_with("vue:"),
             _ => false,
         })
     }
@@ -688,7 +725,13 @@ impl<'a, 'b> DirectiveParser<'a, 'b> {
             .expect("Parse without detection requires attribute be directive.");
         let is_prop = attr.name.starts_with('.');
         let is_v_slot = name == "slot";
-        let (arg_str, mods_str) = self.split_arg_and_mods(prefixed, is_v_slot, is_prop);
+        // bare shorthand has no argument, e.g. :="obj" @="handlers" #="props"
+        let is_bare_shorthand = prefixed == attr.name && prefixed.len() == 1 && !is_prop;
+        let (arg_str, mods_str) = if is_bare_shorthand {
+            ("", "")
+        } else {
+            self.split_arg_and_mods(prefixed, is_v_slot, is_prop)
+        };
         let argument = self.parse_directive_arg(arg_str);
         let modifiers = self.parse_directive_mods(mods_str, is_prop);
         self.cached = None; // cleanup
@@ -797,7 +840,8 @@ impl<'a, 'b> DirectiveParser<'a, 'b> {
             DirectiveArg::Dynamic(&arg[1..])
         })
     }
-    // TODO: check duplicate modifiers
+    // Modifiers keep source order and duplicates are not removed:
+    // v-on applies guards in the order they are written.
     fn parse_directive_mods(&self, mods: &'a str, is_prop: bool) -> Vec<&'a str> {
         debug_assert!(mods.is_empty() || mods.starts_with(MOD_CHAR));
         let report_missing_mod = |s: &&str| {
@@ -911,7 +955,7 @@ fn is_special_template_directive(n: &str) -> bool {
 }
 
 fn is_template_element(e: &Element) -> bool {
-    e.tag_name == "template" && find_dir(e, is_special_template_directive).is_some()
+    e.tag_name == "template" && find_dir_empty(e, is_special_template_directive).is_some()
 }
 
 fn element_matches_end_tag(e: &Element, tag: &str) -> bool {
@@ -919,7 +963,7 @@ fn element_matches_end_tag(e: &Element, tag: &str) -> bool {
 }
 
 fn is_v_pre_boundary(elem: &Element) -> bool {
-    find_dir(elem, "pre").is_some()
+    find_dir_empty(elem, "pre").is_some()
 }
 
 #[cfg(test)]
@@ -954,6 +998,219 @@ pub mod test {
         let val = decode.value.unwrap().content;
         assert_eq!(val.into_string(), "&");
     }
+    #[test]
+    fn test_directive_arg() {
+        let case = "<p :[foo]='a' :href='b' v-bind='c' @[e].stop='d'/>";
+        let mut children = base_parse(case).children;
+        let p = cast!(children.remove(0), AstNode::Element);
+        let mut dirs = p.properties.into_iter().map(|p| cast!(p, ElemProp::Dir));
+        let dynamic = dirs.next().unwrap().argument;
+        assert!(matches!(dynamic, Some(DirectiveArg::Dynamic("foo"))));
+        let stat = dirs.next().unwrap().argument;
+        assert!(matches!(stat, Some(DirectiveArg::Static("href"))));
+        assert!(dirs.next().unwrap().argument.is_none());
+        let on = dirs.next().unwrap();
+        assert!(matches!(on.argument, Some(DirectiveArg::Dynamic("e"))));
+        assert_eq!(on.modifiers, vec!["stop"]);
+    }
+    #[test]
+    fn test_directive_mods() {
+        let case = "<p @click.stop.prevent='a' @key.prevent.stop.prevent='b' :x.camel.prop='c' .y.camel='d'/>";
+        let mut children = base_parse(case).children;
+        let p = cast!(children.remove(0), AstNode::Element);
+        let mods: Vec<_> = p
+            .properties
+            .into_iter()
+            .map(|p| cast!(p, ElemProp::Dir).modifiers)
+            .collect();
+        assert_eq!(mods[0], vec!["stop", "prevent"]);
+        assert_eq!(mods[1], vec!["prevent", "stop", "prevent"]);
+        assert_eq!(mods[2], vec!["camel", "prop"]);
+        assert_eq!(mods[3], vec!["camel", "prop"]);
+    }
+    #[test]
+    fn test_shorthands() {
+        let case =
+            "<p :foo='a' @foo='b' #foo='c' #[name]='d' v-bind='e' v-on='f' :='g' @='h' #='i'/>";
+        let (ast, eh) = parse_with_handler(case);
+        let mut children = ast.children;
+        assert!(eh.errors().is_empty());
+        let p = cast!(children.remove(0), AstNode::Element);
+        let dirs: Vec<_> = p
+            .properties
+            .into_iter()
+            .map(|p| cast!(p, ElemProp::Dir))
+            .collect();
+        let names: Vec<_> = dirs.iter().map(|d| d.name).collect();
+        let expected = [
+            "bind", "on", "slot", "slot", "bind", "on", "bind", "on", "slot",
+        ];
+        assert_eq!(names, expected);
+        let args: Vec<_> = dirs
+            .iter()
+            .map(|d| match &d.argument {
+                Some(DirectiveArg::Static(s)) => s.to_string(),
+                Some(DirectiveArg::Dynamic(s)) => format!("[{}]", s),
+                None => String::new(),
+            })
+            .collect();
+        assert_eq!(args, ["foo", "foo", "foo", "[name]", "", "", "", "", ""]);
+        let exprs: Vec<_> = dirs
+            .iter()
+            .map(|d| d.expression.as_ref().unwrap().content.raw)
+            .collect();
+        assert_eq!(exprs, ["a", "b", "c", "d", "e", "f", "g", "h", "i"]);
+    }
+    #[test]
+    fn test_v_pre_interpolation() {
+        let case = "<p v-pre>{{ raw }}<b :a='b'>{{c}}</b></p>{{d}}";
+        let mut children = base_parse(case).children;
+        let d = children.pop().unwrap();
+        assert_eq!(cast!(d, AstNode::Interpolation).source, "d");
+        let mut p = cast!(children.pop().unwrap(), AstNode::Element);
+        assert!(matches!(p.properties[0], ElemProp::Dir(_)));
+        let mut b = cast!(p.children.pop().unwrap(), AstNode::Element);
+        let attr = cast!(b.properties.remove(0), ElemProp::Attr);
+        assert_eq!(attr.name, ":a");
+        let c = cast!(b.children.remove(0), AstNode::Text);
+        assert_eq!(c.text.iter().map(|t| t.raw).collect::<String>(), "{{c}}");
+        let raw = cast!(p.children.pop().unwrap(), AstNode::Text);
+        let raw: String = raw.text.iter().map(|t| t.raw).collect();
+        assert_eq!(raw, "{{ raw }}");
+    }
+    #[test]
+    fn test_pre_whitespace() {
+        let case = "<pre>\n  a  \n\n  <b>  c  </b>\n</pre>";
+        let mut children = base_parse(case).children;
+        let mut pre = cast!(children.remove(0), AstNode::Element);
+        assert_eq!(pre.children.len(), 3);
+        let last = cast!(pre.children.pop().unwrap(), AstNode::Text);
+        assert_eq!(last.text[0].into_string(), "\n");
+        let mut b = cast!(pre.children.pop().unwrap(), AstNode::Element);
+        let c = cast!(b.children.remove(0), AstNode::Text);
+        assert_eq!(c.text[0].into_string(), "  c  ");
+        let first = cast!(pre.children.pop().unwrap(), AstNode::Text);
+        assert_eq!(first.text[0].into_string(), "  a  \n\n  ");
+    }
+
+    #[test]
+    fn test_comment_location() {
+        let case = "<!--a--><div>\n  <!-- b -->\n</div>";
+        let ast = base_parse(case);
+        let comments = ast.comments();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].source, "a");
+        assert_eq!(comments[0].location.start.offset, 0);
+        assert_eq!(comments[0].location.end.offset, 8);
+        let b = comments[1];
+        assert_eq!(b.source, " b ");
+        let start = case.find("<!-- b").unwrap();
+        assert_eq!(b.location.start.offset, start);
+        assert_eq!(b.location.end.offset, start + "<!-- b -->".len());
+        assert_eq!((b.location.start.line, b.location.start.column), (2, 3));
+    }
+
+    #[test]
+    fn test_no_preserve_comment() {
+        let parser = Parser::new(ParseOption {
+            preserve_comment: false,
+            ..Default::default()
+        });
+        let eh = std::rc::Rc::new(TestErrorHandler);
+        let ast = parser.parse(base_scan("<!--a--><p><!--b--></p>"), eh);
+        assert!(ast.comments().is_empty());
+    }
+
+    #[test]
+    fn test_abrupt_comment() {
+        // scanner reports the warning so it must share the handler
+        let (ast, eh) = parse_with_handler("<p><!--></p>");
+        let comments = ast.comments();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].source, "");
+        assert!(eh.errors().is_empty());
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        let kind = &warnings[0].kind;
+        assert!(matches!(kind, ErrorKind::AbruptClosingOfEmptyComment));
+    }
+
+    #[test]
+    fn test_namespaced_attr() {
+        let case = "<svg><use xlink:href='a' xmlns:xlink='b' :href='c' :xlink:href='d'/></svg>";
+        let mut children = base_parse(case).children;
+        let mut svg = cast!(children.remove(0), AstNode::Element);
+        let use_elem = cast!(svg.children.remove(0), AstNode::Element);
+        let mut props = use_elem.properties.into_iter();
+        let attr = cast!(props.next().unwrap(), ElemProp::Attr);
+        assert_eq!(attr.name, "xlink:href");
+        let attr = cast!(props.next().unwrap(), ElemProp::Attr);
+        assert_eq!(attr.name, "xmlns:xlink");
+        let dir = cast!(props.next().unwrap(), ElemProp::Dir);
+        assert_eq!(dir.name, "bind");
+        assert!(matches!(dir.argument, Some(DirectiveArg::Static("href"))));
+        let dir = cast!(props.next().unwrap(), ElemProp::Dir);
+        assert_eq!(dir.name, "bind");
+        assert!(matches!(
+            dir.argument,
+            Some(DirectiveArg::Static("xlink:href"))
+        ));
+    }
+
+    fn parse_with_handler(s: &str) -> (AstRoot, std::rc::Rc<crate::error::VecErrorHandler>) {
+        use crate::error::VecErrorHandler;
+        use crate::scanner::{ScanOption, Scanner};
+        let eh = std::rc::Rc::new(VecErrorHandler::default());
+        let tokens = Scanner::new(ScanOption::default()).scan(s, eh.clone());
+        let parser = Parser::new(ParseOption::default());
+        let ast = parser.parse(tokens, eh.clone());
+        (ast, eh)
+    }
+
+    fn parse_with_errors(s: &str) -> (AstRoot, Vec<CompilationError>) {
+        let (ast, eh) = parse_with_handler(s);
+        let errors = std::mem::take(&mut *eh.error_mut());
+        (ast, errors)
+    }
+
+    #[test]
+    fn test_bogus_comment() {
+        let (ast, errors) = parse_with_errors("<p><?php echo 1 ?></p>");
+        let comments = ast.comments();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].source, "?php echo 1 ?");
+        assert_eq!(errors.len(), 1);
+        let kind = &errors[0].kind;
+        assert!(matches!(
+            kind,
+            ErrorKind::UnexpectedQuestionMarkInsteadOfTagName
+        ));
+        let (ast, errors) = parse_with_errors("<p><! a ></p>");
+        assert_eq!(ast.comments()[0].source, " a ");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::IncorrectlyOpenedComment
+        ));
+    }
+
+    #[test]
+    fn test_doctype() {
+        let case = "<!DOCTYPE html><p><!doctype html></p>";
+        let (ast, eh) = parse_with_handler(case);
+        assert!(eh.errors().is_empty());
+        assert_eq!(ast.children.len(), 2);
+        let comments = ast.comments();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].source, "DOCTYPE html");
+        assert_eq!(comments[1].source, "doctype html");
+        // only the inline doctype is reported
+        let warnings = eh.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind, ErrorKind::UnexpectedDoctype));
+        let start = case.rfind("<!doctype").unwrap();
+        assert_eq!(warnings[0].location.start.offset, start);
+    }
 
     pub fn base_parse(s: &str) -> AstRoot {
         let tokens = base_scan(s);
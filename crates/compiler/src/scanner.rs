@@ -13,6 +13,7 @@ use std::{iter::FusedIterator, str::Bytes};
 #[cfg(feature = "serde")]
 use serde::{Serialize};
 
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Attribute<'a> {
     pub name: Name<'a>,
@@ -29,6 +30,7 @@ impl<'a> Attribute<'a> {
     }
 }
 
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct AttributeValue<'a> {
     pub content: VStr<'a>,
@@ -99,6 +101,12 @@ pub trait FlagCDataNs {
     fn need_flag_hint(&self) -> bool;
 }
 
+/// A scanner needs to know if it is inside a v-pre element, where
+/// interpolation delimiters are plain text. Parser sets it like FlagCDataNs.
+pub trait FlagVPre {
+    fn set_is_in_v_pre(&mut self, flag: bool);
+}
+
 /// This trait produces a compiler's current position and selects a range.
 pub trait Locatable {
     /// Returns the scanner's current position in the source.
@@ -150,6 +158,8 @@ impl Scanner {
             option: self.option.clone(),
             last_start_tag_name: None,
             is_in_html_namespace: true,
+            is_in_v_pre: false,
+            has_seen_start_tag: false,
             delimiter_first_char: self.delimiter_first_char,
         }
     }
@@ -169,6 +179,10 @@ pub struct Tokens<'a> {
     last_start_tag_name: Option<&'a str>,
     // this flag is for handling CDATA in non HTML namespace.
     is_in_html_namespace: bool,
+    // interpolation is not scanned in v-pre
+    is_in_v_pre: bool,
+    // DOCTYPE is only expected before the first start tag
+    has_seen_start_tag: bool,
     delimiter_first_char: char,
 }
 
@@ -187,12 +201,13 @@ impl<'a> Tokens<'a> {
         let mut offset = 0;
         // process html entity & later
         while let Some(i) = self.source[offset..].find(&['<', d][..]) {
+            let i = offset + i;
             if i != 0 {
                 // found non empty text
                 return self.scan_text(i);
             } else if self.source.starts_with('<') {
                 return self.scan_tag_open();
-            } else if self.source.starts_with(&self.option.delimiters.0) {
+            } else if !self.is_in_v_pre && self.source.starts_with(&self.option.delimiters.0) {
                 return self.scan_interpolation();
             } else {
                 offset = i + 1;
@@ -256,6 +271,7 @@ impl<'a> Tokens<'a> {
         debug_assert!(self.source.starts_with('<'));
         self.move_by(1);
         let tag = self.scan_tag_name();
+        self.has_seen_start_tag = true;
         // https://html.spec.whatwg.org/multipage/parsing.html#parsing-elements-that-contain-only-text
         // Parsing algorithms are always invoked in response to a start tag token.
         let parsing_algorithm = self.option.get_text_mode;
@@ -299,10 +315,12 @@ impl<'a> Tokens<'a> {
                 continue;
             }
             let attr = self.scan_attribute();
-            if set.contains(attr.name) {
+            if set.contains(attr.name) && !is_dir_like(attr.name) {
                 // new attribute must be removed from the token.
                 // NB: original vue compiler does not remove it.
-                self.emit_error(ErrorKind::DuplicateAttribute);
+                let error = CompilationError::new(ErrorKind::DuplicateAttribute)
+                    .with_location(attr.location);
+                self.err_handle.on_error(error);
                 continue;
             }
             set.insert(attr.name);
@@ -408,9 +426,10 @@ impl<'a> Tokens<'a> {
             return None;
         };
         // https://html.spec.whatwg.org/multipage/parsing.html#after-attribute-value-(quoted)-state
+        // whitespace is left for scan_attributes so attr location excludes it
         if !self.is_about_to_close_tag()
             && !self.did_skip_slash_in_tag()
-            && self.skip_whitespace() == 0
+            && self.source.starts_with(non_whitespace)
         {
             self.emit_error(ErrorKind::MissingWhitespaceBetweenAttributes);
         }
@@ -489,9 +508,17 @@ impl<'a> Tokens<'a> {
     // https://html.spec.whatwg.org/multipage/parsing.html#markup-declaration-open-state
     fn scan_comment_and_like(&mut self) -> Token<'a> {
         let s = &self.source;
+        // DOCTYPE is case insensitive. It is kept as a comment and
+        // only reported if it does not precede all elements.
+        let is_doctype = s
+            .get(..9)
+            .map_or(false, |d| d.eq_ignore_ascii_case("<!DOCTYPE"));
         if s.starts_with("<!--") {
             self.scan_comment()
-        } else if s.starts_with("<!DOCTYPE") {
+        } else if is_doctype {
+            if self.has_seen_start_tag {
+                self.emit_warning(ErrorKind::UnexpectedDoctype);
+            }
             self.scan_bogus_comment()
         } else if s.starts_with("<![CDATA[") {
             if self.is_in_html_namespace {
@@ -526,9 +553,9 @@ impl<'a> Tokens<'a> {
         // NB: we take &str here since we will call move_by later
         let text = if let Some(end) = comment_end {
             debug_assert!(end >= 2, "first two chars must be <!");
-            // <!---> or <!-->
+            // <!---> or <!--> is recovered as an empty comment
             if end <= 3 {
-                self.emit_error(ErrorKind::AbruptClosingOfEmptyComment);
+                self.emit_warning(ErrorKind::AbruptClosingOfEmptyComment);
                 self.move_by(end);
                 return "";
             }
@@ -681,6 +708,13 @@ impl<'a> Tokens<'a> {
         self.err_handle.on_error(err);
     }
 
+    fn emit_warning(&self, error_kind: ErrorKind) {
+        let start = self.current_position();
+        let loc = self.get_location_from(start);
+        let err = CompilationError::new(error_kind).with_location(loc);
+        self.err_handle.on_warn(err);
+    }
+
     fn decode_text(&self, src: &'a str) -> VStr<'a> {
         *VStr::raw(src).decode(false)
     }
@@ -745,6 +779,12 @@ fn semi_valid_unquoted_attr_value(&c: &u8) -> bool {
     !c.is_ascii_whitespace() && c != b'>'
 }
 
+// repeated directives like @click are kept for converter to merge or report
+#[inline]
+fn is_dir_like(name: &str) -> bool {
+    name.starts_with("v-") || name.starts_with(&[':', '@', '#', '.'][..])
+}
+
 #[inline]
 fn is_valid_name_char(c: u8) -> bool {
     !c.is_ascii_whitespace() && c != b'/' && c != b'>'
@@ -790,6 +830,12 @@ impl<'a> FlagCDataNs for Tokens<'a> {
     }
 }
 
+impl<'a> FlagVPre for Tokens<'a> {
+    fn set_is_in_v_pre(&mut self, in_v_pre: bool) {
+        self.is_in_v_pre = in_v_pre;
+    }
+}
+
 impl<'a> Locatable for Tokens<'a> {
     fn current_position(&self) -> Position {
         self.position.clone()
@@ -807,12 +853,16 @@ impl<'a> Locatable for Tokens<'a> {
     }
 }
 
-pub trait TokenSource<'a>: FusedIterator<Item = Token<'a>> + FlagCDataNs + Locatable {}
+pub trait TokenSource<'a>:
+    FusedIterator<Item = Token<'a>> + FlagCDataNs + FlagVPre + Locatable
+{
+}
 impl<'a> TokenSource<'a> for Tokens<'a> {}
 
 #[cfg(test)]
 pub mod test {
     use super::{super::error::test::TestErrorHandler, *};
+    use crate::error::VecErrorHandler;
     use crate::cast;
     #[test]
     fn test_single_delimiter() {
@@ -853,6 +903,52 @@ pub mod test {
         scan_with_opt(s, ScanOption::default())
     }
     #[test]
+    fn test_duplicate_attribute() {
+        let mut a: Vec<_> = base_scan("<p id='a' id='b' @click='c' @click='d'/>").collect();
+        let tag = cast!(a.remove(0), Token::StartTag);
+        let names: Vec<_> = tag.attributes.iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["id", "@click", "@click"]);
+        let val = tag.attributes[0].value.as_ref().unwrap();
+        assert_eq!(val.content.raw, "a");
+    }
+    #[test]
+    fn test_duplicate_attribute_error() {
+        let case = "<p id='a' id='b' :class='c' :class='d'/>";
+        let eh = std::rc::Rc::new(VecErrorHandler::default());
+        let _: Vec<_> = Scanner::new(ScanOption::default())
+            .scan(case, eh.clone())
+            .collect();
+        let errors = eh.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::DuplicateAttribute));
+        let start = case.find("id='b'").unwrap();
+        assert_eq!(errors[0].location.start.offset, start);
+        assert_eq!(errors[0].location.end.offset, start + "id='b'".len());
+    }
+    #[test]
+    fn test_token_positions() {
+        let src = "<p>{{a}}</p>";
+        let mut tokens = base_scan(src);
+        let mut spans = vec![];
+        while tokens.next().is_some() {
+            let start = tokens.last_position().offset;
+            let end = tokens.current_position().offset;
+            spans.push(&src[start..end]);
+        }
+        assert_eq!(spans, vec!["<p>", "{{a}}", "</p>"]);
+        // offset counts chars instead of bytes
+        let src = "<p>ü{{ä}}</p>";
+        let chars: Vec<_> = src.chars().collect();
+        let mut tokens = base_scan(src);
+        let mut spans = vec![];
+        while tokens.next().is_some() {
+            let start = tokens.last_position().offset;
+            let end = tokens.current_position().offset;
+            spans.push(chars[start..end].iter().collect::<String>());
+        }
+        assert_eq!(spans, vec!["<p>", "ü", "{{ä}}", "</p>"]);
+    }
+    #[test]
     fn test_tokens_moveby_fun() {
         let mut test_moved_str = return_base_tokens("hello");
         let first = test_moved_str.move_by(2);
@@ -20,24 +20,43 @@ pub struct EntityCollector<'a> {
 
 impl<'a> CorePass<BaseInfo<'a>> for EntityCollector<'a> {
     fn exit_root(&mut self, r: &mut BaseRoot<'a>) {
-        if r.body.len() > 1 {
+        if r.is_fragment || r.body.len() > 1 {
+            self.helpers.collect(RH::OPEN_BLOCK);
+            self.helpers.collect(RH::CREATE_ELEMENT_BLOCK);
             self.helpers.collect(RH::FRAGMENT);
         }
         let scope = &mut r.top_scope;
+        // keep helpers registered by converters or earlier passes
+        self.helpers.merge(&scope.helpers);
         swap(&mut scope.helpers, &mut self.helpers);
         swap(&mut scope.components, &mut self.components);
         swap(&mut scope.directives, &mut self.directives);
+        scope.counter.components = scope.components.len();
+        scope.counter.directives = scope.directives.len();
     }
     fn exit_js_expr(&mut self, e: &mut Js) {
         match e {
             Js::Call(h, ..) | Js::Symbol(h) => {
                 self.helpers.collect(*h);
             }
+            // rewritten expression may contain helpers like unref/isRef
+            Js::Compound(v) | Js::FuncCompound { body: v, .. } => {
+                for e in v.iter() {
+                    if let Js::Call(h, ..) = e {
+                        self.helpers.collect(*h);
+                    }
+                }
+            }
             _ => {}
         }
     }
     fn exit_if(&mut self, i: &mut BaseIf) {
-        if i.branches.iter().all(|b| b.condition.is_some()) {
+        // v-if in slot falls back to undefined instead of comment
+        let in_slot = i
+            .branches
+            .iter()
+            .any(|b| matches!(&*b.child, IR::AlterableSlot(_)));
+        if !in_slot && i.branches.iter().all(|b| b.condition.is_some()) {
             self.helpers.collect(RH::CREATE_COMMENT);
         }
     }
@@ -66,16 +85,8 @@ impl<'a> CorePass<BaseInfo<'a>> for EntityCollector<'a> {
         }
         let h = get_vnode_call_helper(v);
         self.helpers.collect(h);
-        if !v.is_component {
-            return;
-        }
-        // only hoisted asset needs handling, Js::Symbol is collected in js_expr
-        // see [resolve_element_tag] in convert_element
-        if let Some(tag) = is_hoisted_asset(&v.tag) {
-            self.helpers.collect(RH::RESOLVE_COMPONENT);
-            self.components.insert(*tag);
-        }
-        // only StrLit needs handling, see [build_directive_arg] in convert_element
+        // custom directives on both elements and components need resolving
+        // see [build_directive_arg] in convert_element
         let mut hoisted_dir_names = v
             .directives
             .iter()
@@ -88,6 +99,15 @@ impl<'a> CorePass<BaseInfo<'a>> for EntityCollector<'a> {
         for dir_name in hoisted_dir_names {
             self.directives.insert(*dir_name);
         }
+        if !v.is_component {
+            return;
+        }
+        // only hoisted asset needs handling, Js::Symbol is collected in js_expr
+        // see [resolve_element_tag] in convert_element
+        if let Some(tag) = is_hoisted_asset(&v.tag) {
+            self.helpers.collect(RH::RESOLVE_COMPONENT);
+            self.components.insert(*tag);
+        }
     }
     fn exit_slot_outlet(&mut self, _: &mut BaseRenderSlot<'a>) {
         self.helpers.collect(RH::RENDER_SLOT);
@@ -165,4 +185,38 @@ mod test {
         assert!(helpers.contains(RH::RENDER_LIST));
         assert!(helpers.contains(RH::WITH_CTX));
     }
+    #[test]
+    fn test_collect_assets() {
+        let ir = transform(
+            "<div v-focus>
+                <comp v-tooltip='a'><p>{{ msg }}</p></comp>
+                <FooBar/>
+                <comp/>
+                <slot/>
+                <!--c-->
+            </div>",
+        );
+        let scope = ir.top_scope;
+        let helpers = scope.helpers;
+        for h in [
+            RH::WITH_DIRECTIVES,
+            RH::RESOLVE_COMPONENT,
+            RH::RESOLVE_DIRECTIVE,
+            RH::WITH_CTX,
+            RH::RENDER_SLOT,
+            RH::CREATE_COMMENT,
+            RH::CREATE_VNODE,
+            RH::CREATE_ELEMENT_VNODE,
+        ] {
+            assert!(helpers.contains(h), "{:?}", h.helper_str(&[]));
+        }
+        assert!(!helpers.contains(RH::RENDER_LIST));
+        let mut components: Vec<_> = scope.components.iter().map(|c| c.raw).collect();
+        components.sort_unstable();
+        assert_eq!(components, ["FooBar", "comp"]);
+        let mut directives: Vec<_> = scope.directives.iter().map(|d| d.raw).collect();
+        directives.sort_unstable();
+        assert_eq!(directives, ["focus", "tooltip"]);
+        assert_eq!(scope.temps, 0);
+    }
 }
@@ -1,31 +1,73 @@
 /// cache handlers so that it's always the same handler being passed down.
 /// this avoids unnecessary re-renders when users use inline handlers on
 /// components. NB. requires prefix_identifiers
+/// static vnodes are hoisted out of render function to skip re-creation.
 use super::{BaseInfo, BaseVNode, CorePassExt, BaseCache, Scope};
 use crate::{
-    converter::v_on::get_handler_type,
-    flags::StaticLevel,
-    ir::{JsExpr as Js, CacheKind, HandlerType},
+    converter::{v_on::get_handler_type, BaseIR, BaseRoot},
+    flags::{PatchFlag, StaticLevel},
+    ir::{JsExpr as Js, CacheKind, HandlerType, IRNode},
 };
+use std::mem;
 
 // 1. cache handler
 // 2. hoist static
-pub struct HoistStatic {
+pub struct HoistStatic<'a> {
     in_v_once: bool,
     is_component: bool,
     cache_handlers: bool,
+    hoist_static: bool,
+    hoists: Vec<BaseIR<'a>>,
+    /// index of the next hoist, continued from TopScope::counter
+    next_hoist: usize,
 }
-impl HoistStatic {
-    pub fn new(cache_handlers: bool) -> Self {
+impl<'a> HoistStatic<'a> {
+    pub fn new(cache_handlers: bool, hoist_static: bool) -> Self {
         Self {
             in_v_once: false,
             is_component: false,
             cache_handlers,
+            hoist_static,
+            hoists: vec![],
+            next_hoist: 0,
+        }
+    }
+    /// replace static children with references to hoisted nodes
+    fn hoist_children(&mut self, children: &mut [BaseIR<'a>]) {
+        if !self.hoist_static || self.in_v_once {
+            return;
+        }
+        for child in children.iter_mut() {
+            let hoistable = match child {
+                IRNode::VNodeCall(vn) => is_static_vnode(vn),
+                IRNode::StaticCall(_) => true,
+                _ => false,
+            };
+            if hoistable {
+                // hint hydration to skip the static sub tree
+                if let IRNode::VNodeCall(vn) = child {
+                    vn.patch_flag = PatchFlag::HOISTED;
+                }
+                let hoisted = IRNode::Hoisted(self.next_hoist);
+                self.next_hoist += 1;
+                self.hoists.push(mem::replace(child, hoisted));
+            }
         }
     }
 }
 
-impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic {
+impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic<'a> {
+    fn enter_root(&mut self, r: &mut BaseRoot<'a>, _: &mut Scope<'a>) {
+        self.next_hoist = r.top_scope.counter.hoists;
+    }
+    fn exit_root(&mut self, r: &mut BaseRoot<'a>, _: &mut Scope<'a>) {
+        // single root is a block and must stay in render function
+        if r.is_fragment {
+            self.hoist_children(&mut r.body);
+        }
+        r.top_scope.counter.hoists = self.next_hoist;
+        r.top_scope.hoists.append(&mut self.hoists);
+    }
     fn enter_cache(&mut self, cn: &mut BaseCache<'a>, _: &mut Scope<'a>) {
         if matches!(cn.kind, CacheKind::Once) {
             self.in_v_once = true;
@@ -39,6 +81,14 @@ impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic {
     fn enter_vnode(&mut self, vn: &mut BaseVNode<'a>, _: &mut Scope<'a>) {
         self.is_component = vn.is_component;
     }
+    // children are hoisted only if the parent itself cannot be hoisted
+    // so that the outermost static subtree becomes one hoisted node.
+    fn exit_vnode(&mut self, vn: &mut BaseVNode<'a>, _: &mut Scope<'a>) {
+        if vn.is_component || is_static_vnode(vn) {
+            return;
+        }
+        self.hoist_children(&mut vn.children);
+    }
     #[allow(clippy::nonminimal_bool)]
     fn enter_js_expr(&mut self, exp: &mut Js<'a>, scope: &mut Scope<'a>) {
         // unnecessary to cache inside v-once
@@ -71,3 +121,124 @@ impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for HoistStatic {
         *cache = should_cache;
     }
 }
+
+fn is_static_vnode(vn: &BaseVNode) -> bool {
+    let static_props = vn
+        .props
+        .as_ref()
+        .map_or(true, |p| p.static_level() >= StaticLevel::CanHoist);
+    !vn.is_component
+        && !vn.is_block
+        && vn.directives.is_empty()
+        && vn.patch_flag.is_empty()
+        && matches!(vn.tag, Js::StrLit(_))
+        && static_props
+        && vn.children.iter().all(is_static_node)
+}
+
+fn is_static_node(ir: &BaseIR) -> bool {
+    match ir {
+        IRNode::VNodeCall(vn) => is_static_vnode(vn),
+        IRNode::TextCall(t) => t
+            .texts
+            .iter()
+            .all(|t| t.static_level() >= StaticLevel::CanHoist),
+        IRNode::CommentCall(_) | IRNode::StaticCall(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{test::transformer_ext, BaseRoot, BaseTransformer, Transformer};
+    use super::*;
+    use crate::converter::{
+        test::{base_convert, handler_convert},
+        BaseIR,
+    };
+    use crate::{cast, ir::IRNode};
+
+    fn get_handler<'a, 'b>(ir: &'b mut BaseIR<'a>) -> &'b mut Js<'a> {
+        let vn = match ir {
+            IRNode::CacheNode(cn) => return get_handler(&mut cn.child),
+            IRNode::VNodeCall(vn) => vn,
+            _ => panic!("unexpected ir"),
+        };
+        let props = cast!(vn.props.as_mut().unwrap(), Js::Props);
+        &mut props[0].1
+    }
+
+    fn transform(s: &str) -> BaseRoot {
+        let mut ir = handler_convert(s);
+        // pretend the handler is a setup constant
+        if let Js::FuncSimple { lvl, .. } = get_handler(&mut ir.body[0]) {
+            *lvl = StaticLevel::CanHoist;
+        }
+        let pass = transformer_ext(HoistStatic::new(true, false));
+        BaseTransformer::transform(&mut ir, pass);
+        ir
+    }
+
+    #[test]
+    fn test_cache_handler() {
+        let mut ir = transform("<p @click='a'/>");
+        let handler = get_handler(&mut ir.body[0]);
+        assert!(matches!(handler, Js::FuncSimple { cache: true, .. }));
+    }
+
+    #[test]
+    fn test_skip_v_once() {
+        let mut ir = transform("<p v-once @click='a'/>");
+        let handler = get_handler(&mut ir.body[0]);
+        assert!(matches!(handler, Js::FuncSimple { cache: false, .. }));
+    }
+
+    fn hoist(s: &str, hoist_static: bool) -> BaseRoot {
+        let mut ir = base_convert(s);
+        let pass = transformer_ext(HoistStatic::new(false, hoist_static));
+        BaseTransformer::transform(&mut ir, pass);
+        ir
+    }
+
+    #[test]
+    fn test_hoist_static() {
+        let mut ir = hoist("<div><p class='a'>static</p><p>{{ a }}</p></div>", true);
+        assert_eq!(ir.top_scope.hoists.len(), 1);
+        let hoisted = cast!(ir.top_scope.hoists.remove(0), IRNode::VNodeCall);
+        assert_eq!(cast!(hoisted.tag, Js::StrLit).raw, "p");
+        assert_eq!(hoisted.patch_flag, PatchFlag::HOISTED);
+        let div = cast!(ir.body.remove(0), IRNode::VNodeCall);
+        assert!(matches!(div.children[0], IRNode::Hoisted(0)));
+        assert!(matches!(div.children[1], IRNode::VNodeCall(_)));
+    }
+
+    #[test]
+    fn test_hoist_outermost() {
+        let ir = hoist("<p>{{ a }}</p><div><p>a</p><p>b</p></div>", true);
+        assert_eq!(ir.top_scope.hoists.len(), 1);
+        assert_eq!(ir.top_scope.counter.hoists, 1);
+        assert!(matches!(ir.body[1], IRNode::Hoisted(0)));
+    }
+
+    #[test]
+    fn test_no_hoist_static() {
+        let ir = hoist("<div><p class='a'>static</p><p>{{ a }}</p></div>", false);
+        assert!(ir.top_scope.hoists.is_empty());
+        let ir = hoist("<p>{{ a }}</p><div><p>a</p><p>b</p></div>", false);
+        assert!(ir.top_scope.hoists.is_empty());
+    }
+
+    #[test]
+    fn test_no_hoist_dynamic() {
+        let cases = [
+            "<div><p :id='a'/></div>",
+            "<div><comp/></div>",
+            "<div><p v-focus/></div>",
+            "<div><p ref='a'/></div>",
+        ];
+        for case in cases {
+            let ir = hoist(case, true);
+            assert!(ir.top_scope.hoists.is_empty(), "{}", case);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // mark patch flag and is_block for runtime
 // it should happen after process_expression
 use super::{BaseFor, BaseIf, BaseInfo, BaseText, BaseVNode, CorePass};
-use crate::converter::BaseIR;
+use crate::converter::{BaseIR, BaseRoot};
 use crate::flags::{PatchFlag, RuntimeHelper as RH, StaticLevel};
 use crate::ir::{IRNode as IR, JsExpr as Js, Prop};
 use crate::util::is_builtin_symbol;
@@ -9,6 +9,16 @@ use crate::util::is_builtin_symbol;
 pub struct PatchFlagMarker;
 
 impl<'a> CorePass<BaseInfo<'a>> for PatchFlagMarker {
+    fn enter_root(&mut self, r: &mut BaseRoot<'a>) {
+        // single root element is a block. multiple roots are
+        // wrapped in a stable fragment block in codegen
+        if r.is_fragment {
+            return;
+        }
+        if let [IR::VNodeCall(vn)] = &mut r.body[..] {
+            vn.is_block = true;
+        }
+    }
     fn enter_if(&mut self, i: &mut BaseIf<'a>) {
         for branch in i.branches.iter_mut() {
             // TODO: handle v-memo/v-once
@@ -53,17 +63,25 @@ impl<'a> CorePass<BaseInfo<'a>> for PatchFlagMarker {
             vn.patch_flag |= PatchFlag::TEXT;
         }
     }
+    // source is transformed before enter_for so its static level is known
+    fn enter_for(&mut self, f: &mut BaseFor<'a>) {
+        f.is_stable = f.source.static_level() > StaticLevel::NotStatic;
+        // each item of an unstable list is a block, template fragment always is
+        if let IR::VNodeCall(vn) = &mut *f.child {
+            if !matches!(vn.tag, Js::Symbol(RH::FRAGMENT)) {
+                vn.is_block = !f.is_stable;
+            }
+        }
+    }
     fn exit_for(&mut self, f: &mut BaseFor<'a>) {
-        let is_stable_fragment = f.source.static_level() > StaticLevel::NotStatic;
         let has_key = find_key(&f.child);
-        f.fragment_flag = if is_stable_fragment {
+        f.fragment_flag = if f.is_stable {
             PatchFlag::STABLE_FRAGMENT
         } else if has_key {
             PatchFlag::KEYED_FRAGMENT
         } else {
             PatchFlag::UNKEYED_FRAGMENT
         };
-        f.is_stable = is_stable_fragment;
     }
 
     fn exit_text(&mut self, t: &mut BaseText<'a>) {
@@ -88,7 +106,12 @@ fn find_prop<'a, 'b>(t: &'b BaseIR<'a>) -> Option<&'b Js<'a>> {
         IR::VSlotUse(_) => {
             panic!("v-slot with v-for must be alterable slots")
         }
-        IR::TextCall(_) | IR::For(_) | IR::If(_) | IR::CommentCall(_) => {
+        IR::TextCall(_)
+        | IR::For(_)
+        | IR::If(_)
+        | IR::CommentCall(_)
+        | IR::StaticCall(_)
+        | IR::Hoisted(_) => {
             panic!("v-for child must be vnode/renderSlot/slotfn")
         }
     }
@@ -140,3 +163,119 @@ fn inject_prop<'a>(props: &mut Js<'a>, key: Prop<'a>) {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::{
+        optimize_text::TextOptimizer, process_expression::ExpressionProcessor,
+        test::transformer_ext, BaseTransformer, Transformer,
+    };
+    use super::*;
+    use crate::{cast, chain, converter::test::handler_convert, error::NoopErrorHandler};
+    use std::rc::Rc;
+
+    fn get_vnode(s: &str) -> BaseVNode {
+        let mut ir = handler_convert(s);
+        BaseTransformer::transform(&mut ir, chain![TextOptimizer, PatchFlagMarker]);
+        cast!(ir.body.remove(0), IR::VNodeCall)
+    }
+
+    fn transform(s: &str) -> BaseRoot {
+        let mut ir = handler_convert(s);
+        let exp = ExpressionProcessor {
+            prefix_identifier: true,
+            sfc_info: &Default::default(),
+            err_handle: Rc::new(NoopErrorHandler),
+        };
+        let pass = chain![TextOptimizer, PatchFlagMarker, transformer_ext(exp)];
+        BaseTransformer::transform(&mut ir, pass);
+        ir
+    }
+
+    fn get_flag(s: &str) -> i32 {
+        get_vnode(s).patch_flag.bits()
+    }
+
+    #[test]
+    fn test_patch_flag_values() {
+        assert_eq!(get_flag("<p/>"), 0);
+        assert_eq!(get_flag("<p id='a'>text</p>"), 0);
+        assert_eq!(get_flag("<p>{{a}}</p>"), 1);
+        assert_eq!(get_flag("<p :class='a'/>"), 2);
+        assert_eq!(get_flag("<p :style='a'/>"), 4);
+        assert_eq!(get_flag("<p :id='a'/>"), 8);
+        assert_eq!(get_flag("<p :id='a'>{{b}}</p>"), 9);
+        assert_eq!(get_flag("<p :class='a' :style='b' :id='c'/>"), 14);
+        assert_eq!(get_flag("<p v-bind='o'/>"), 16);
+        assert_eq!(get_flag("<p :[k]='v'/>"), 16);
+        assert_eq!(get_flag("<p @input='h'/>"), 40);
+        assert_eq!(get_flag("<p @click='h'/>"), 8);
+        assert_eq!(get_flag("<p ref='r'/>"), 512);
+        assert_eq!(get_flag("<comp :class='a'/>"), 8);
+        assert_eq!(get_flag("<comp @input='h'/>"), 8);
+    }
+
+    #[test]
+    fn test_dynamic_props() {
+        let vn = get_vnode("<p :id='a' :title='b' :class='c' :key='d'/>");
+        assert_eq!(vn.patch_flag, PatchFlag::CLASS | PatchFlag::PROPS);
+        let names: Vec<_> = vn.dynamic_props.iter().map(|v| v.raw).collect();
+        assert_eq!(names, vec!["id", "title"]);
+        let vn = get_vnode("<comp :class='a'/>");
+        let names: Vec<_> = vn.dynamic_props.iter().map(|v| v.raw).collect();
+        assert_eq!(names, vec!["class"]);
+    }
+
+    #[test]
+    fn test_block_placement() {
+        let case = "<div>\
+            <p v-if='a'/><span v-else/>\
+            <li v-for='i in list' :key='i'/>\
+            <li v-for='i in 3'/>\
+            <template v-for='i in list'><b/><i/></template>\
+        </div>";
+        let mut ir = transform(case);
+        let root = cast!(ir.body.remove(0), IR::VNodeCall);
+        assert!(root.is_block);
+        let mut children = root.children.into_iter();
+        let i = cast!(children.next().unwrap(), IR::If);
+        for branch in i.branches {
+            assert!(cast!(*branch.child, IR::VNodeCall).is_block);
+        }
+        let keyed = cast!(children.next().unwrap(), IR::For);
+        assert!(!keyed.is_stable);
+        assert_eq!(keyed.fragment_flag, PatchFlag::KEYED_FRAGMENT);
+        assert!(cast!(*keyed.child, IR::VNodeCall).is_block);
+        let stable = cast!(children.next().unwrap(), IR::For);
+        assert!(stable.is_stable);
+        assert_eq!(stable.fragment_flag, PatchFlag::STABLE_FRAGMENT);
+        assert!(!cast!(*stable.child, IR::VNodeCall).is_block);
+        let template = cast!(children.next().unwrap(), IR::For);
+        assert_eq!(template.fragment_flag, PatchFlag::UNKEYED_FRAGMENT);
+        let fragment = cast!(*template.child, IR::VNodeCall);
+        assert!(fragment.is_block);
+        assert!(fragment
+            .children
+            .iter()
+            .all(|c| !cast!(c, IR::VNodeCall).is_block));
+    }
+
+    #[test]
+    fn test_for_fragment_flag() {
+        let mut ir = transform("<li v-for='i in list'/>");
+        let unkeyed = cast!(ir.body.remove(0), IR::For);
+        assert_eq!(unkeyed.fragment_flag, PatchFlag::UNKEYED_FRAGMENT);
+        let mut ir = transform("<template v-for='i in list' :key='i'><b/><i/></template>");
+        let keyed = cast!(ir.body.remove(0), IR::For);
+        assert_eq!(keyed.fragment_flag, PatchFlag::KEYED_FRAGMENT);
+        let fragment = cast!(*keyed.child, IR::VNodeCall);
+        assert!(fragment.patch_flag.contains(PatchFlag::STABLE_FRAGMENT));
+        assert!(fragment.props.is_some());
+    }
+
+    #[test]
+    fn test_multi_root_not_block() {
+        let ir = transform("<p/><p/>");
+        assert!(ir.body.iter().all(|c| !cast!(c, IR::VNodeCall).is_block));
+    }
+}
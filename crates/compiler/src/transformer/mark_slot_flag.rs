@@ -9,12 +9,15 @@ impl<'a> CorePassExt<BaseInfo<'a>, Scope<'a>> for SlotFlagMarker {
         if !v.is_component || v.children.is_empty() {
             return;
         }
-        debug_assert_eq!(v.children.len(), 1);
+        // Teleport and KeepAlive receive raw children instead of slots
+        if !matches!(&v.children[..], [IRNode::VSlotUse(_)]) {
+            return;
+        }
         let has_dynamic_slots = scope.has_ref_in_vnode(v);
         // has dynamic stable slot key
         let v_slot = match &mut v.children[0] {
             IRNode::VSlotUse(v_slot) => v_slot,
-            _ => panic!("impossible"),
+            _ => unreachable!(),
         };
         let has_dynamic_slots = has_dynamic_slots
             || !v_slot.alterable_slots.is_empty()
@@ -52,10 +55,13 @@ fn has_forward_one(ir: &BaseIR) -> bool {
         IR::For(f) => has_forward_one(&f.child),
         IR::VNodeCall(vn) => has_forward_list(&vn.children),
         IR::CacheNode(c) => has_forward_one(&c.child),
-        IR::VSlotUse(s) => has_forwarded_slots(s),
+        // nested component slots are marked by their own component
+        IR::VSlotUse(_) => false,
         IR::AlterableSlot(s) => has_forward_list(&s.body),
         IR::TextCall(_) => false,
         IR::CommentCall(_) => false,
+        IR::StaticCall(_) => false,
+        IR::Hoisted(_) => false,
     }
 }
 
@@ -113,4 +119,43 @@ mod test {
         assert_eq!(slot.stable_slots.len(), 1);
         assert!(matches!(slot.slot_flag, SlotFlag::Dynamic));
     }
+
+    fn get_flag(s: &str) -> SlotFlag {
+        let mut ir = transform(base_convert(s));
+        let vn = cast!(ir.body.remove(0), IRNode::VNodeCall);
+        let has_dynamic = vn.patch_flag.contains(PatchFlag::DYNAMIC_SLOTS);
+        let slot = get_slot(IRNode::VNodeCall(vn));
+        assert_eq!(has_dynamic, matches!(slot.slot_flag, SlotFlag::Dynamic));
+        slot.slot_flag
+    }
+
+    #[test]
+    fn test_stable_slot() {
+        let flag = get_flag("<comp><template #header>hello</template></comp>");
+        assert!(matches!(flag, SlotFlag::Stable));
+        let flag = get_flag("<comp>default</comp>");
+        assert!(matches!(flag, SlotFlag::Stable));
+    }
+
+    #[test]
+    fn test_conditional_slot() {
+        let flag = get_flag("<comp><template #header v-if='ok'>hello</template></comp>");
+        assert!(matches!(flag, SlotFlag::Dynamic));
+        let flag = get_flag("<comp><template #[name]>hello</template></comp>");
+        assert!(matches!(flag, SlotFlag::Dynamic));
+    }
+
+    #[test]
+    fn test_forwarded_slot() {
+        let flag = get_flag("<comp><template #header><slot/></template></comp>");
+        assert!(matches!(flag, SlotFlag::Forwarded));
+        let flag = get_flag("<comp><p v-if='ok'><slot name='a'/></p></comp>");
+        assert!(matches!(flag, SlotFlag::Forwarded));
+        // outlets in nested component slots do not forward outer slots
+        let flag = get_flag("<comp><comp><slot/></comp></comp>");
+        assert!(matches!(flag, SlotFlag::Stable));
+        // dynamic slots take precedence over forwarded ones
+        let flag = get_flag("<comp><template #header v-if='ok'><slot/></template></comp>");
+        assert!(matches!(flag, SlotFlag::Dynamic));
+    }
 }
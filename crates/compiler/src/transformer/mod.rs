@@ -19,16 +19,22 @@ Vue track it by helper/helperString.
 2. wrap text in createTextVNode
 * patch_flag:
 seems patch flag can be extracted out
+* visitor:
+walk IR with a visitor that can replace or remove nodes.
+* node_transform:
+run user transforms registered in CompileOption over every node.
  */
 
 pub mod collect_entities;
 pub mod hoist_static;
 pub mod mark_patch_flag;
 pub mod mark_slot_flag;
+pub mod node_transform;
 pub mod normalize_props;
 pub mod optimize_text;
 pub mod pass;
 pub mod process_expression;
+pub mod visitor;
 
 use std::marker::PhantomData;
 
@@ -76,6 +82,8 @@ trait CoreTransformer<T: ConvertInfo, P: CorePass<T>>: Transformer<P> {
             I::VSlotUse(s) => Self::transform_v_slot(s, ps),
             I::CacheNode(c) => Self::transform_cache(c, ps),
             I::AlterableSlot(a) => Self::transform_slot_fn(a, ps),
+            // stringified html and hoisted node have nothing left to transform
+            I::StaticCall(_) | I::Hoisted(_) => (),
         }
     }
     fn transform_children(children: &mut Vec<IRNode<T>>, ps: &mut P) {
@@ -264,6 +272,7 @@ where
             | Js::Param(_)
             | Js::FuncSimple { .. }
             | Js::StrLit(_)
+            | Js::Joined { .. }
             | Js::Symbol(_) => {
                 // no further recursion.
             }
@@ -277,7 +286,6 @@ mod test {
     use super::pass::{Scope, SharedInfoPasses};
     use super::*;
     pub use crate::converter::test::base_convert;
-    use rustc_hash::FxHashMap;
     use std::marker::PhantomData;
 
     pub fn transformer_ext<'a, Ps: CorePassExt<BaseInfo<'a>, Scope<'a>>>(
@@ -285,9 +293,7 @@ mod test {
     ) -> SharedInfoPasses<BaseInfo<'a>, Ps, Scope<'a>> {
         SharedInfoPasses {
             passes,
-            shared_info: Scope {
-                identifiers: FxHashMap::default(),
-            },
+            shared_info: Scope::default(),
             pd: PhantomData,
         }
     }
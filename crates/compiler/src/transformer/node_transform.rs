@@ -0,0 +1,87 @@
+//! User node transforms registered in `CompileOption::node_transforms`.
+//! They run before the built-in passes so that patch flags, hoisting
+//! and entity collection all see the transformed IR.
+use super::visitor::{walk, IRVisitor};
+use super::BaseInfo;
+use crate::converter::{BaseIR, BaseRoot, TopScope};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TransformPhase {
+    Enter,
+    Exit,
+}
+
+/// Transforms every IR node in the tree. Like passes in a chain, transforms
+/// are called on enter in registration order and on exit in reverse order.
+/// The top scope can be used to register helpers, components or imports.
+pub type NodeTransform = for<'a> fn(&mut BaseIR<'a>, &mut TopScope<'a>, TransformPhase);
+
+struct NodeTransformer<'s, 'a> {
+    transforms: &'s [NodeTransform],
+    top_scope: TopScope<'a>,
+}
+
+impl<'s, 'a> IRVisitor<BaseInfo<'a>> for NodeTransformer<'s, 'a> {
+    fn enter_node(&mut self, n: &mut BaseIR<'a>) {
+        for transform in self.transforms {
+            transform(n, &mut self.top_scope, TransformPhase::Enter);
+        }
+    }
+    fn exit_node(&mut self, n: &mut BaseIR<'a>) {
+        for transform in self.transforms.iter().rev() {
+            transform(n, &mut self.top_scope, TransformPhase::Exit);
+        }
+    }
+}
+
+pub fn apply_node_transforms(root: &mut BaseRoot, transforms: &[NodeTransform]) {
+    if transforms.is_empty() {
+        return;
+    }
+    let mut transformer = NodeTransformer {
+        transforms,
+        top_scope: std::mem::take(&mut root.top_scope),
+    };
+    walk(root, &mut transformer);
+    root.top_scope = transformer.top_scope;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compiler::{get_base_passes, BaseCompiler, CompileOption, TemplateCompiler};
+    use crate::ir::{IRNode, JsExpr as Js};
+    use crate::util::VStr;
+
+    fn rename_prop<'a>(n: &mut BaseIR<'a>, _: &mut TopScope<'a>, phase: TransformPhase) {
+        if phase != TransformPhase::Enter {
+            return;
+        }
+        let props = match n {
+            IRNode::VNodeCall(vn) => vn.props.as_mut(),
+            _ => return,
+        };
+        if let Some(Js::Props(ps)) = props {
+            for (key, _) in ps.iter_mut() {
+                if matches!(key, Js::StrLit(k) if k.raw == "data-test") {
+                    *key = Js::StrLit(VStr::raw("data-id"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rename_prop() {
+        let option = CompileOption {
+            node_transforms: vec![rename_prop],
+            ..Default::default()
+        };
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_base_passes, option);
+        let template = "<div data-test='a'><p v-if='b' data-test='c'/></div>";
+        let out = compiler.compile(template, &sfc_info).unwrap();
+        let code = String::from_utf8(out).unwrap();
+        assert_eq!(code.matches("data-id").count(), 2, "{}", code);
+        assert!(!code.contains("data-test"), "{}", code);
+    }
+}
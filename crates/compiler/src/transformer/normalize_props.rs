@@ -1,4 +1,4 @@
-/// extract class/style for faster runtime patching
+/// normalize props with dynamic keys or from v-bind object
 use crate::ir::JsExpr as Js;
 use crate::flags::RuntimeHelper as RH;
 use super::{BaseInfo, BaseVNode, CorePass};
@@ -34,36 +34,14 @@ fn is_handler_expr(j: &Js) -> bool {
     matches!(j, Js::FuncSimple { .. }) || matches!(j, Js::FuncCompound { .. })
 }
 
-fn pre_normalize_prop<'a>(mut props: Vec<(Js<'a>, Js<'a>)>) -> Js<'a> {
-    let mut class_val = None;
-    let mut style_val = None;
-    let mut has_dynamic_key = false;
-    for (key, val) in props.iter_mut() {
-        if let Js::StrLit(k) = key {
-            if k.raw == "class" {
-                class_val = Some(val);
-            } else if k.raw == "style" {
-                style_val = Some(val);
-            }
-        } else if !is_handler_expr(val) {
-            has_dynamic_key = true;
-        }
-    }
+// class/style in props object are already normalized in build_props
+fn pre_normalize_prop<'a>(props: Vec<(Js<'a>, Js<'a>)>) -> Js<'a> {
+    let has_dynamic_key = props
+        .iter()
+        .any(|(key, val)| !matches!(key, Js::StrLit(_)) && !is_handler_expr(val));
     if has_dynamic_key {
-        return Js::Call(RH::NORMALIZE_PROPS, vec![Js::Props(props)]);
-    }
-    if let Some(cls) = class_val {
-        if !matches!(cls, Js::StrLit(..)) {
-            let val = mem::take(cls);
-            *cls = Js::Call(RH::NORMALIZE_CLASS, vec![val]);
-        }
-    }
-    if let Some(stl) = style_val {
-        // Props is parsed from literal style string
-        if !matches!(stl, Js::Props(..)) {
-            let val = mem::take(stl);
-            *stl = Js::Call(RH::NORMALIZE_STYLE, vec![val]);
-        }
+        Js::Call(RH::NORMALIZE_PROPS, vec![Js::Props(props)])
+    } else {
+        Js::Props(props)
     }
-    Js::Props(props)
 }
@@ -109,6 +109,41 @@ mod test {
         BaseTransformer::transform(&mut ir, TextOptimizer);
         assert_eq!(ir.body.len(), 6);
     }
+    #[test]
+    fn test_merge_text_interp_text() {
+        let mut ir = base_convert("hello {{ name }} world");
+        assert_eq!(ir.body.len(), 3);
+        BaseTransformer::transform(&mut ir, TextOptimizer);
+        assert_eq!(ir.body.len(), 1);
+        let texts = must_text(&mut ir.body[0]);
+        assert_eq!(texts.len(), 3);
+        assert!(matches!(texts[0], Js::StrLit(_)));
+        assert!(matches!(texts[1], Js::Call(..)));
+        assert!(matches!(texts[2], Js::StrLit(_)));
+    }
+
+    #[test]
+    fn test_merge_interp_interp() {
+        let mut ir = base_convert("<p>{{a}}{{b}}</p>");
+        BaseTransformer::transform(&mut ir, TextOptimizer);
+        let p = cast!(&mut ir.body[0], IR::VNodeCall);
+        assert_eq!(p.children.len(), 1);
+        assert_eq!(must_text(&mut p.children[0]).len(), 2);
+        assert!(must_ir(&p.children[0]).fast_path);
+    }
+
+    #[test]
+    fn test_merge_split_by_element() {
+        let mut ir = base_convert("<div>a{{b}}<p/>{{c}}d</div>");
+        BaseTransformer::transform(&mut ir, TextOptimizer);
+        let div = cast!(&mut ir.body[0], IR::VNodeCall);
+        assert_eq!(div.children.len(), 3);
+        assert_eq!(must_text(&mut div.children[0]).len(), 2);
+        assert!(matches!(div.children[1], IR::VNodeCall(_)));
+        assert_eq!(must_text(&mut div.children[2]).len(), 2);
+        assert!(!must_ir(&div.children[2]).fast_path);
+    }
+
     #[test]
     fn test_merge_text_with_slot() {
         let mut ir = base_convert("<slot>hello {{world}}</slot>");
@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 
 use super::{BaseInfo, BaseTransformer, BaseVNode, ConvertInfo, CoreTransformer, Js, C};
+use crate::converter::ModelTarget;
 use crate::Name;
 use rustc_hash::FxHashMap;
 
@@ -125,6 +126,8 @@ type Identifiers<'a> = FxHashMap<Name<'a>, usize>;
 #[derive(Default)]
 pub struct Scope<'a> {
     pub identifiers: Identifiers<'a>,
+    /// v-model targets taken from TopScope, checked when their element is entered
+    pub model_targets: Vec<ModelTarget<'a>>,
 }
 
 /// Check if an IR contains expressions that reference current context scope ids
@@ -141,7 +144,12 @@ impl<'a> Scope<'a> {
         *self.identifiers.entry(id).or_default() += 1;
     }
     pub fn remove_identifier(&mut self, id: Name<'a>) {
-        *self.identifiers.entry(id).or_default() -= 1;
+        let count = self.identifiers.entry(id).or_default();
+        *count -= 1;
+        // identifier out of scope should be prefixed again
+        if *count == 0 {
+            self.identifiers.remove(id);
+        }
     }
     pub fn has_ref_in_vnode(&self, node: &mut BaseVNode<'a>) -> bool {
         if self.identifiers.is_empty() {
@@ -2,13 +2,15 @@
 // currently only v-for and v-slot
 // 2. prefix expression
 use super::collect_entities::is_hoisted_asset;
-use super::{BaseInfo, CorePassExt, Scope};
+use super::{BaseInfo, BaseRoot, BaseVNode, CorePassExt, Scope};
+use crate::converter::ModelTarget;
 use crate::converter::v_on::get_handler_type;
 use crate::error::{CompilationError, CompilationErrorKind as ErrorKind, RcErrHandle};
 use crate::flags::{RuntimeHelper as RH, StaticLevel};
-use crate::ir::JsExpr as Js;
-use crate::util::{is_global_allow_listed, is_simple_identifier, rslint, VStr};
+use crate::ir::{flatten_compound, HandlerType, JsExpr as Js};
+use crate::util::{is_global_allow_listed, is_literal_constant, is_simple_identifier, rslint, VStr};
 use crate::{cast, BindingTypes, SFCInfo, SourceLocation};
+use std::mem;
 
 pub struct ExpressionProcessor<'a, 'b> {
     pub prefix_identifier: bool,
@@ -17,6 +19,21 @@ pub struct ExpressionProcessor<'a, 'b> {
 }
 
 impl<'a, 'b> CorePassExt<BaseInfo<'a>, Scope<'a>> for ExpressionProcessor<'a, 'b> {
+    fn enter_root(&mut self, r: &mut BaseRoot<'a>, shared: &mut Scope<'a>) {
+        shared.model_targets = mem::take(&mut r.top_scope.model_targets);
+    }
+    // props are processed before children, so the scope is the one props see
+    fn enter_vnode(&mut self, v: &mut BaseVNode<'a>, shared: &mut Scope<'a>) {
+        if !self.prefix_identifier {
+            return;
+        }
+        let element = v.loc.start.offset;
+        for target in shared.model_targets.iter() {
+            if target.element == element {
+                self.check_model_target(target, shared);
+            }
+        }
+    }
     fn enter_fn_param(&mut self, p: &mut Js<'a>, shared: &mut Scope<'a>) {
         self.process_fn_param(p);
         match p {
@@ -44,6 +61,13 @@ impl<'a, 'b> CorePassExt<BaseInfo<'a>, Scope<'a>> for ExpressionProcessor<'a, 'b
     // e.g. compound/array/call expression
     fn exit_js_expr(&mut self, e: &mut Js<'a>, shared: &mut Scope<'a>) {
         self.process_expression(e, shared);
+        // rewritten identifiers nest compounds, flatten them for codegen
+        match e {
+            Js::Compound(v) | Js::FuncCompound { body: v, .. } => {
+                *v = flatten_compound(mem::take(v));
+            }
+            _ => (),
+        }
     }
 }
 
@@ -85,9 +109,17 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         if is_hoisted_asset(e).is_some() {
             return;
         }
+        // neither does module level import
+        if matches!(e, Js::Simple(s, _) if VStr::is_imported(s)) {
+            return;
+        }
         use crate::ir::HandlerType::InlineStmt;
         // complex expr will be handled recursively in transformer
         let (exp, mut mock_js) = match e {
+            Js::FuncSimple { src, .. } if VStr::is_event_assign(src) => {
+                *e = self.process_event_assign(*src, scope);
+                return;
+            }
             Js::FuncSimple { src, lvl, .. } => (*src, Js::Simple(*src, *lvl)),
             Js::Simple(..) => return self.process_simple_expr(e, scope),
             _ => return,
@@ -115,6 +147,46 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         }
     }
 
+    /// scope variables and props cannot be assigned by v-model.
+    fn check_model_target(&self, target: &ModelTarget<'a>, scope: &Scope) {
+        let raw = target.expr.raw;
+        if !is_simple_identifier(VStr::raw(raw)) {
+            return;
+        }
+        let bindings = &self.sfc_info.binding_metadata;
+        let kind = if scope.has_identifier(raw) {
+            ErrorKind::VModelOnScopeVariable
+        } else if bindings.get(raw) == Some(&BindingTypes::Props) {
+            ErrorKind::VModelOnProps
+        } else {
+            return;
+        };
+        let error = CompilationError::new(kind).with_location(target.loc.clone());
+        self.err_handle.on_error(error);
+    }
+
+    /// v-model assigns `$event` to its value so the target must be writable.
+    /// e.g. ref bindings in inline mode are assigned via `.value`
+    fn process_event_assign(&self, src: VStr<'a>, scope: &Scope) -> Js<'a> {
+        let raw = src.raw;
+        let bindings = &self.sfc_info.binding_metadata;
+        let is_simple = is_simple_identifier(VStr::raw(raw));
+        let is_binding = is_simple && !scope.has_identifier(raw);
+        let body = match bindings.get(raw) {
+            Some(bind) if self.sfc_info.inline && is_binding => rewrite_inline_assign(raw, bind),
+            _ => {
+                let mut target = Js::simple(raw);
+                self.process_simple_expr(&mut target, scope);
+                vec![Js::Src("("), target, Js::Src(") = $event")]
+            }
+        };
+        Js::FuncCompound {
+            body,
+            ty: HandlerType::InlineStmt,
+            cache: false,
+        }
+    }
+
     fn process_simple_expr(&self, e: &mut Js<'a>, scope: &Scope) {
         if self.process_expr_fast(e, scope) {
             return;
@@ -172,7 +244,8 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         if broken_atoms.is_empty() {
             // if expr has no template var nor prefixed var, it can be hoisted as static
             // NOTE: func call and member access must be bailed for potential side-effect
-            let side_effect = raw.contains('(') || raw.contains('.');
+            // but dots in number or string literals are fine.
+            let side_effect = !is_literal_constant(raw) && (raw.contains('(') || raw.contains('.'));
             *level = if !local_ref && !side_effect {
                 StaticLevel::CanStringify
             } else {
@@ -215,7 +288,6 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
         scope: &Scope,
     ) -> Option<(FreeVarAtoms<'a>, bool)> {
         let expr = rslint::parse_js_expr(raw)?;
-        let inline = self.sfc_info.inline;
         let mut atoms = vec![];
         let mut has_local_ref = false;
         rslint::walk_free_variables(expr, |fv| {
@@ -230,7 +302,8 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
                 has_local_ref = true;
                 return;
             }
-            let ctx_type = if inline { todo!() } else { CtxType::NoWrite };
+            // TODO: detect assignment and update in inline statement
+            let ctx_type = CtxType::NoWrite;
             atoms.push(Atom {
                 range,
                 property: FreeVarProp {
@@ -262,13 +335,15 @@ impl<'a, 'b> ExpressionProcessor<'a, 'b> {
 
 // This implementation assumes that broken param expression has only two kinds subexpr:
 // 1. param identifiers represented by Js::Param
-// 2. expression in default binding that has been prefixed
+// 2. expression in default binding that has been prefixed and flattened
 fn only_param_ids<'a, 'b>(ids: &'b [Js<'a>]) -> impl Iterator<Item = &'a str> + 'b {
     ids.iter().filter_map(|id| match id {
         Js::Param(p) => Some(*p),
-        Js::Src(_) => None,
+        Js::Src(_) | Js::Joined { .. } => None,
         Js::Simple(..) => None,
-        Js::Compound(..) => None, // object shorthand
+        Js::StrLit(_) => None, // object shorthand key
+        Js::Call(RH::UNREF, _) => None,
+        Js::Compound(..) => None,
         _ => panic!("Illegal sub expr kind in param."),
     })
 }
@@ -333,9 +408,7 @@ fn rewrite_inline_identifier<'a>(
     debug_assert!(level == StaticLevel::NotStatic || bind == &BT::SetupConst);
     let expr = move || Js::Simple(raw, level);
     let dot_value = Js::Compound(vec![expr(), Js::Src(".value")]);
-    if VStr::is_event_assign(&raw) {
-        todo!("handle event assign differently")
-    }
+    debug_assert!(!VStr::is_event_assign(&raw));
     match bind {
         BT::SetupConst => expr(),
         BT::SetupRef => dot_value,
@@ -356,6 +429,27 @@ fn rewrite_inline_identifier<'a>(
     }
 }
 
+fn rewrite_inline_assign<'a>(raw: &'a str, bind: &BindingTypes) -> Vec<Js<'a>> {
+    use BindingTypes as BT;
+    let expr = || Js::simple(raw);
+    match bind {
+        BT::SetupConst => vec![expr(), Js::Src(" = $event")],
+        BT::SetupRef => vec![Js::Src("("), expr(), Js::Src(").value = $event")],
+        // the binding may not be a ref so the assignment must check it.
+        // non-ref bindings fall back to setting the setup local directly.
+        BT::SetupMaybeRef | BT::SetupLet => vec![
+            Js::Call(RH::IS_REF, vec![expr()]),
+            Js::Src(" ? ("),
+            expr(),
+            Js::Src(").value = $event : "),
+            expr(),
+            Js::Src(" = $event"),
+        ],
+        BT::Props => vec![Js::Src("__props."), expr(), Js::Src(" = $event")],
+        BT::Data | BT::Options => vec![Js::Src("_ctx."), expr(), Js::Src(" = $event")],
+    }
+}
+
 fn rewrite_setup_let<'a, E>(ctx: CtxType<'a>, expr: E, dot_value: Js<'a>) -> Js<'a>
 where
     E: Fn() -> Js<'a>,
@@ -405,7 +499,10 @@ mod test {
     use crate::cast;
     use crate::converter::BaseIR;
     use crate::error::{NoopErrorHandler, RcErrHandle, VecErrorHandler};
-    use crate::ir::IRNode;
+    use crate::converter::test::handler_convert;
+    use crate::ir::{IRNode, Slot};
+    use crate::BindingMetadata;
+    use rustc_hash::FxHashMap;
     use std::rc::Rc;
 
     fn transform_with_err(s: &str, handler: RcErrHandle) -> BaseRoot {
@@ -420,6 +517,27 @@ mod test {
         ir
     }
 
+    fn transform_inline<'a>(s: &'a str, sfc_info: &SFCInfo<'a>) -> BaseRoot<'a> {
+        let mut ir = handler_convert(s);
+        let exp = ExpressionProcessor {
+            prefix_identifier: true,
+            sfc_info,
+            err_handle: Rc::new(NoopErrorHandler),
+        };
+        let pass = transformer_ext(exp);
+        BaseTransformer::transform(&mut ir, pass);
+        ir
+    }
+    fn inline_info(name: &str, ty: BindingTypes) -> SFCInfo {
+        let mut map = FxHashMap::default();
+        map.insert(name, ty);
+        SFCInfo {
+            inline: true,
+            binding_metadata: BindingMetadata::new(map, true),
+            ..Default::default()
+        }
+    }
+
     fn transform(s: &str) -> BaseRoot {
         transform_with_err(s, Rc::new(NoopErrorHandler))
     }
@@ -439,6 +557,22 @@ mod test {
         assert_eq!(expr.into_string(), "_ctx.test");
     }
     #[test]
+    fn test_interpolation_constant() {
+        let level = |s| {
+            let text = cast!(first_child(transform(s)), IRNode::TextCall);
+            match &text.texts[0] {
+                Js::Call(_, args) => args[0].static_level(),
+                _ => panic!("interpolation should call toDisplayString"),
+            }
+        };
+        assert!(level("{{ 1 + 1 }}") == StaticLevel::CanStringify);
+        assert!(level("{{ 1.5 }}") == StaticLevel::CanStringify);
+        assert!(level("{{ 'a.b' }}") == StaticLevel::CanStringify);
+        assert!(level("{{ a }}") == StaticLevel::NotStatic);
+        assert!(level("{{ a.b() }}") == StaticLevel::NotStatic);
+        assert!(level("{{ Math.max(1, 2) }}") == StaticLevel::NotStatic);
+    }
+    #[test]
     fn test_prop_prefix() {
         let ir = transform("<p :test='a'/>");
         let vn = cast!(first_child(ir), IRNode::VNodeCall);
@@ -458,6 +592,16 @@ mod test {
         assert_eq!(expr.into_string(), "_ctx.b");
     }
     #[test]
+    fn test_slot_name_prefix() {
+        let ir = transform("<slot :name='n' :user='u'/>");
+        let r = cast!(first_child(ir), IRNode::RenderSlotCall);
+        let name = cast!(&r.slot_name, Js::Simple);
+        assert_eq!(name.into_string(), "_ctx.n");
+        let props = cast!(r.slot_props.unwrap(), Js::Props);
+        let expr = cast!(&props[0].1, Js::Simple);
+        assert_eq!(expr.into_string(), "_ctx.u");
+    }
+    #[test]
     fn test_prefix_v_for() {
         let ir = transform("<p v-for='a in b'/>");
         let v_for = cast!(first_child(ir), IRNode::For);
@@ -490,10 +634,9 @@ mod test {
             _ => panic!("wrong interpolation"),
         };
         let expr = cast!(text, Js::Compound);
-        let prop = cast!(&expr[1], Js::Compound);
-        let key = cast!(prop[0], Js::StrLit);
-        let colon = cast!(prop[1], Js::Src);
-        let val = cast!(prop[2], Js::Simple);
+        let key = cast!(expr[1], Js::StrLit);
+        let colon = cast!(expr[2], Js::Src);
+        let val = cast!(expr[3], Js::Simple);
         assert_eq!(key.into_string(), "a");
         assert_eq!(colon, ": ");
         assert_eq!(val.into_string(), "_ctx.a");
@@ -529,14 +672,104 @@ mod test {
         let ir = transform("<p v-for='a={c} in b'/>");
         let v_for = cast!(first_child(ir), IRNode::For);
         let val = cast!(v_for.parse_result.value, Js::Compound);
-        let c = cast!(&val[2], Js::Compound);
-        let prop = cast!(&c[1], Js::Compound);
-        let key = cast!(prop[0], Js::StrLit);
-        let val = cast!(prop[2], Js::Simple);
+        assert!(matches!(&val[1], Js::Joined { text, .. } if text == "={"));
+        let key = cast!(val[2], Js::StrLit);
+        let val = cast!(val[4], Js::Simple);
         assert_eq!(key.into_string(), "c");
         assert_eq!(val.into_string(), "_ctx.c");
     }
 
+    fn text_expr<'a, 'b>(ir: &'b BaseIR<'a>) -> &'b Js<'a> {
+        let text = cast!(ir, IRNode::TextCall);
+        match &text.texts[0] {
+            Js::Call(_, r) => &r[0],
+            _ => panic!("wrong interpolation"),
+        }
+    }
+
+    #[test]
+    fn test_slot_destruct_scope() {
+        let ir = transform("<comp v-slot='{ a, b: c }'>{{ a }}{{ b }}{{ c }}</comp>");
+        let comp = cast!(first_child(ir), IRNode::VNodeCall);
+        let slots = cast!(&comp.children[0], IRNode::VSlotUse);
+        let slot = &slots.stable_slots[0];
+        let param = cast!(slot.param.as_ref().unwrap(), Js::Compound);
+        let params: Vec<_> = param
+            .iter()
+            .filter_map(|p| match p {
+                Js::Param(p) => Some(*p),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(params, ["a", "c"]);
+        let ids: Vec<_> = slot
+            .body
+            .iter()
+            .map(|t| cast!(text_expr(t), Js::Simple).into_string())
+            .collect();
+        assert_eq!(ids, ["a", "_ctx.b", "c"]);
+    }
+
+    #[test]
+    fn test_slot_scope() {
+        let slot_ids = |slot: &Slot<BaseInfo>| -> Vec<String> {
+            slot.body
+                .iter()
+                .map(|t| cast!(text_expr(t), Js::Simple).into_string())
+                .collect()
+        };
+        let ir = transform("<comp v-slot='props'>{{ props }}{{ other }}</comp>");
+        let comp = cast!(first_child(ir), IRNode::VNodeCall);
+        let slots = cast!(&comp.children[0], IRNode::VSlotUse);
+        assert_eq!(slot_ids(&slots.stable_slots[0]), ["props", "_ctx.other"]);
+        let ir = transform(
+            "<comp>\
+                <template #a='{ item, index = 0 }'>{{ item }}{{ index }}</template>\
+                <template #b>{{ item }}{{ index }}</template>\
+            </comp>",
+        );
+        let comp = cast!(first_child(ir), IRNode::VNodeCall);
+        let slots = cast!(&comp.children[0], IRNode::VSlotUse);
+        assert_eq!(slot_ids(&slots.stable_slots[0]), ["item", "index"]);
+        assert_eq!(
+            slot_ids(&slots.stable_slots[1]),
+            ["_ctx.item", "_ctx.index"]
+        );
+    }
+
+    #[test]
+    fn test_array_destruct_scope() {
+        let ir = transform("<p v-for='[x, y] in list'>{{ x }}{{ y }}{{ z }}</p>");
+        let v_for = cast!(first_child(ir), IRNode::For);
+        let p = cast!(*v_for.child, IRNode::VNodeCall);
+        let ids: Vec<_> = p
+            .children
+            .iter()
+            .map(|t| cast!(text_expr(t), Js::Simple).into_string())
+            .collect();
+        assert_eq!(ids, ["x", "y", "_ctx.z"]);
+    }
+
+    #[test]
+    fn test_nested_default_destruct() {
+        let ir = transform("<p v-for='{ a = 1, b: { c } } in list'>{{ a }}{{ b }}{{ c }}</p>");
+        let v_for = cast!(first_child(ir), IRNode::For);
+        let p = cast!(*v_for.child, IRNode::VNodeCall);
+        let ids: Vec<_> = p
+            .children
+            .iter()
+            .map(|t| cast!(text_expr(t), Js::Simple).into_string())
+            .collect();
+        assert_eq!(ids, ["a", "_ctx.b", "c"]);
+    }
+
+    #[test]
+    fn test_scope_exit() {
+        let ir = transform("<p v-for='{ a } in b'/>{{ a }}");
+        let text = text_expr(&ir.body[1]);
+        assert_eq!(cast!(text, Js::Simple).into_string(), "_ctx.a");
+    }
+
     #[test]
     fn test_error_expression() {
         let error_handler = Rc::new(VecErrorHandler::default());
@@ -546,4 +779,138 @@ mod test {
         let kind = &errs[0].kind;
         assert!(matches!(kind, ErrorKind::InvalidExpression));
     }
+
+    #[test]
+    fn test_v_model_on_props() {
+        let error_handler = Rc::new(VecErrorHandler::default());
+        let info = inline_info("foo", BindingTypes::Props);
+        let mut ir = handler_convert("<comp v-model='foo'/>");
+        let exp = ExpressionProcessor {
+            prefix_identifier: true,
+            sfc_info: &info,
+            err_handle: error_handler.clone(),
+        };
+        BaseTransformer::transform(&mut ir, transformer_ext(exp));
+        let errs = error_handler.errors();
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(errs[0].kind, ErrorKind::VModelOnProps));
+        assert_eq!(errs[0].location.start.offset, 6);
+    }
+
+    #[test]
+    fn test_v_model_on_scope_variable() {
+        let error_handler = Rc::new(VecErrorHandler::default());
+        let case = "<comp v-for='item in list' v-model='item'/>";
+        let mut ir = handler_convert(case);
+        let exp = ExpressionProcessor {
+            prefix_identifier: true,
+            sfc_info: &Default::default(),
+            err_handle: error_handler.clone(),
+        };
+        BaseTransformer::transform(&mut ir, transformer_ext(exp));
+        let errs = error_handler.errors();
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(errs[0].kind, ErrorKind::VModelOnScopeVariable));
+        let start = errs[0].location.start.offset;
+        assert_eq!(start, case.find("v-model").unwrap());
+    }
+
+    #[test]
+    fn test_inline_maybe_ref_read() {
+        let info = inline_info("foo", BindingTypes::SetupMaybeRef);
+        let ir = transform_inline("<p :test='foo'/>", &info);
+        let vn = cast!(first_child(ir), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        let (helper, args) = match &props[0].1 {
+            Js::Call(h, args) => (h, args),
+            _ => panic!("maybe ref should be unref-ed"),
+        };
+        assert_eq!(*helper, RH::UNREF);
+        let foo = cast!(args[0], Js::Simple);
+        assert_eq!(foo.into_string(), "foo");
+    }
+
+    #[test]
+    fn test_inline_dynamic_component() {
+        let tag = |name, ty| {
+            let info = inline_info(name, ty);
+            let ir = transform_inline("<component :is='view'/>", &info);
+            let vn = cast!(first_child(ir), IRNode::VNodeCall);
+            assert!(vn.is_block);
+            let args = match vn.tag {
+                Js::Call(RH::RESOLVE_DYNAMIC_COMPONENT, args) => args,
+                _ => panic!("dynamic component should be resolved at runtime"),
+            };
+            match &args[0] {
+                Js::Simple(v, _) => v.into_string(),
+                Js::Call(RH::UNREF, args) => {
+                    let arg = cast!(&args[0], Js::Simple);
+                    format!("unref({})", arg.into_string())
+                }
+                _ => panic!("unexpected is expression"),
+            }
+        };
+        assert_eq!(tag("view", BindingTypes::SetupConst), "view");
+        assert_eq!(tag("view", BindingTypes::SetupMaybeRef), "unref(view)");
+        assert_eq!(tag("other", BindingTypes::SetupConst), "_ctx.view");
+    }
+
+    fn stringify(exprs: &[Js]) -> String {
+        exprs
+            .iter()
+            .map(|e| match e {
+                Js::Src(s) => s.to_string(),
+                Js::Simple(v, _) => v.into_string(),
+                Js::Call(h, args) => format!("{}({})", h.helper_str(&[]), stringify(args)),
+                _ => panic!("unexpected expression"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_v_model_assign() {
+        use BindingTypes as BT;
+        let cases = [
+            (BT::Data, "_ctx.foo = $event"),
+            (BT::Options, "_ctx.foo = $event"),
+            (BT::Props, "__props.foo = $event"),
+            (BT::SetupConst, "foo = $event"),
+            (BT::SetupRef, "(foo).value = $event"),
+            (
+                BT::SetupMaybeRef,
+                "isRef(foo) ? (foo).value = $event : foo = $event",
+            ),
+            (
+                BT::SetupLet,
+                "isRef(foo) ? (foo).value = $event : foo = $event",
+            ),
+        ];
+        for (ty, expected) in cases {
+            let info = inline_info("foo", ty);
+            let ir = transform_inline("<comp v-model='foo'/>", &info);
+            let vn = cast!(first_child(ir), IRNode::VNodeCall);
+            let props = cast!(vn.props.unwrap(), Js::Props);
+            let body = match &props[1].1 {
+                Js::FuncCompound { body, .. } => body,
+                _ => panic!("v-model handler should be rewritten"),
+            };
+            assert_eq!(stringify(body), expected);
+        }
+    }
+
+    #[test]
+    fn test_inline_maybe_ref_assign_fallback() {
+        let info = inline_info("foo", BindingTypes::SetupMaybeRef);
+        let ir = transform_inline("<input v-model='foo'/>", &info);
+        let vn = cast!(first_child(ir), IRNode::VNodeCall);
+        let props = cast!(vn.props.unwrap(), Js::Props);
+        let body = match &props[1].1 {
+            Js::FuncCompound { body, .. } => body,
+            _ => panic!("v-model handler should be rewritten"),
+        };
+        assert!(matches!(&body[0], Js::Call(RH::IS_REF, _)));
+        // non-ref value must still be written back to the local
+        let setter = &body[body.len() - 2..];
+        assert_eq!(stringify(setter), "foo = $event");
+    }
 }
@@ -0,0 +1,284 @@
+//! A generic visitor over IRNode trees.
+//! Unlike CorePass, which is driven by the transformer and only mutates
+//! nodes in place, a visitor can replace or remove the node it enters
+//! by returning a VisitAction. Nodes are visited in document order.
+use crate::ir::{
+    CacheIR, ConvertInfo, ForNodeIR, IRNode, IRRoot, IfNodeIR, RenderSlotIR, Slot, StaticIR,
+    TextIR, VNodeIR, VSlotIR,
+};
+
+pub enum VisitAction<T: ConvertInfo> {
+    /// keep the node and visit its children
+    Continue,
+    /// keep the node but skip its children. exit is still called
+    Skip,
+    /// replace the node. the new node is not visited
+    Replace(IRNode<T>),
+    /// remove the node from its parent. exit is not called.
+    /// the only child of v-if branch, v-for and cache becomes an empty comment
+    Remove,
+}
+
+use VisitAction as VA;
+
+#[allow(unused_variables)]
+pub trait IRVisitor<T: ConvertInfo> {
+    fn enter_root(&mut self, r: &mut IRRoot<T>) {}
+    fn exit_root(&mut self, r: &mut IRRoot<T>) {}
+    /// called on every node before its variant specific enter
+    fn enter_node(&mut self, n: &mut IRNode<T>) {}
+    /// called on every kept node after its variant specific exit
+    fn exit_node(&mut self, n: &mut IRNode<T>) {}
+    fn enter_text(&mut self, t: &mut TextIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_text(&mut self, t: &mut TextIR<T>) {}
+    fn enter_if(&mut self, i: &mut IfNodeIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_if(&mut self, i: &mut IfNodeIR<T>) {}
+    fn enter_for(&mut self, f: &mut ForNodeIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_for(&mut self, f: &mut ForNodeIR<T>) {}
+    fn enter_vnode(&mut self, v: &mut VNodeIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_vnode(&mut self, v: &mut VNodeIR<T>) {}
+    fn enter_slot_outlet(&mut self, r: &mut RenderSlotIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_slot_outlet(&mut self, r: &mut RenderSlotIR<T>) {}
+    fn enter_v_slot(&mut self, s: &mut VSlotIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_v_slot(&mut self, s: &mut VSlotIR<T>) {}
+    /// called for both stable slots and alterable slots.
+    /// NB: a stable slot can be removed but not replaced.
+    fn enter_slot_fn(&mut self, s: &mut Slot<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_slot_fn(&mut self, s: &mut Slot<T>) {}
+    fn enter_cache(&mut self, c: &mut CacheIR<T>) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_cache(&mut self, c: &mut CacheIR<T>) {}
+    fn enter_comment(&mut self, c: &mut T::CommentType) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_comment(&mut self, c: &mut T::CommentType) {}
+    fn enter_static(&mut self, s: &mut StaticIR) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_static(&mut self, s: &mut StaticIR) {}
+    fn enter_hoisted(&mut self, h: &mut T::HoistedType) -> VisitAction<T> {
+        VA::Continue
+    }
+    fn exit_hoisted(&mut self, h: &mut T::HoistedType) {}
+}
+
+pub fn walk<T: ConvertInfo>(root: &mut IRRoot<T>, visitor: &mut impl IRVisitor<T>) {
+    visitor.enter_root(root);
+    walk_children(&mut root.body, visitor);
+    visitor.exit_root(root);
+}
+
+fn walk_children<T, V>(children: &mut Vec<IRNode<T>>, v: &mut V)
+where
+    T: ConvertInfo,
+    V: IRVisitor<T>,
+{
+    let mut i = 0;
+    while i < children.len() {
+        if walk_node(&mut children[i], v) {
+            i += 1;
+        } else {
+            children.remove(i);
+        }
+    }
+}
+
+// the only child of v-if branch, v-for and cache cannot be removed
+fn walk_boxed<T, V>(child: &mut IRNode<T>, v: &mut V)
+where
+    T: ConvertInfo,
+    V: IRVisitor<T>,
+{
+    if !walk_node(child, v) {
+        *child = IRNode::CommentCall(Default::default());
+    }
+}
+
+/// Returns false if the node should be removed.
+fn walk_node<T, V>(node: &mut IRNode<T>, v: &mut V) -> bool
+where
+    T: ConvertInfo,
+    V: IRVisitor<T>,
+{
+    use IRNode as IR;
+    v.enter_node(node);
+    let action = match node {
+        IR::TextCall(t) => visit(v, t, V::enter_text, V::exit_text, |_, _| ()),
+        IR::If(i) => visit(v, i, V::enter_if, V::exit_if, |i, v| {
+            for branch in i.branches.iter_mut() {
+                walk_boxed(&mut branch.child, v);
+            }
+        }),
+        IR::For(f) => visit(v, f, V::enter_for, V::exit_for, |f, v| {
+            walk_boxed(&mut f.child, v)
+        }),
+        IR::VNodeCall(vn) => visit(v, vn, V::enter_vnode, V::exit_vnode, |vn, v| {
+            walk_children(&mut vn.children, v)
+        }),
+        IR::RenderSlotCall(r) => visit(v, r, V::enter_slot_outlet, V::exit_slot_outlet, |r, v| {
+            walk_children(&mut r.fallbacks, v)
+        }),
+        IR::VSlotUse(s) => visit(v, s, V::enter_v_slot, V::exit_v_slot, walk_v_slot),
+        IR::AlterableSlot(s) => visit(v, s, V::enter_slot_fn, V::exit_slot_fn, |s, v| {
+            walk_children(&mut s.body, v)
+        }),
+        IR::CacheNode(c) => visit(v, c, V::enter_cache, V::exit_cache, |c, v| {
+            walk_boxed(&mut c.child, v)
+        }),
+        IR::CommentCall(c) => visit(v, c, V::enter_comment, V::exit_comment, |_, _| ()),
+        IR::StaticCall(s) => visit(v, s, V::enter_static, V::exit_static, |_, _| ()),
+        IR::Hoisted(h) => visit(v, h, V::enter_hoisted, V::exit_hoisted, |_, _| ()),
+    };
+    match action {
+        VA::Replace(n) => {
+            *node = n;
+            true
+        }
+        VA::Remove => false,
+        VA::Continue | VA::Skip => {
+            v.exit_node(node);
+            true
+        }
+    }
+}
+
+fn walk_v_slot<T, V>(s: &mut VSlotIR<T>, v: &mut V)
+where
+    T: ConvertInfo,
+    V: IRVisitor<T>,
+{
+    let mut i = 0;
+    while i < s.stable_slots.len() {
+        let slot = &mut s.stable_slots[i];
+        match visit(v, slot, V::enter_slot_fn, V::exit_slot_fn, |s, v| {
+            walk_children(&mut s.body, v)
+        }) {
+            VA::Continue | VA::Skip => i += 1,
+            VA::Remove => {
+                s.stable_slots.remove(i);
+            }
+            VA::Replace(_) => panic!("stable slot cannot be replaced"),
+        }
+    }
+    walk_children(&mut s.alterable_slots, v);
+}
+
+fn visit<T, V, N, En, Ex, C>(v: &mut V, n: &mut N, enter: En, exit: Ex, children: C) -> VA<T>
+where
+    T: ConvertInfo,
+    En: FnOnce(&mut V, &mut N) -> VA<T>,
+    Ex: FnOnce(&mut V, &mut N),
+    C: FnOnce(&mut N, &mut V),
+{
+    let action = enter(v, n);
+    match action {
+        VA::Continue => children(n, v),
+        VA::Skip => (),
+        VA::Replace(_) | VA::Remove => return action,
+    }
+    exit(v, n);
+    action
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::converter::{test::base_convert, BaseConvertInfo as BaseInfo};
+    use crate::{cast, ir::JsExpr as Js};
+
+    #[derive(Default)]
+    struct TextCounter {
+        count: usize,
+    }
+    impl<'a> IRVisitor<BaseInfo<'a>> for TextCounter {
+        fn enter_text(&mut self, _: &mut TextIR<BaseInfo<'a>>) -> VisitAction<BaseInfo<'a>> {
+            self.count += 1;
+            VA::Continue
+        }
+    }
+
+    struct CommentRemover;
+    impl<'a> IRVisitor<BaseInfo<'a>> for CommentRemover {
+        fn enter_comment(&mut self, _: &mut &'a str) -> VisitAction<BaseInfo<'a>> {
+            VA::Remove
+        }
+    }
+
+    #[test]
+    fn test_count_text() {
+        let mut ir = base_convert(
+            "<p>a<span>b</span><i v-if='x'>c</i></p>\
+            <b v-for='i in l'>d</b>\
+            <comp><template #foo>e</template></comp>",
+        );
+        let mut counter = TextCounter::default();
+        walk(&mut ir, &mut counter);
+        assert_eq!(counter.count, 5);
+    }
+
+    #[test]
+    fn test_remove_comment() {
+        let mut ir = base_convert("<div><!--a--><p/><!--b--></div><!--c-->");
+        walk(&mut ir, &mut CommentRemover);
+        assert_eq!(ir.body.len(), 1);
+        let div = cast!(&ir.body[0], IRNode::VNodeCall);
+        assert_eq!(div.children.len(), 1);
+        let p = cast!(&div.children[0], IRNode::VNodeCall);
+        assert!(matches!(p.tag, Js::StrLit(t) if t.raw == "p"));
+    }
+
+    struct PRemover;
+    impl<'a> IRVisitor<BaseInfo<'a>> for PRemover {
+        fn enter_vnode(&mut self, v: &mut VNodeIR<BaseInfo<'a>>) -> VisitAction<BaseInfo<'a>> {
+            match v.tag {
+                Js::StrLit(t) if t.raw == "p" => VA::Remove,
+                _ => VA::Continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_only_child() {
+        let mut ir = base_convert("<p v-if='x'/><i v-else/><p v-for='i in l'/>");
+        walk(&mut ir, &mut PRemover);
+        let i = cast!(&ir.body[0], IRNode::If);
+        assert!(matches!(*i.branches[0].child, IRNode::CommentCall("")));
+        assert!(matches!(*i.branches[1].child, IRNode::VNodeCall(_)));
+        let f = cast!(&ir.body[1], IRNode::For);
+        assert!(matches!(*f.child, IRNode::CommentCall("")));
+    }
+
+    struct SpanReplacer;
+    impl<'a> IRVisitor<BaseInfo<'a>> for SpanReplacer {
+        fn enter_vnode(&mut self, v: &mut VNodeIR<BaseInfo<'a>>) -> VisitAction<BaseInfo<'a>> {
+            match v.tag {
+                Js::StrLit(t) if t.raw == "span" => VA::Replace(IRNode::CommentCall("span")),
+                _ => VA::Continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace_node() {
+        let mut ir = base_convert("<p><span>a</span>b</p>");
+        walk(&mut ir, &mut SpanReplacer);
+        let p = cast!(&ir.body[0], IRNode::VNodeCall);
+        assert!(matches!(p.children[0], IRNode::CommentCall("span")));
+        assert!(matches!(p.children[1], IRNode::TextCall(_)));
+    }
+}
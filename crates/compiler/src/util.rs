@@ -52,7 +52,7 @@ pub fn is_core_component(tag: &str) -> bool {
 fn is_event_prop(prop: &str) -> bool {
     let bytes = prop.as_bytes();
     // equivalent to /^on[^a-z]/
-    bytes.len() > 2 && bytes.starts_with(b"on") && !bytes[3].is_ascii_lowercase()
+    bytes.len() > 2 && bytes.starts_with(b"on") && !bytes[2].is_ascii_lowercase()
 }
 
 pub fn is_mergeable_prop(prop: &str) -> bool {
@@ -70,7 +70,17 @@ pub fn is_simple_identifier(s: VStr) -> bool {
     }
     let is_ident = |c| !not_js_identifier(c);
     let raw = s.raw;
-    raw.chars().all(is_ident) && !raw.starts_with(|c: char| c.is_ascii_digit())
+    // camelized dashes like before-update are removed in output
+    let camelized = VStr::is_camelized(&s);
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        let is_dropped_dash =
+            camelized && c == '-' && matches!(chars.peek(), Some(n) if n.is_ascii_alphanumeric());
+        if !is_ident(c) && !is_dropped_dash {
+            return false;
+        }
+    }
+    !raw.starts_with(|c: char| c.is_ascii_digit() || c == '-')
 }
 
 macro_rules! make_list {
@@ -114,6 +124,52 @@ pub fn is_global_allow_listed(s: &str) -> bool {
     ALLOWED_GLOBALS.contains(&s)
 }
 
+/// Returns true if the expression is a JS literal whose value is known
+/// at compile time, e.g. `42`, `true`, `null` or `'str'`.
+pub fn is_literal_constant(s: &str) -> bool {
+    let s = s.trim();
+    if matches!(s, "true" | "false" | "null") {
+        return true;
+    }
+    is_number_literal(s) || is_string_literal(s)
+}
+
+fn is_number_literal(s: &str) -> bool {
+    let n = s.strip_prefix('-').unwrap_or(s);
+    if let Some(hex) = n.strip_prefix("0x").or_else(|| n.strip_prefix("0X")) {
+        return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    // NB: f64 parser accepts inf and NaN which are not literals
+    n.starts_with(|c: char| c.is_ascii_digit() || c == '.') && n.parse::<f64>().is_ok()
+}
+
+fn is_string_literal(s: &str) -> bool {
+    let quote = match s.chars().next() {
+        Some(q @ ('\'' | '"' | '`')) => q,
+        _ => return false,
+    };
+    let body = match s[1..].strip_suffix(quote) {
+        Some(body) => body,
+        None => return false,
+    };
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // skip escaped char. trailing backslash escapes the closing quote
+            '\\' => {
+                if chars.next().is_none() {
+                    return false;
+                }
+            }
+            // template literal with interpolation is not constant
+            '$' if quote == '`' => return false,
+            c if c == quote => return false,
+            _ => (),
+        }
+    }
+    true
+}
+
 // https://github.com/vuejs/rfcs/blob/master/active-rfcs/0008-render-function-api-change.md#special-reserved-props
 const RESERVED: &[&str] = make_list![
     key,
@@ -131,6 +187,55 @@ pub fn is_reserved_prop(tag: &str) -> bool {
     RESERVED.contains(&tag)
 }
 
+// https://html.spec.whatwg.org/multipage/indices.html#attributes-3
+// keywords like async/loop/default cannot be used in make_list
+const BOOLEAN_ATTRS: &[&str] = &[
+    "itemscope",
+    "allowfullscreen",
+    "formnovalidate",
+    "ismap",
+    "nomodule",
+    "novalidate",
+    "readonly",
+    "async",
+    "autofocus",
+    "autoplay",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "hidden",
+    "inert",
+    "loop",
+    "open",
+    "required",
+    "reversed",
+    "scoped",
+    "seamless",
+    "checked",
+    "muted",
+    "multiple",
+    "selected",
+];
+
+/// Escapes decoded text so it can be written into html attributes or content.
+pub fn escape_html(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[inline]
+pub fn is_boolean_attr(attr: &str) -> bool {
+    BOOLEAN_ATTRS.contains(&attr)
+}
+
 pub fn is_component_tag(tag: &str) -> bool {
     tag == "component" || tag == "Component"
 }
@@ -472,6 +577,34 @@ mod test {
     use super::*;
     use crate::parser::test::mock_element;
 
+    #[test]
+    fn test_literal_constant() {
+        let consts = [
+            "42", "-1.5", "1e3", "0xff", "true", "false", "null", "'a'", "\"b c\"", "`d`",
+            "'it\\'s'",
+        ];
+        for c in consts {
+            assert!(is_literal_constant(c), "{}", c);
+        }
+        let non_consts = [
+            "a",
+            "NaN",
+            "inf",
+            "undefined",
+            "this",
+            "'a' + b",
+            "'a",
+            "`${a}`",
+            "'a\\'",
+            "'a\\\\\\'",
+            "1 + 2",
+            "0x",
+        ];
+        for c in non_consts {
+            assert!(!is_literal_constant(c), "{}", c);
+        }
+    }
+
     #[test]
     fn test_find_dir() {
         let e = mock_element("<p v-if=true/>");
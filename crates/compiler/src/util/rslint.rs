@@ -299,20 +299,39 @@ where
 }
 
 pub fn is_member_expression(text: &str) -> bool {
-    is_member_expr_impl(parse_js_expr(text))
+    is_member_expr_impl(parse_js_expr(text), true)
 }
-fn is_member_expr_impl(expr_opt: Option<Expr>) -> bool {
+/// member expression that can be assigned to, e.g. a?.b is not.
+pub fn is_assignable_member_expression(text: &str) -> bool {
+    is_member_expr_impl(parse_js_expr(text), false)
+}
+fn is_member_expr_impl(expr_opt: Option<Expr>, allow_optional: bool) -> bool {
     let expr = match expr_opt {
         Some(e) => e,
         None => return false,
     };
     use Expr as E;
     match expr {
-        E::NameRef(_) | E::DotExpr(_) | E::BracketExpr(_) => true,
-        E::GroupingExpr(e) => is_member_expr_impl(e.inner()),
+        E::NameRef(_) => true,
+        E::DotExpr(_) | E::BracketExpr(_) => allow_optional || !has_optional_chain(&expr),
+        E::GroupingExpr(e) => is_member_expr_impl(e.inner(), allow_optional),
         _ => false,
     }
 }
+// parenthesis ends an optional chain so grouping is not walked into.
+fn has_optional_chain(expr: &Expr) -> bool {
+    use Expr as E;
+    let (node, object) = match expr {
+        E::DotExpr(e) => (e.syntax(), e.object()),
+        E::BracketExpr(e) => (e.syntax(), e.object()),
+        E::CallExpr(e) => (e.syntax(), e.callee()),
+        _ => return false,
+    };
+    let is_optional = node
+        .children_with_tokens()
+        .any(|t| t.kind() == SyntaxKind::QUESTIONDOT);
+    is_optional || object.map_or(false, |o| has_optional_chain(&o))
+}
 
 #[cfg(test)]
 mod test {
@@ -360,6 +379,15 @@ mod test {
         assert!(parse_js_expr("if (a) {b} else {c}").is_none());
         // assert!(parse_js_expr("{a = 4}").is_none()); // TODO
     }
+    #[test]
+    fn test_assignable_member_expr() {
+        assert!(is_member_expression("a?.b"));
+        assert!(!is_assignable_member_expression("a?.b"));
+        assert!(!is_assignable_member_expression("a?.[b].c"));
+        assert!(!is_assignable_member_expression("a?.b().c"));
+        assert!(is_assignable_member_expression("a.b[c?.d]"));
+        assert!(is_assignable_member_expression("(a?.b).c"));
+    }
 
     fn walk_ident(s: &str) -> Vec<String> {
         let expr = parse_js_expr(s).unwrap();
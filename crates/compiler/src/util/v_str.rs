@@ -19,7 +19,7 @@ bitflags! {
     /// depending on whether the manipulation is idempotent or not
     /// NB strops is order sensitive when it is cast to string.
     #[derive(Default)]
-    pub struct StrOps: u16 {
+    pub struct StrOps: u32 {
         const HANDLER_KEY         = 1 << 0;
         const MODEL_HANDLER       = 1 << 1;
         const VALID_DIR           = 1 << 2;
@@ -30,12 +30,16 @@ bitflags! {
         const CAMEL_CASE          = 1 << 7;
         const CAPITALIZED         = 1 << 8;
         const JS_STRING           = 1 << 9;
+        const VNODE_HOOK          = 1 << 10;
         const CTX_PREFIX          = 1 << 11;
         const MOD_SUFFIX          = 1 << 12;
         const ASSIGN_EVT          = 1 << 13;
         // marker op is placed at the end
         const SELF_SUFFIX         = 1 << 14;
         const DECODE_ATTR         = 1 << 15;
+        const PROP_PREFIX         = 1 << 16;
+        const ATTR_PREFIX         = 1 << 17;
+        const IMPORTED            = 1 << 18;
         /// Ops that can be safely carried out multiple times
         const IDEMPOTENT_OPS =
             Self::COMPRESS_WHITESPACE.bits | Self::DECODE_ENTITY.bits |
@@ -45,7 +49,8 @@ bitflags! {
         const AFFINE_OPS =
             Self::HANDLER_KEY.bits | Self::MODEL_HANDLER.bits | Self::VALID_DIR.bits |
             Self::VALID_COMP.bits | Self::SELF_SUFFIX.bits | Self::V_DIR_PREFIX.bits |
-            Self::JS_STRING.bits | Self::CTX_PREFIX.bits;
+            Self::JS_STRING.bits | Self::CTX_PREFIX.bits | Self::VNODE_HOOK.bits |
+            Self::PROP_PREFIX.bits | Self::ATTR_PREFIX.bits | Self::IMPORTED.bits;
         /// Ops that mark the string is an hoisted asset
         const ASSET_OPS = Self::VALID_DIR.bits | Self::VALID_COMP.bits |
             Self::SELF_SUFFIX.bits;
@@ -185,6 +190,7 @@ impl StrOps {
             StrOps::CAPITALIZED => write_capitalized(s, w),
             StrOps::VALID_DIR => write_valid_asset(s, w, "directive"),
             StrOps::VALID_COMP => write_valid_asset(s, w, "component"),
+            StrOps::IMPORTED => write_valid_asset(s, w, "imported"),
             StrOps::SELF_SUFFIX => {
                 // noop, just a marker
                 w.write_str(s)
@@ -199,6 +205,10 @@ impl StrOps {
                 write_capitalized(&s[0..len], &mut w)?;
                 w.write_str(&s[len..])
             }
+            StrOps::VNODE_HOOK => {
+                w.write_str("onVnode")?;
+                write_capitalized(s, w)
+            }
             StrOps::MODEL_HANDLER => {
                 w.write_str("onUpdate:")?;
                 w.write_str(s)
@@ -211,6 +221,14 @@ impl StrOps {
                 w.write_str(s)?;
                 w.write_str("Modifiers")
             }
+            StrOps::PROP_PREFIX => {
+                w.write_str(".")?;
+                w.write_str(s)
+            }
+            StrOps::ATTR_PREFIX => {
+                w.write_str("^")?;
+                w.write_str(s)
+            }
             StrOps::ASSIGN_EVT => {
                 w.write_str("(")?;
                 w.write_str(s)?;
@@ -265,7 +283,7 @@ impl<'a> VStr<'a> {
     }
     pub fn is_handler(s: &VStr) -> bool {
         if s.ops
-            .intersects(StrOps::HANDLER_KEY | StrOps::MODEL_HANDLER)
+            .intersects(StrOps::HANDLER_KEY | StrOps::MODEL_HANDLER | StrOps::VNODE_HOOK)
         {
             return true;
         }
@@ -277,12 +295,18 @@ impl<'a> VStr<'a> {
     pub fn is_asset(s: &VStr) -> bool {
         s.ops.intersects(StrOps::ASSET_OPS)
     }
+    pub fn is_imported(s: &VStr) -> bool {
+        s.ops.contains(StrOps::IMPORTED)
+    }
     pub fn is_ctx_prefixed(s: &VStr) -> bool {
         s.ops.contains(StrOps::CTX_PREFIX)
     }
     pub fn is_event_assign(s: &VStr) -> bool {
         s.ops.contains(StrOps::ASSIGN_EVT)
     }
+    pub fn is_camelized(s: &VStr) -> bool {
+        s.ops.contains(StrOps::CAMEL_CASE)
+    }
     pub fn has_affix(s: &VStr) -> bool {
         s.ops.intersects(
             StrOps::MODEL_HANDLER
@@ -291,7 +315,9 @@ impl<'a> VStr<'a> {
                 | StrOps::V_DIR_PREFIX
                 | StrOps::CTX_PREFIX
                 | StrOps::MOD_SUFFIX
-                | StrOps::ASSIGN_EVT,
+                | StrOps::ASSIGN_EVT
+                | StrOps::PROP_PREFIX
+                | StrOps::ATTR_PREFIX,
         )
     }
 }
@@ -306,6 +332,11 @@ impl<'a> VStr<'a> {
         self.ops |= ops;
         self
     }
+    /// keep html entities as is, e.g. SSR outputs raw html
+    pub fn undecode(&mut self) -> &mut Self {
+        self.ops.remove(StrOps::DECODE_ENTITY | StrOps::DECODE_ATTR);
+        self
+    }
     pub fn camelize(&mut self) -> &mut Self {
         self.ops |= StrOps::CAMEL_CASE;
         self
@@ -326,6 +357,11 @@ impl<'a> VStr<'a> {
         self.ops |= StrOps::HANDLER_KEY;
         self
     }
+    /// convert vue:arg to vnode hook key: mounted -> onVnodeMounted
+    pub fn be_vnode_hook(&mut self) -> &mut Self {
+        self.ops |= StrOps::VNODE_HOOK;
+        self
+    }
     /// convert v-model:arg to onModelUpdate:arg
     pub fn be_vmodel(&mut self) -> &mut Self {
         self.ops |= StrOps::MODEL_HANDLER;
@@ -345,6 +381,11 @@ impl<'a> VStr<'a> {
         self.ops.remove(StrOps::VALID_COMP);
         self
     }
+    /// convert into the local name of an imported component
+    pub fn be_imported(&mut self) -> &mut Self {
+        self.ops |= StrOps::IMPORTED;
+        self
+    }
     pub fn be_directive(&mut self) -> &mut Self {
         self.ops |= StrOps::VALID_DIR;
         self
@@ -370,6 +411,16 @@ impl<'a> VStr<'a> {
         self.ops |= StrOps::MOD_SUFFIX;
         self
     }
+    /// v-bind.prop sets DOM property, e.g. `.innerHTML`
+    pub fn prefix_prop(&mut self) -> &mut Self {
+        self.ops |= StrOps::PROP_PREFIX;
+        self
+    }
+    /// v-bind.attr forces attribute, e.g. `^width`
+    pub fn prefix_attr(&mut self) -> &mut Self {
+        self.ops |= StrOps::ATTR_PREFIX;
+        self
+    }
     pub fn assign_event(&mut self) -> &mut Self {
         self.ops |= StrOps::ASSIGN_EVT;
         self
@@ -465,6 +516,11 @@ mod test {
             ("å—åŒ–ã‘", StrOps::empty(), "å—åŒ–ã‘"),
             ("å—åŒ–ã‘", StrOps::JS_STRING, stringify!("å—åŒ–ã‘")),
             ("foo-bar", StrOps::CAMEL_CASE, "fooBar"),
+            (
+                "before-mount",
+                StrOps::CAMEL_CASE | StrOps::VNODE_HOOK,
+                "onVnodeBeforeMount",
+            ),
             ("foo-bar", StrOps::CAPITALIZED, "Foo-bar"),
             ("", StrOps::CAPITALIZED, ""),
             ("ālaya-vijñāna", StrOps::CAMEL_CASE, "ālayaVijñāna"),
@@ -483,6 +539,12 @@ mod test {
             ("a^_^", StrOps::VALID_COMP, "_component_a94_94"),
             ("a--b", StrOps::VALID_DIR, "_directive_a__b"),
             ("a--", StrOps::VALID_DIR, "_directive_a__"),
+            (
+                "foo-bar",
+                StrOps::CAMEL_CASE | StrOps::PROP_PREFIX,
+                ".fooBar",
+            ),
+            ("width", StrOps::ATTR_PREFIX, "^width"),
         ];
         for (src, ops, expect) in cases {
             let origin = ops;
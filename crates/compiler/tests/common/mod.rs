@@ -47,6 +47,7 @@ pub struct TestError {
     pub msg: String,
 }
 
+/// Returns reported errors followed by warnings.
 pub fn get_errors(source: &str) -> Vec<TestError> {
     let error_handler = Rc::new(VecErrorHandler::new());
     let option = CompileOption {
@@ -60,8 +61,10 @@ pub fn get_errors(source: &str) -> Vec<TestError> {
     let compiler = BaseCompiler::new(dest, get_base_passes, option);
     let ret = compiler.compile(source, &sfc_info).unwrap();
     let errors = error_handler.errors();
+    let warnings = error_handler.warnings();
     errors
         .iter()
+        .chain(warnings.iter())
         .map(|e| TestError {
             msg: e.msg().to_string(),
             loc: e.location.clone(),
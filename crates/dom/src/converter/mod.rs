@@ -5,7 +5,8 @@ mod v_show;
 mod v_text;
 
 use compiler::converter::{
-    CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element, ErrorHandler,
+    ConvertContext, CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element,
+    ErrorHandler,
 };
 use compiler::ir::JsExpr;
 
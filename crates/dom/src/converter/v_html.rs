@@ -1,14 +1,15 @@
 use super::{
     CoreDirConvRet, Directive, DirectiveConverter, Element, ErrorHandler, DirectiveConvertResult,
-    JsExpr as Js,
+    ConvertContext, JsExpr as Js,
 };
 use compiler::error::{CompilationErrorKind, CompilationError};
 use crate::extension::DomError;
 
 pub fn convert_v_html<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     let error_kind = CompilationErrorKind::extended(DomError::VHtmlNoExpression);
     if let Some(err) = dir.check_empty_expr(error_kind) {
@@ -19,7 +20,8 @@ pub fn convert_v_html<'a>(
         let error = CompilationError::extended(DomError::VHtmlWithChildren)
             .with_location(dir.location.clone());
         eh.on_error(error);
-        // TODO remove element children
+        // drop children since innerHTML overrides them at runtime
+        e.children.clear();
     }
     let val = dir.expression.take().unwrap().content;
     let props = vec![(Js::str_lit("innerHTML"), Js::simple(val))];
@@ -29,3 +31,32 @@ pub fn convert_v_html<'a>(
     }
 }
 pub const V_HTML: DirectiveConverter = ("html", convert_v_html);
+
+#[cfg(test)]
+mod test {
+    use crate::test::compile;
+
+    #[test]
+    fn test_v_html() {
+        let (code, errors) = compile("<div v-html='raw'/>");
+        assert!(errors.is_empty());
+        assert!(code.contains("innerHTML: raw"), "{}", code);
+        assert!(code.contains(r#"["innerHTML"]"#), "{}", code);
+    }
+
+    #[test]
+    fn test_v_html_with_children() {
+        let (code, errors) = compile("<div v-html='raw'><span>child</span></div>");
+        assert_eq!(errors, vec!["v-html will override element children."]);
+        assert!(code.contains("innerHTML: raw"), "{}", code);
+        assert!(!code.contains("child"), "{}", code);
+    }
+
+    #[test]
+    fn test_v_html_with_props() {
+        let (code, errors) = compile("<div :id='foo' v-html='raw'/>");
+        assert!(errors.is_empty());
+        assert!(code.contains("id: foo"), "{}", code);
+        assert!(code.contains(r#"["id", "innerHTML"]"#), "{}", code);
+    }
+}
@@ -1,5 +1,5 @@
 use compiler::cast;
-use compiler::converter::{CompilationError, v_model::convert_v_model_event};
+use compiler::converter::{CompilationError, v_model::convert_v_model as convert_v_model_core};
 use compiler::flags::RuntimeHelper;
 use compiler::ir::JsExpr as Js;
 use compiler::parser::{ElemProp, DirectiveArg};
@@ -8,26 +8,27 @@ use super::DirectiveConvertResult;
 use crate::extension::{dom_helper as dh, DomError};
 use crate::options::is_native_tag;
 
-use super::{CoreDirConvRet, Directive, DirectiveConverter, Element, ErrorHandler};
+use super::{ConvertContext, CoreDirConvRet, Directive, DirectiveConverter, Element, ErrorHandler};
 pub fn convert_v_model<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    ctx: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     if e.is_component() {
-        return convert_v_model_event(dir, e, eh);
+        return convert_v_model_core(dir, e, eh, ctx);
     }
     if dir.argument.is_some() {
         let error = CompilationError::extended(DomError::VModelArgOnElement)
             .with_location(dir.location.clone());
         eh.on_error(error);
     }
-    let mut base = convert_v_model_event(dir, e, eh);
+    let mut base = convert_v_model_core(dir, e, eh, ctx);
     let (value, runtime) = match &mut base {
         DirectiveConvertResult::Dropped | DirectiveConvertResult::Preserve => return base,
         DirectiveConvertResult::Converted { value, runtime } => (value, runtime),
     };
-    let runtime_to_use = match compute_v_model_runtime(e, dir) {
+    match compute_v_model_runtime(e, dir) {
         Ok(rt) => {
             if matches!(rt, dh::V_MODEL_TEXT | dh::V_MODEL_SELECT) {
                 check_redundant_value_prop(e, eh);
@@ -85,7 +86,11 @@ fn compute_v_model_runtime(e: &Element, dir: &Directive) -> RuntimeResult {
     let ty = ty.get_ref();
     let val = match ty {
         ElemProp::Dir(..) => return Ok(dh::V_MODEL_DYNAMIC),
-        ElemProp::Attr(attr) => attr.value.as_ref().expect("non empty"),
+        ElemProp::Attr(attr) => match &attr.value {
+            Some(val) => val,
+            // <input type v-model> falls back to text
+            None => return Ok(dh::V_MODEL_TEXT),
+        },
     };
     match val.content.raw {
         "radio" => Ok(dh::V_MODEL_RADIO),
@@ -115,3 +120,48 @@ pub fn has_dynamic_v_bind(e: &Element) -> bool {
 }
 
 pub const V_MODEL: DirectiveConverter = ("model", convert_v_model);
+
+#[cfg(test)]
+mod test {
+    use crate::test::compile;
+
+    fn assert_runtime(s: &str, runtime: &str) {
+        let (code, errors) = compile(s);
+        assert!(errors.is_empty(), "{}", s);
+        assert!(code.contains(runtime), "{}", code);
+        assert!(code.contains("\"onUpdate:modelValue\""), "{}", code);
+    }
+
+    #[test]
+    fn test_native_v_model_runtime() {
+        assert_runtime("<input v-model='a'/>", "_vModelText");
+        assert_runtime("<input type='text' v-model='a'/>", "_vModelText");
+        assert_runtime("<input type='radio' v-model='a'/>", "_vModelRadio");
+        assert_runtime("<input type='checkbox' v-model='a'/>", "_vModelCheckbox");
+        assert_runtime("<textarea v-model='a'></textarea>", "_vModelText");
+        assert_runtime("<select v-model='a'></select>", "_vModelSelect");
+        assert_runtime("<input :type='t' v-model='a'/>", "_vModelDynamic");
+        assert_runtime("<input v-bind='obj' v-model='a'/>", "_vModelDynamic");
+    }
+
+    #[test]
+    fn test_native_v_model_modifiers() {
+        let (code, _) = compile("<input v-model.lazy.trim='a'/>");
+        assert!(code.contains("[_vModelText, a"), "{}", code);
+        assert!(code.contains("lazy: true"), "{}", code);
+        assert!(code.contains("trim: true"), "{}", code);
+        assert!(!code.contains("modelModifiers"), "{}", code);
+    }
+
+    #[test]
+    fn test_native_v_model_error() {
+        let (_, errors) = compile("<div v-model='a'/>");
+        assert_eq!(
+            errors,
+            vec!["v-model can only be used on <input>, <textarea> and <select> elements."]
+        );
+        let (_, errors) = compile("<input type='file' v-model='a'/>");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("file inputs"));
+    }
+}
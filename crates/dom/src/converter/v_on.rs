@@ -1,20 +1,22 @@
-use compiler::converter::v_on::convert_v_on as convert_v_on_core;
+use compiler::converter::v_on::{convert_v_on as convert_v_on_core, get_vnode_hook};
+use compiler::parser::DirectiveArg;
 
 use super::{
     CoreDirConvRet, Directive, DirectiveConvertResult, DirectiveConverter, Element, ErrorHandler,
-    JsExpr as Js,
+    ConvertContext, JsExpr as Js,
 };
 use crate::extension::dom_helper;
 use compiler::util::VStr;
 
 pub fn convert_v_on<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    ctx: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     use DirectiveConvertResult::Converted;
-    let base_converted = convert_v_on_core(dir, e, eh);
-    if dir.modifiers.is_empty() {
+    let base_converted = convert_v_on_core(dir, e, eh, ctx);
+    if dir.modifiers.is_empty() || is_vnode_hook(dir) {
         return base_converted;
     }
     let mut props = match base_converted {
@@ -34,6 +36,14 @@ pub fn convert_v_on<'a>(
     }
 }
 
+// vnode hooks are not DOM events so modifiers do not apply
+fn is_vnode_hook(dir: &Directive) -> bool {
+    match dir.argument {
+        Some(DirectiveArg::Static(s)) => get_vnode_hook(s).is_some(),
+        _ => false,
+    }
+}
+
 struct ResolvedMods<'a> {
     event_option: Vec<&'a str>,
     key_modifiers: Vec<&'a str>,
@@ -1,14 +1,15 @@
 use super::{
     CoreDirConvRet, Directive, DirectiveConverter, Element, ErrorHandler, DirectiveConvertResult,
-    JsExpr as Js,
+    ConvertContext, JsExpr as Js,
 };
 use compiler::error::CompilationErrorKind;
 use crate::extension::{DomError, dom_helper};
 
 pub fn convert_v_show<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     let error_kind = CompilationErrorKind::extended(DomError::VShowNoExpression);
     if let Some(err) = dir.check_empty_expr(error_kind) {
@@ -20,3 +21,41 @@ pub fn convert_v_show<'a>(
     }
 }
 pub const V_SHOW: DirectiveConverter = ("show", convert_v_show);
+
+#[cfg(test)]
+mod test {
+    use crate::extension::dom_helper;
+    use crate::test::compile;
+    use crate::{compile_option, get_dom_pass};
+    use compiler::compiler::{BaseCompiler, TemplateCompiler};
+    use compiler::error::VecErrorHandler;
+    use compiler::{cast, ir::IRNode, ir::JsExpr as Js, SFCInfo};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_v_show_ir() {
+        let sfc_info = SFCInfo::default();
+        let option = compile_option(Rc::new(VecErrorHandler::default()));
+        let compiler = BaseCompiler::new(Vec::new, get_dom_pass, option);
+        let ast = compiler.parse(compiler.scan("<div v-show='ok'/>"));
+        let mut ir = compiler.convert(ast, &sfc_info);
+        let vn = cast!(ir.body.remove(0), IRNode::VNodeCall);
+        assert!(vn.props.is_none());
+        assert_eq!(vn.directives.len(), 1);
+        let dir = &vn.directives[0];
+        assert!(matches!(dir.name, Js::Symbol(dom_helper::V_SHOW)));
+        let expr = cast!(dir.expr.as_ref().unwrap(), Js::Simple);
+        assert_eq!(expr.raw, "ok");
+    }
+
+    #[test]
+    fn test_v_show_codegen() {
+        let (code, errors) = compile("<div v-show='ok'/>");
+        assert!(errors.is_empty());
+        assert!(code.contains("vShow: _vShow"), "{}", code);
+        assert!(code.contains("_withDirectives("), "{}", code);
+        assert!(code.contains("[_vShow, ok]"), "{}", code);
+        let (_, errors) = compile("<div v-show/>");
+        assert_eq!(errors, vec!["v-show is missing expression."]);
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     CoreDirConvRet, Directive, DirectiveConverter, Element, ErrorHandler, DirectiveConvertResult,
-    JsExpr as Js,
+    ConvertContext, JsExpr as Js,
 };
 use compiler::error::{CompilationError, CompilationErrorKind};
 use compiler::flags::RuntimeHelper;
@@ -8,8 +8,9 @@ use crate::extension::DomError;
 
 pub fn convert_v_text<'a>(
     dir: &mut Directive<'a>,
-    e: &Element<'a>,
+    e: &mut Element<'a>,
     eh: &dyn ErrorHandler,
+    _: &ConvertContext<'a>,
 ) -> CoreDirConvRet<'a> {
     let error_kind = CompilationErrorKind::extended(DomError::VTextNoExpression);
     if let Some(err) = dir.check_empty_expr(error_kind) {
@@ -20,20 +21,68 @@ pub fn convert_v_text<'a>(
         let error = CompilationError::extended(DomError::VTextWithChildren)
             .with_location(dir.location.clone());
         eh.on_error(error);
+        // drop children since textContent overrides them at runtime
+        e.children.clear();
     }
     let exp = dir
         .expression
         .take()
         .expect("should not be empty after check")
         .content;
-    let args = vec![Js::simple(exp)];
-    let prop = (
-        Js::str_lit("textContent"),
-        Js::Call(RuntimeHelper::TO_DISPLAY_STRING, args),
-    );
+    // fold string literal so the prop can be hoisted as static
+    let text = match as_string_literal(exp.raw) {
+        Some(lit) => Js::str_lit(lit),
+        None => Js::Call(RuntimeHelper::TO_DISPLAY_STRING, vec![Js::simple(exp)]),
+    };
+    let prop = (Js::str_lit("textContent"), text);
     DirectiveConvertResult::Converted {
         value: Js::Props(vec![prop]),
         runtime: Err(false),
     }
 }
+
+fn as_string_literal(exp: &str) -> Option<&str> {
+    let exp = exp.trim();
+    let quote = exp.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = exp.strip_prefix(quote)?.strip_suffix(quote)?;
+    // bail out on escapes or concatenation like 'a' + 'b'
+    if inner.contains(quote) || inner.contains('\\') {
+        return None;
+    }
+    Some(inner)
+}
+
 pub const V_TEXT: DirectiveConverter = ("text", convert_v_text);
+
+#[cfg(test)]
+mod test {
+    use crate::test::compile;
+
+    #[test]
+    fn test_v_text() {
+        let (code, errors) = compile("<div v-text='msg'/>");
+        assert!(errors.is_empty());
+        let expected = "textContent: _toDisplayString(msg)";
+        assert!(code.contains(expected), "{}", code);
+        assert!(code.contains(r#"["textContent"]"#), "{}", code);
+    }
+
+    #[test]
+    fn test_v_text_errors() {
+        let (code, errors) = compile("<div v-text='msg'>child</div>");
+        assert_eq!(errors, vec!["v-text will override element children."]);
+        assert!(!code.contains("child"), "{}", code);
+        let (_, errors) = compile("<div v-text/>");
+        assert_eq!(errors, vec!["v-text is missing expression."]);
+    }
+
+    #[test]
+    fn test_v_text_literal() {
+        let (code, errors) = compile(r#"<div v-text="'hello'"/>"#);
+        assert!(errors.is_empty());
+        assert!(code.contains(r#"textContent: "hello""#), "{}", code);
+        assert!(!code.contains("toDisplayString"), "{}", code);
+        let (code, _) = compile(r#"<div v-text="'a' + 'b'"/>"#);
+        assert!(code.contains("_toDisplayString"), "{}", code);
+    }
+}
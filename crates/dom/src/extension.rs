@@ -38,6 +38,7 @@ pub enum DomError {
     VModelUnnecessaryValue,
     VShowNoExpression,
     TransitionInvalidChildren,
+    TransitionSlotChildren,
     IgnoredSideEffectTag,
 }
 
@@ -55,6 +56,7 @@ impl ErrorKind for DomError {
           VModelUnnecessaryValue => "Unnecessary value binding used alongside v-model. It will interfere with v-model's behavior.",
           VShowNoExpression => "v-show is missing expression.",
           TransitionInvalidChildren => "<Transition> expects exactly one child element or component.",
+          TransitionSlotChildren => "<Transition> may receive multiple children from <slot> at runtime.",
           IgnoredSideEffectTag => "Tags with side effect (<script> and <style>) are ignored in client component templates."
         }
     }
@@ -5,6 +5,34 @@ mod extension;
 mod options;
 mod transformer;
 
-pub use options::compile_option;
+pub use options::{compile_option, module_compile_option};
 pub use converter::DOM_DIR_CONVERTERS;
 pub use transformer::get_dom_pass;
+
+#[cfg(test)]
+mod test {
+    use super::{compile_option, get_dom_pass};
+    use compiler::compiler::{BaseCompiler, CompileOption, TemplateCompiler};
+    use compiler::error::VecErrorHandler;
+    use std::rc::Rc;
+
+    pub fn compile_with_option(s: &str, option: CompileOption) -> String {
+        let sfc_info = Default::default();
+        let compiler = BaseCompiler::new(Vec::new, get_dom_pass, option);
+        let out = compiler.compile(s, &sfc_info).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// compiles with dom passes and keeps reported diagnostics
+    pub fn compile_with_handler(s: &str) -> (String, Rc<VecErrorHandler>) {
+        let eh = Rc::new(VecErrorHandler::default());
+        let code = compile_with_option(s, compile_option(eh.clone()));
+        (code, eh)
+    }
+
+    pub fn compile(s: &str) -> (String, Vec<&'static str>) {
+        let (code, eh) = compile_with_handler(s);
+        let errors = eh.errors().iter().map(|e| e.msg()).collect();
+        (code, errors)
+    }
+}
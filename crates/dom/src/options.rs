@@ -1,6 +1,7 @@
 use compiler::{
-    Namespace, codegen::ScriptMode, compiler::CompileOption, converter::RcErrHandle,
-    flags::RuntimeHelper, parser::Element, scanner::TextMode,
+    Namespace, codegen::ScriptMode, compiler::CompileOption,
+    converter::{RcErrHandle, BASE_DIR_CONVERTERS}, flags::BuiltinComponent, parser::Element,
+    scanner::TextMode,
 };
 use crate::{converter::DOM_DIR_CONVERTERS, extension::dom_helper};
 use phf::{phf_set, Set};
@@ -47,16 +48,17 @@ const VOID_TAGS: &[&str] = &[
     "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
     "track", "wbr",
 ];
-fn is_void_tag(tag: &str) -> bool {
+pub(crate) fn is_void_tag(tag: &str) -> bool {
     VOID_TAGS.contains(&tag)
 }
 
-fn get_builtin_component(tag: &str) -> Option<RuntimeHelper> {
-    match tag {
-        "transition" | "Transition" => Some(dom_helper::TRANSITION),
-        "TransitionGroup" | "transition-group" => Some(dom_helper::TRANSITION_GROUP),
-        _ => None,
-    }
+fn get_builtin_component(tag: &str) -> Option<BuiltinComponent> {
+    let helper = match tag {
+        "transition" | "Transition" => dom_helper::TRANSITION,
+        "TransitionGroup" | "transition-group" => dom_helper::TRANSITION_GROUP,
+        _ => return None,
+    };
+    Some(BuiltinComponent::Helper(helper))
 }
 
 fn get_text_mode(tag: &str) -> TextMode {
@@ -113,7 +115,12 @@ pub fn compile_option(error_handler: RcErrHandle) -> CompileOption {
         get_builtin_component,
         get_namespace,
         delimiters: ("{{".to_string(), "}}".to_string()),
-        directive_converters: DOM_DIR_CONVERTERS.iter().copied().collect(),
+        // DOM converters override the base ones of the same name
+        directive_converters: BASE_DIR_CONVERTERS
+            .iter()
+            .chain(DOM_DIR_CONVERTERS)
+            .copied()
+            .collect(),
         helper_strs: dom_helper::DOM_HELPER_MAP,
         error_handler,
         mode: ScriptMode::Function {
@@ -123,3 +130,74 @@ pub fn compile_option(error_handler: RcErrHandle) -> CompileOption {
         ..Default::default()
     }
 }
+
+/// Options for ES module output, e.g. SFC templates bundled by build tools.
+/// Static hoisting is on since hoists live at module scope.
+pub fn module_compile_option(error_handler: RcErrHandle) -> CompileOption {
+    CompileOption {
+        mode: ScriptMode::Module {
+            runtime_module_name: "vue".into(),
+        },
+        hoist_static: true,
+        ..compile_option(error_handler)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::module_compile_option;
+    use crate::test::{compile, compile_with_option};
+    use compiler::error::NoopErrorHandler;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_builtin_transition() {
+        let (code, _) = compile("<Transition><p/></Transition>");
+        assert!(code.contains("_Transition"), "{}", code);
+        assert!(!code.contains("resolveComponent"), "{}", code);
+    }
+
+    #[test]
+    fn test_builtin_spellings() {
+        let cases = [
+            ("Transition", "transition", "_Transition"),
+            ("TransitionGroup", "transition-group", "_TransitionGroup"),
+        ];
+        for (pascal, kebab, helper) in cases {
+            for tag in [pascal, kebab] {
+                let (code, _) = compile(&format!("<{0}><p/></{0}>", tag));
+                let block = format!("_createBlock({},", helper);
+                assert!(code.contains(&block), "{}", code);
+                assert!(!code.contains("resolveComponent"), "{}", code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_component() {
+        let (code, _) = compile("<Foo/>");
+        let resolve = r#"const _component_Foo = _resolveComponent("Foo")"#;
+        assert!(code.contains(resolve), "{}", code);
+        assert!(code.contains("_createBlock(_component_Foo)"), "{}", code);
+    }
+
+    #[test]
+    fn test_v_cloak() {
+        let (code, _) = compile("<div v-cloak/>");
+        assert!(code.contains(r#"_createElementBlock("div")"#), "{}", code);
+        assert!(!code.contains("cloak"), "{}", code);
+        assert!(!code.contains("withDirectives"), "{}", code);
+    }
+
+    #[test]
+    fn test_module_hoist_static() {
+        let option = module_compile_option(Rc::new(NoopErrorHandler));
+        let code = compile_with_option("<div><p>static</p><p>{{ a }}</p></div>", option);
+        let hoisted =
+            r#"const _hoisted_1 = _createElementVNode("p", null, "static", -1 /*HOISTED*/)"#;
+        assert!(code.contains(hoisted), "{}", code);
+        assert!(code.contains("_hoisted_1,"), "{}", code);
+        let (code, _) = compile("<div><p>static</p><p>{{ a }}</p></div>");
+        assert!(!code.contains("_hoisted"), "{}", code);
+    }
+}
@@ -1,6 +1,7 @@
 mod stringify_static;
 mod warn_dom_usage;
 
+use stringify_static::StringifyStatic;
 use warn_dom_usage::UsageWarner;
 use compiler::transformer::{
     CorePass,
@@ -23,15 +24,22 @@ pub fn get_dom_pass<'a>(
     opt: &CompileOption,
 ) -> impl CorePass<BaseConvertInfo<'a>> {
     let prefix_identifier = opt.transforming().prefix_identifier;
+    // ssr renders static content into strings, hoisting is unnecessary
+    let hoist_static = opt.hoist_static && !opt.ssr;
     let shared = chain![
         SlotFlagMarker,
-        HoistStatic::new(opt.cache_handlers),
+        HoistStatic::new(opt.cache_handlers, hoist_static),
         ExpressionProcessor {
             prefix_identifier,
             sfc_info,
             err_handle: opt.error_handler.clone(),
         },
     ];
+    let threshold = if hoist_static {
+        opt.stringify_threshold
+    } else {
+        usize::MAX
+    };
     chain![
         PatchFlagMarker,
         UsageWarner(opt.error_handler.clone()),
@@ -43,5 +51,6 @@ pub fn get_dom_pass<'a>(
             shared_info: Scope::default(),
             pd: PhantomData,
         },
+        StringifyStatic::new(threshold),
     ]
 }
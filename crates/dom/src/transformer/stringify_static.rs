@@ -1 +1,263 @@
+//! Stringify large chunks of adjacent static nodes into one `createStaticVNode`.
+//! Runtime creates the nodes by setting innerHTML, which is much faster than
+//! creating and mounting each vnode when the static content is large enough.
+//! Attributes are serialized in source order and always double quoted.
+use crate::options::is_void_tag;
+use compiler::converter::{BaseConvertInfo, BaseIR, BaseRoot};
+use compiler::flags::RuntimeHelper as RH;
+use compiler::ir::{IRNode as IR, JsExpr as Js, Prop, StaticIR};
+use compiler::transformer::{BaseVNode, CorePass};
+use compiler::util::{escape_html, is_boolean_attr, is_reserved_prop};
 
+pub struct StringifyStatic {
+    /// minimum number of static elements in a stringified chunk
+    threshold: usize,
+    has_static: bool,
+}
+
+impl StringifyStatic {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            has_static: false,
+        }
+    }
+    fn stringify_children(&mut self, children: &mut Vec<BaseIR>) {
+        let mut i = 0;
+        while i < children.len() {
+            let mut end = i;
+            let mut count = 0;
+            while let Some(n) = children.get(end).and_then(node_count) {
+                count += n;
+                end += 1;
+            }
+            if count == 0 || count < self.threshold {
+                i = end.max(i + 1);
+                continue;
+            }
+            let mut content = String::new();
+            for child in children.drain(i..end) {
+                write_node(child, &mut content);
+            }
+            let count = end - i;
+            children.insert(i, IR::StaticCall(StaticIR { content, count }));
+            self.has_static = true;
+            i += 1;
+        }
+    }
+}
+
+// NB: this pass is the last in chain so its exit hooks run first.
+// Children are already finalized when their parent exits, and
+// HoistStatic can then hoist the stringified chunks.
+impl<'a> CorePass<BaseConvertInfo<'a>> for StringifyStatic {
+    fn exit_vnode(&mut self, vn: &mut BaseVNode<'a>) {
+        if vn.is_component || is_non_stringifiable(&vn.tag) {
+            return;
+        }
+        self.stringify_children(&mut vn.children);
+    }
+    fn exit_root(&mut self, r: &mut BaseRoot<'a>) {
+        // single root is a block and must stay a vnode
+        if r.is_fragment {
+            self.stringify_children(&mut r.body);
+        }
+        if self.has_static {
+            r.top_scope.helpers.collect(RH::CREATE_STATIC);
+        }
+    }
+}
+
+/// innerHTML does not work in these table elements
+fn is_non_stringifiable(tag: &Js) -> bool {
+    const TABLE_TAGS: &[&str] = &[
+        "caption", "thead", "tr", "th", "tbody", "td", "tfoot", "colgroup", "col",
+    ];
+    matches!(tag, Js::StrLit(t) if TABLE_TAGS.contains(&t.raw))
+}
+
+/// Returns the number of elements in a stringifiable node, or None.
+fn node_count(ir: &BaseIR) -> Option<usize> {
+    match ir {
+        IR::TextCall(t) => t
+            .texts
+            .iter()
+            .all(|t| matches!(t, Js::StrLit(_)))
+            .then(|| 0),
+        IR::CommentCall(_) => Some(0),
+        IR::VNodeCall(vn) => element_count(vn),
+        _ => None,
+    }
+}
+
+fn element_count(vn: &BaseVNode) -> Option<usize> {
+    if vn.is_component
+        || vn.is_block
+        || !vn.directives.is_empty()
+        || !vn.patch_flag.is_empty()
+        || !matches!(vn.tag, Js::StrLit(_))
+    {
+        return None;
+    }
+    let static_props = match &vn.props {
+        None => true,
+        Some(Js::Props(ps)) => ps.iter().all(is_static_attr),
+        Some(_) => false,
+    };
+    if !static_props {
+        return None;
+    }
+    vn.children
+        .iter()
+        .try_fold(1, |sum, child| Some(sum + node_count(child)?))
+}
+
+fn is_static_attr(prop: &Prop) -> bool {
+    match prop {
+        (Js::StrLit(k), Js::StrLit(_) | Js::Joined { quoted: true, .. }) => !is_reserved_prop(k),
+        (Js::StrLit(k), Js::Src("true")) => is_boolean_attr(k),
+        _ => false,
+    }
+}
+
+fn write_node(ir: BaseIR, out: &mut String) {
+    match ir {
+        IR::TextCall(t) => {
+            for text in t.texts {
+                if let Js::StrLit(s) = text {
+                    escape_html(&s.into_string(), out);
+                }
+            }
+        }
+        IR::CommentCall(c) => {
+            out.push_str("<!--");
+            out.push_str(c);
+            out.push_str("-->");
+        }
+        IR::VNodeCall(vn) => write_element(vn, out),
+        _ => panic!("only static node can be stringified"),
+    }
+}
+
+/// Text and attribute values are decoded first and escaped again
+/// so that innerHTML parses them back to the same content.
+fn write_element(vn: BaseVNode, out: &mut String) {
+    let tag = match vn.tag {
+        Js::StrLit(tag) => tag.raw,
+        _ => panic!("static element must have string tag"),
+    };
+    out.push('<');
+    out.push_str(tag);
+    if let Some(Js::Props(props)) = vn.props {
+        for prop in props {
+            match prop {
+                (Js::StrLit(k), Js::StrLit(v)) => {
+                    out.push(' ');
+                    out.push_str(k.raw);
+                    out.push_str("=\"");
+                    escape_html(&v.into_string(), out);
+                    out.push('"');
+                }
+                (Js::StrLit(k), Js::Joined { text, quoted: true }) => {
+                    out.push(' ');
+                    out.push_str(k.raw);
+                    out.push_str("=\"");
+                    escape_html(&text, out);
+                    out.push('"');
+                }
+                (Js::StrLit(k), _) => {
+                    out.push(' ');
+                    out.push_str(k.raw);
+                }
+                _ => panic!("static element must have static attrs"),
+            }
+        }
+    }
+    out.push('>');
+    if is_void_tag(tag) {
+        return;
+    }
+    for child in vn.children {
+        write_node(child, out);
+    }
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+#[cfg(test)]
+mod test {
+    use crate::compile_option;
+    use crate::test::compile_with_option;
+    use compiler::compiler::CompileOption;
+    use compiler::error::NoopErrorHandler;
+    use std::rc::Rc;
+
+    fn compile(s: &str) -> String {
+        let option = CompileOption {
+            hoist_static: true,
+            ..compile_option(Rc::new(NoopErrorHandler))
+        };
+        compile_with_option(s, option)
+    }
+
+    fn list(n: usize) -> String {
+        let items: String = (0..n)
+            .map(|i| format!("<li class='a'>{}</li>", i))
+            .collect();
+        format!("<ul>{}</ul>", items)
+    }
+
+    #[test]
+    fn test_stringify_static() {
+        let code = compile(&list(20));
+        let html = r#"<li class=\"a\">0</li><li class=\"a\">1</li>"#;
+        assert!(code.contains("_createStaticVNode(\"<li"), "{}", code);
+        assert!(code.contains(html), "{}", code);
+        assert!(
+            code.contains("<li class=\\\"a\\\">19</li>\", 20)"),
+            "{}",
+            code
+        );
+        assert_eq!(code.matches("_createStaticVNode(").count(), 1, "{}", code);
+        assert!(
+            code.contains("_hoisted_1 = _createStaticVNode("),
+            "{}",
+            code
+        );
+        assert!(!code.contains("_createElementVNode(\"li\""), "{}", code);
+    }
+
+    #[test]
+    fn test_below_threshold() {
+        let code = compile(&list(19));
+        assert!(!code.contains("createStaticVNode"), "{}", code);
+        assert!(code.contains("_createElementVNode(\"li\""), "{}", code);
+    }
+
+    #[test]
+    fn test_dynamic_breaks_chunk() {
+        let items = list(20).replace("<li class='a'>10</li>", "<li :id='a'>10</li>");
+        let code = compile(&items);
+        assert!(!code.contains("createStaticVNode"), "{}", code);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        let items = list(20).replace(
+            "<li class='a'>10</li>",
+            "<li title='a<b &amp; \"c\"'>1 &lt; 2 &amp; 3 > 0</li>",
+        );
+        let code = compile(&items);
+        let attr = r#"title=\"a&lt;b &amp; &quot;c&quot;\""#;
+        assert!(code.contains(attr), "{}", code);
+        assert!(code.contains("1 &lt; 2 &amp; 3 &gt; 0"), "{}", code);
+    }
+
+    #[test]
+    fn test_no_hoist_static() {
+        let option = compile_option(Rc::new(NoopErrorHandler));
+        let code = compile_with_option(&list(20), option);
+        assert!(!code.contains("createStaticVNode"), "{}", code);
+    }
+}
@@ -11,11 +11,18 @@ impl<'a> CorePass<BaseInfo<'a>> for UsageWarner {
     fn enter_vnode(&mut self, vn: &mut BaseVNode<'a>) {
         match vn.tag {
             Js::Symbol(dom_helper::TRANSITION) => {
-                if has_multiple_children(&vn.children) != Multiplicity::Multi {
-                    return;
+                match has_multiple_children(&vn.children) {
+                    Multiplicity::Multi => {
+                        let error = CE::extended(DomError::TransitionInvalidChildren);
+                        self.0.on_error(error);
+                    }
+                    // slot may render one child, so it is only a warning
+                    Multiplicity::Maybe => {
+                        let warning = CE::extended(DomError::TransitionSlotChildren);
+                        self.0.on_warn(warning);
+                    }
+                    _ => {}
                 }
-                let error = CE::extended(DomError::TransitionInvalidChildren);
-                self.0.on_error(error);
             }
             Js::StrLit(s) if ["script", "style"].contains(&s.raw) => {
                 let error = CE::extended(DomError::IgnoredSideEffectTag);
@@ -30,6 +37,8 @@ impl<'a> CorePass<BaseInfo<'a>> for UsageWarner {
 enum Multiplicity {
     Zero,
     One,
+    /// slot outlet may render any number of nodes
+    Maybe,
     Multi,
 }
 
@@ -39,15 +48,12 @@ fn has_multiple_children(children: &[BaseIR]) -> Multiplicity {
     for child in children.iter() {
         match ir_multilicity(child) {
             Zero => (),
-            One => {
-                if multi == One {
+            Multi => return Multi,
+            m => {
+                if multi != Zero {
                     return Multi;
-                } else {
-                    multi = One;
                 }
-            }
-            Multi => {
-                return Multi;
+                multi = m;
             }
         }
     }
@@ -86,7 +92,7 @@ fn ir_multilicity(ir: &BaseIR) -> Multiplicity {
                 One
             }
         }
-        IRNode::RenderSlotCall(..) => One, // be lenient
+        IRNode::RenderSlotCall(..) => Maybe,
         IRNode::VNodeCall(vn) => {
             if let Js::Symbol(RuntimeHelper::FRAGMENT) = vn.tag {
                 has_multiple_children(&vn.children)
@@ -94,6 +100,64 @@ fn ir_multilicity(ir: &BaseIR) -> Multiplicity {
                 One
             }
         }
+        IRNode::StaticCall(s) => {
+            if s.count > 1 {
+                Multi
+            } else {
+                One
+            }
+        }
+        IRNode::Hoisted(..) => One,
         IRNode::AlterableSlot(..) => panic!("impossible"),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::test::compile_with_handler;
+
+    fn compile_errors(s: &str) -> Vec<&'static str> {
+        let (_, eh) = compile_with_handler(s);
+        let errors = eh.errors();
+        errors.iter().map(|e| e.msg()).collect()
+    }
+
+    fn compile_warnings(s: &str) -> Vec<&'static str> {
+        let (_, eh) = compile_with_handler(s);
+        let warnings = eh.warnings();
+        warnings.iter().map(|e| e.msg()).collect()
+    }
+
+    const INVALID: &str = "<Transition> expects exactly one child element or component.";
+    const SLOT: &str = "<Transition> may receive multiple children from <slot> at runtime.";
+
+    #[test]
+    fn test_transition_multiple_children() {
+        let errors = compile_errors("<Transition><p/><p/></Transition>");
+        assert_eq!(errors, vec![INVALID]);
+        let errors = compile_errors("<Transition><p v-for='a in b'/></Transition>");
+        assert_eq!(errors, vec![INVALID]);
+    }
+
+    #[test]
+    fn test_transition_ignore_whitespace_comment() {
+        let errors = compile_errors("<Transition> <p/> <!--c--> </Transition>");
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_transition_v_if() {
+        let errors = compile_errors("<Transition><p v-if='a'/><p v-else/></Transition>");
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_transition_slot() {
+        let errors = compile_errors("<Transition><slot/></Transition>");
+        assert!(errors.is_empty(), "{:?}", errors);
+        let warnings = compile_warnings("<Transition><slot/></Transition>");
+        assert_eq!(warnings, vec![SLOT]);
+        let errors = compile_errors("<Transition><slot/><p/></Transition>");
+        assert_eq!(errors, vec![INVALID]);
+    }
+}